@@ -1,9 +1,15 @@
+#[path = "manifest.rs"]
+mod manifest;
+
 use sea_orm_codegen::{
     DateTimeCrate, EntityTransformer, EntityWriterContext, OutputFile, WithSerde,
 };
-use sea_schema::mysql::discovery::SchemaDiscovery;
+use sea_schema::mysql::{
+    def::{TableDef, Type},
+    discovery::SchemaDiscovery,
+};
 use sqlx::{MySql, Pool};
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 use tokio::{
     fs::{create_dir_all, File},
     io::AsyncWriteExt,
@@ -15,7 +21,26 @@ struct Table<'a> {
     columns: &'a [&'a str],
 }
 
+/// The environment variable naming a JSON manifest of the tables and columns to generate, in place of [`TABLES_SPECS`]
+const MODELS_MANIFEST_ENV: &str = "MODELS_MANIFEST";
+
 const TABLES_SPECS: &[&Table] = &[
+    &Table {
+        name: "Proposal",
+        columns: &["proposalId", "proposalCode", "proposalNumber"],
+    },
+    &Table {
+        name: "BLSession",
+        columns: &["sessionId", "proposalId", "visit_number"],
+    },
+    &Table {
+        name: "DataCollectionGroup",
+        columns: &["dataCollectionGroupId", "sessionId"],
+    },
+    &Table {
+        name: "DataCollection",
+        columns: &["dataCollectionId", "dataCollectionGroupId"],
+    },
     &Table {
         name: "AutoProcProgramAttachment",
         columns: &[
@@ -26,6 +51,15 @@ const TABLES_SPECS: &[&Table] = &[
             "filePath",
         ],
     },
+    &Table {
+        name: "DataCollectionFileAttachment",
+        columns: &[
+            "dataCollectionFileAttachmentId",
+            "dataCollectionId",
+            "fileFullPath",
+            "fileType",
+        ],
+    },
     &Table {
         name: "ProcessingJob",
         columns: &[
@@ -106,6 +140,47 @@ const TABLES_SPECS: &[&Table] = &[
     },
 ];
 
+/// Reads the tables and columns to generate, from the manifest named by [`MODELS_MANIFEST_ENV`] if set or else from
+/// [`TABLES_SPECS`]
+///
+/// The manifest is parsed by [`manifest::parse`].
+fn table_specs() -> HashMap<String, Vec<String>> {
+    let Ok(path) = std::env::var(MODELS_MANIFEST_ENV) else {
+        return TABLES_SPECS
+            .iter()
+            .map(|spec| {
+                (
+                    spec.name.to_string(),
+                    spec.columns
+                        .iter()
+                        .map(|column| column.to_string())
+                        .collect(),
+                )
+            })
+            .collect();
+    };
+    manifest::read(&path)
+}
+
+/// Generates enum columns as strings where an earlier table, by name, has an enum column of the same name
+///
+/// The generated active enums are named after their column, such that a second enum column of the same name would
+/// replace the variants of the first.
+fn demote_shared_enums(tables: &mut [TableDef]) {
+    let mut enum_columns = Vec::new();
+    for table in tables.iter_mut() {
+        for column in table.columns.iter_mut() {
+            if let Type::Enum(def) = &column.col_type {
+                if enum_columns.contains(&column.name) {
+                    column.col_type = Type::Varchar(def.attr.clone());
+                } else {
+                    enum_columns.push(column.name.clone());
+                }
+            }
+        }
+    }
+}
+
 fn main() {
     tokio::runtime::Builder::new_current_thread()
         .enable_time()
@@ -121,25 +196,47 @@ fn main() {
             let connection = Pool::<MySql>::connect(database_url.as_str()).await.unwrap();
             let schema_discovery = SchemaDiscovery::new(connection, database_name);
             let schema = schema_discovery.discover().await.unwrap();
-            let table_statements = schema
+            let specs = table_specs();
+            let mismatches = specs
+                .iter()
+                .flat_map(|(table, columns)| {
+                    match schema.tables.iter().find(|def| &def.info.name == table) {
+                        Some(def) => columns
+                            .iter()
+                            .filter(|column| {
+                                !def.columns
+                                    .iter()
+                                    .any(|discovered| &discovered.name == *column)
+                            })
+                            .map(|column| format!("column {table}.{column} does not exist"))
+                            .collect(),
+                        None => vec![format!("table {table} does not exist")],
+                    }
+                })
+                .collect::<Vec<_>>();
+            if !mismatches.is_empty() {
+                panic!(
+                    "The models specification does not match database {database_name}: {}",
+                    mismatches.join(", ")
+                );
+            }
+            let mut tables = schema
                 .tables
                 .into_iter()
                 .filter_map(|mut def| {
-                    if let Some(spec) = TABLES_SPECS.iter().find(|spec| spec.name == def.info.name)
-                    {
-                        def.foreign_keys.retain(|fk| {
-                            TABLES_SPECS
-                                .iter()
-                                .any(|spec| spec.name == fk.referenced_table)
-                        });
-                        def.columns
-                            .retain(|column| spec.columns.contains(&column.name.as_str()));
-                        Some(def.write())
+                    if let Some(columns) = specs.get(&def.info.name) {
+                        def.foreign_keys
+                            .retain(|fk| specs.contains_key(&fk.referenced_table));
+                        def.columns.retain(|column| columns.contains(&column.name));
+                        Some(def)
                     } else {
                         None
                     }
                 })
-                .collect();
+                .collect::<Vec<_>>();
+            tables.sort_by(|a, b| a.info.name.cmp(&b.info.name));
+            demote_shared_enums(&mut tables);
+            let table_statements = tables.into_iter().map(|def| def.write()).collect();
 
             let writer_context = EntityWriterContext::new(
                 false,
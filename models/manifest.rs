@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+
+/// Parses a manifest, a JSON object mapping table names to arrays of column names, e.g.
+/// `{"Proposal": ["proposalId", "proposalCode"]}`
+pub fn parse(manifest: &str) -> Result<HashMap<String, Vec<String>>, serde_json::Error> {
+    serde_json::from_str(manifest)
+}
+
+/// Reads and parses the manifest at the path, panicking with the path and the cause if it cannot be read or is
+/// malformed
+pub fn read(path: &str) -> HashMap<String, Vec<String>> {
+    let manifest = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Could not read models manifest {path}: {err}"));
+    parse(&manifest).unwrap_or_else(|err| {
+        panic!("Models manifest {path} must be an object mapping table names to arrays of column names: {err}")
+    })
+}
@@ -0,0 +1,55 @@
+#[path = "../manifest.rs"]
+mod manifest;
+
+use std::collections::HashMap;
+
+/// The path of a file in the fixtures directory
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn sample_manifest_lists_the_columns_of_each_table() {
+    assert_eq!(
+        manifest::read(&fixture("manifest.json")),
+        HashMap::from([
+            (
+                "Proposal".to_string(),
+                vec![
+                    "proposalId".to_string(),
+                    "proposalCode".to_string(),
+                    "proposalNumber".to_string()
+                ]
+            ),
+            (
+                "BLSession".to_string(),
+                vec![
+                    "sessionId".to_string(),
+                    "proposalId".to_string(),
+                    "visit_number".to_string()
+                ]
+            ),
+        ])
+    );
+}
+
+#[test]
+fn manifest_must_be_an_object_of_string_arrays() {
+    assert!(manifest::parse(r#"["Proposal"]"#).is_err());
+    assert!(manifest::parse(r#"{"Proposal": "proposalId"}"#).is_err());
+    assert!(manifest::parse(r#"{"Proposal": [1, 2]}"#).is_err());
+    assert!(manifest::parse(r#"{"Proposal": ["proposalId"]"#).is_err());
+}
+
+#[test]
+fn empty_manifest_generates_no_tables() {
+    assert!(manifest::parse("{}").unwrap().is_empty());
+}
+
+#[test]
+#[should_panic(
+    expected = "tests/fixtures/malformed_manifest.json must be an object mapping table names"
+)]
+fn malformed_manifest_panics_with_its_path() {
+    manifest::read(&fixture("malformed_manifest.json"));
+}
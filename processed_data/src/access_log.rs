@@ -0,0 +1,159 @@
+use axum::{
+    extract::Request,
+    http::{header::HeaderName, HeaderValue},
+    response::Response,
+    Router,
+};
+use std::time::Duration;
+use tower_http::{
+    trace::{DefaultOnResponse, OnResponse, TraceLayer},
+    LatencyUnit,
+};
+use tracing::{field::Empty, Level, Span};
+
+/// The target of access log events, such that they can be filtered independently of other events
+const ACCESS_LOG_TARGET: &str = "processed_data::access_log";
+
+/// The response header, set by [`axum_tracing_opentelemetry::middleware::OtelInResponseLayer`], carrying the trace context of the request
+const TRACEPARENT_HEADER: HeaderName = HeaderName::from_static("traceparent");
+
+/// Creates the span of a request, carrying its method and path, at the level
+fn make_span(request: &Request, level: Level) -> Span {
+    macro_rules! access_span {
+        ($level:expr) => {
+            tracing::span!(
+                target: ACCESS_LOG_TARGET,
+                $level,
+                "access_log",
+                method = %request.method(),
+                path = %request.uri().path(),
+                request_id = Empty,
+            )
+        };
+    }
+    match level {
+        Level::ERROR => access_span!(Level::ERROR),
+        Level::WARN => access_span!(Level::WARN),
+        Level::INFO => access_span!(Level::INFO),
+        Level::DEBUG => access_span!(Level::DEBUG),
+        Level::TRACE => access_span!(Level::TRACE),
+    }
+}
+
+/// Extracts the trace ID from a `traceparent` header, of the form `{version}-{trace_id}-{parent_id}-{flags}`
+fn trace_id(traceparent: &HeaderValue) -> Option<&str> {
+    traceparent.to_str().ok()?.split('-').nth(1)
+}
+
+/// Emits an access log line, with the method, path, status, duration and request ID, at the level upon responding to each request
+///
+/// Requests are not logged on arrival, nor are failures logged separately, to avoid duplicating the GraphQL operation
+/// level tracing. The request ID is the trace ID of the request, so the layer must wrap the OpenTelemetry layers.
+pub fn with_access_log(router: Router, level: Level) -> Router {
+    router.layer(
+        TraceLayer::new_for_http()
+            .make_span_with(move |request: &Request| make_span(request, level))
+            .on_request(())
+            .on_response(move |response: &Response, latency: Duration, span: &Span| {
+                if let Some(request_id) = response
+                    .headers()
+                    .get(TRACEPARENT_HEADER)
+                    .and_then(trace_id)
+                {
+                    span.record("request_id", request_id);
+                }
+                DefaultOnResponse::new()
+                    .level(level)
+                    .latency_unit(LatencyUnit::Millis)
+                    .on_response(response, latency, span)
+            })
+            .on_failure(()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+    use tower::ServiceExt;
+    use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, Layer};
+
+    /// A buffer into which log lines are written, shared between the subscriber and the test
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Requests the path from a router with access logging at the level, returning the lines logged at or above `INFO`
+    async fn access_log_lines(level: Level, path: &str) -> Vec<String> {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::Registry::default().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone())
+                .with_filter(LevelFilter::INFO),
+        );
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let router = with_access_log(
+            Router::new().route("/graphql", get(|| async { "ok" })),
+            level,
+        );
+
+        router
+            .oneshot(axum::http::Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        output.lines().map(String::from).collect()
+    }
+
+    #[tokio::test]
+    async fn a_line_is_logged_for_each_request() {
+        let lines = access_log_lines(Level::INFO, "/graphql").await;
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        for field in ["method=GET", "path=/graphql", "status=200", "latency="] {
+            assert!(
+                lines[0].contains(field),
+                "{field} missing from {}",
+                lines[0]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn status_of_unrouted_requests_is_logged() {
+        let lines = access_log_lines(Level::INFO, "/missing").await;
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        assert!(lines[0].contains("status=404"), "{}", lines[0]);
+    }
+
+    #[tokio::test]
+    async fn lines_below_the_filtered_level_are_not_logged() {
+        assert!(access_log_lines(Level::DEBUG, "/graphql").await.is_empty());
+    }
+
+    #[test]
+    fn trace_id_is_extracted_from_the_traceparent() {
+        assert_eq!(
+            trace_id(&HeaderValue::from_static(
+                "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"
+            )),
+            Some("0af7651916cd43dd8448eb211c80319c")
+        );
+        assert_eq!(trace_id(&HeaderValue::from_static("malformed")), None);
+    }
+}
@@ -0,0 +1,233 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{post, put},
+    Json, Router,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use tracing_subscriber::{filter::LevelFilter, reload, Registry};
+
+use crate::{graphql::MaintenanceMode, AdminToken};
+
+/// The MAC through which bearer tokens are compared with the admin token
+type AdminTokenMac = Hmac<Sha256>;
+
+/// A handle with which the level of logs emitted can be changed at runtime
+pub type LogLevelHandle = reload::Handle<LevelFilter, Registry>;
+
+/// Shared state of the administrative endpoints
+#[derive(Debug, Clone)]
+struct AdminState {
+    /// The bearer token required to access the endpoints
+    token: AdminToken,
+    /// The maintenance mode switch shared with the GraphQL schema
+    maintenance_mode: MaintenanceMode,
+    /// The handle with which the log level is changed
+    log_level: LogLevelHandle,
+}
+
+impl AdminState {
+    /// Computes the MAC of the token, keyed by the admin token
+    fn mac(&self, token: &str) -> AdminTokenMac {
+        let mut mac = AdminTokenMac::new_from_slice(self.token.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(token.as_bytes());
+        mac
+    }
+
+    /// Returns true if the supplied authorization carries the admin token
+    ///
+    /// The MACs of the tokens are compared in constant time, such that the time taken reveals nothing of the admin token
+    fn is_authorized(&self, authorization: Option<TypedHeader<Authorization<Bearer>>>) -> bool {
+        authorization.is_some_and(|TypedHeader(authorization)| {
+            self.mac(authorization.token())
+                .verify_slice(&self.mac(&self.token).finalize().into_bytes())
+                .is_ok()
+        })
+    }
+}
+
+/// Creates an [`axum::Router`] serving the administrative endpoints, guarded by the admin token
+pub fn admin_router(
+    token: AdminToken,
+    maintenance_mode: MaintenanceMode,
+    log_level: LogLevelHandle,
+) -> Router {
+    Router::new()
+        .route(
+            "/admin/maintenance",
+            put(enable_maintenance_mode).delete(disable_maintenance_mode),
+        )
+        .route("/admin/log-level", post(set_log_level))
+        .with_state(AdminState {
+            token,
+            maintenance_mode,
+            log_level,
+        })
+}
+
+/// Enables maintenance mode, causing GraphQL operations to be rejected
+async fn enable_maintenance_mode(
+    State(state): State<AdminState>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> StatusCode {
+    if !state.is_authorized(authorization) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    state.maintenance_mode.set_enabled(true);
+    StatusCode::NO_CONTENT
+}
+
+/// Disables maintenance mode, allowing GraphQL operations to be executed
+async fn disable_maintenance_mode(
+    State(state): State<AdminState>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> StatusCode {
+    if !state.is_authorized(authorization) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    state.maintenance_mode.set_enabled(false);
+    StatusCode::NO_CONTENT
+}
+
+/// The body of a request to change the log level
+#[derive(Debug, Deserialize)]
+struct LogLevelRequest {
+    /// The level of logs to emit, e.g. `debug`
+    level: String,
+}
+
+/// Changes the level of logs written to stdout, spans exported to OpenTelemetry remain filtered at the level set at startup
+async fn set_log_level(
+    State(state): State<AdminState>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    Json(request): Json<LogLevelRequest>,
+) -> StatusCode {
+    if !state.is_authorized(authorization) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    let Ok(level) = request.level.parse::<LevelFilter>() else {
+        return StatusCode::BAD_REQUEST;
+    };
+    match state.log_level.reload(level) {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::header::AUTHORIZATION};
+    use std::time::Duration;
+    use tower::ServiceExt;
+    use tracing::subscriber::DefaultGuard;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// The bearer token accepted by the router under test
+    const TOKEN: &str = "admin token";
+
+    /// A router guarded by [`TOKEN`] and the handle to the log level it changes, which filters the
+    /// logs of the current thread for as long as the returned guard is held
+    fn router() -> (Router, LogLevelHandle, DefaultGuard) {
+        let (level_filter, log_level) = reload::Layer::new(LevelFilter::INFO);
+        let guard = tracing::subscriber::set_default(Registry::default().with(level_filter));
+        let router = admin_router(
+            TOKEN.parse().unwrap(),
+            MaintenanceMode::new(false, Duration::from_secs(60)),
+            log_level.clone(),
+        );
+        (router, log_level, guard)
+    }
+
+    /// Sends the request to the router, returning the status of the response
+    async fn send(router: Router, request: axum::http::request::Builder, body: Body) -> StatusCode {
+        router
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    /// A request to change the log level, optionally authorized by `token`
+    fn log_level_request(token: Option<&str>) -> axum::http::request::Builder {
+        let request = axum::http::Request::post("/admin/log-level")
+            .header("content-type", "application/json");
+        match token {
+            Some(token) => request.header(AUTHORIZATION, format!("Bearer {token}")),
+            None => request,
+        }
+    }
+
+    #[tokio::test]
+    async fn log_level_changes_effective_filtering() {
+        let (router, _, _guard) = router();
+        assert!(!tracing::enabled!(tracing::Level::DEBUG));
+        assert!(tracing::enabled!(tracing::Level::INFO));
+
+        let status = send(
+            router.clone(),
+            log_level_request(Some(TOKEN)),
+            Body::from(r#"{"level":"debug"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(tracing::enabled!(tracing::Level::DEBUG));
+
+        let status = send(
+            router,
+            log_level_request(Some(TOKEN)),
+            Body::from(r#"{"level":"warn"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(!tracing::enabled!(tracing::Level::INFO));
+        assert!(tracing::enabled!(tracing::Level::WARN));
+    }
+
+    #[tokio::test]
+    async fn log_level_is_unchanged_without_the_admin_token() {
+        let (router, log_level, _guard) = router();
+        for token in [
+            None,
+            Some("wrong token"),
+            Some("admin"),
+            Some("admin tokens"),
+            Some("admin tokeN"),
+        ] {
+            let status = send(
+                router.clone(),
+                log_level_request(token),
+                Body::from(r#"{"level":"trace"}"#),
+            )
+            .await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(
+            log_level.with_current(|level| *level).unwrap(),
+            LevelFilter::INFO
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_log_level_is_rejected() {
+        let (router, log_level, _guard) = router();
+        let status = send(
+            router,
+            log_level_request(Some(TOKEN)),
+            Body::from(r#"{"level":"verbose"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            log_level.with_current(|level| *level).unwrap(),
+            LevelFilter::INFO
+        );
+    }
+}
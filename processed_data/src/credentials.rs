@@ -0,0 +1,187 @@
+use aws_credential_types::{
+    provider::{self, error::CredentialsError, future, ProvideCredentials},
+    Credentials,
+};
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+/// Provides S3 credentials read from files, such as a mounted secret, which are re-read once `refresh_interval` has elapsed so that externally rotated keys are picked up
+#[derive(Debug, Clone)]
+pub struct FileCredentialsProvider {
+    /// The file containing the ID of the access key
+    access_key_id_file: PathBuf,
+    /// The file containing the secret access key
+    secret_access_key_file: PathBuf,
+    /// The period after which the files are re-read
+    refresh_interval: Duration,
+}
+
+impl FileCredentialsProvider {
+    /// Creates a provider reading the access key ID and secret access key from the supplied files
+    pub fn new(
+        access_key_id_file: PathBuf,
+        secret_access_key_file: PathBuf,
+        refresh_interval: Duration,
+    ) -> Self {
+        Self {
+            access_key_id_file,
+            secret_access_key_file,
+            refresh_interval,
+        }
+    }
+
+    /// Reads the credentials, marking them as expiring once the refresh interval has elapsed such that the SDK credentials cache re-reads them
+    fn load(&self) -> provider::Result {
+        let access_key_id = std::fs::read_to_string(&self.access_key_id_file)
+            .map_err(CredentialsError::provider_error)?;
+        let secret_access_key = std::fs::read_to_string(&self.secret_access_key_file)
+            .map_err(CredentialsError::provider_error)?;
+        Ok(Credentials::new(
+            access_key_id.trim(),
+            secret_access_key.trim(),
+            None,
+            Some(SystemTime::now() + self.refresh_interval),
+            "FileCredentialsProvider",
+        ))
+    }
+}
+
+impl ProvideCredentials for FileCredentialsProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::ready(self.load())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presigner::{PresignExpiry, Presigner};
+    use aws_credential_types::provider::SharedCredentialsProvider;
+    use aws_sdk_s3::{config::Region, Client};
+
+    /// A pair of credential files in a directory unique to the test
+    struct CredentialFiles {
+        /// The file containing the ID of the access key
+        access_key_id: PathBuf,
+        /// The file containing the secret access key
+        secret_access_key: PathBuf,
+    }
+
+    impl CredentialFiles {
+        /// Creates the files, containing the credentials, in a directory named after the test
+        fn new(test: &str, access_key_id: &str, secret_access_key: &str) -> Self {
+            let directory =
+                std::env::temp_dir().join(format!("processed_data-{}-{test}", std::process::id()));
+            std::fs::create_dir_all(&directory).unwrap();
+            let files = Self {
+                access_key_id: directory.join("access_key_id"),
+                secret_access_key: directory.join("secret_access_key"),
+            };
+            files.write(access_key_id, secret_access_key);
+            files
+        }
+
+        /// Replaces the credentials in the files, as an external rotation would
+        fn write(&self, access_key_id: &str, secret_access_key: &str) {
+            std::fs::write(&self.access_key_id, format!("{access_key_id}\n")).unwrap();
+            std::fs::write(&self.secret_access_key, format!("{secret_access_key}\n")).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn rotated_credentials_are_read_from_the_files() {
+        let files = CredentialFiles::new("rotation", "first-key", "first-secret");
+        let provider = FileCredentialsProvider::new(
+            files.access_key_id.clone(),
+            files.secret_access_key.clone(),
+            Duration::from_secs(300),
+        );
+
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "first-key");
+        assert_eq!(credentials.secret_access_key(), "first-secret");
+
+        files.write("second-key", "second-secret");
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "second-key");
+        assert_eq!(credentials.secret_access_key(), "second-secret");
+    }
+
+    #[tokio::test]
+    async fn credentials_expire_after_the_refresh_interval() {
+        let files = CredentialFiles::new("expiry", "key", "secret");
+        let provider = FileCredentialsProvider::new(
+            files.access_key_id.clone(),
+            files.secret_access_key.clone(),
+            Duration::from_secs(300),
+        );
+
+        let before = SystemTime::now();
+        let credentials = provider.provide_credentials().await.unwrap();
+        let expiry = credentials.expiry().unwrap();
+        assert!(expiry >= before + Duration::from_secs(300));
+        assert!(expiry <= SystemTime::now() + Duration::from_secs(300));
+    }
+
+    #[tokio::test]
+    async fn missing_files_are_a_provider_error() {
+        let provider = FileCredentialsProvider::new(
+            PathBuf::from("/nonexistent/access_key_id"),
+            PathBuf::from("/nonexistent/secret_access_key"),
+            Duration::from_secs(300),
+        );
+        assert!(matches!(
+            provider.provide_credentials().await,
+            Err(CredentialsError::ProviderError(_))
+        ));
+    }
+
+    /// The access key ID with which the client signs a URL for an object
+    async fn signing_key_id(client: &Client) -> String {
+        let url = client
+            .presign_get_object(
+                "bucket",
+                "key",
+                None,
+                None,
+                None,
+                None,
+                PresignExpiry::new(Duration::from_secs(60), Duration::ZERO),
+            )
+            .await
+            .unwrap();
+        let (_, credential) = url
+            .query_pairs()
+            .find(|(name, _)| name == "X-Amz-Credential")
+            .unwrap();
+        credential.split('/').next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn rotated_credentials_are_used_by_subsequent_presigns() {
+        let files = CredentialFiles::new("presign", "first-key", "first-secret");
+        let client = Client::from_conf(
+            aws_sdk_s3::config::Builder::new()
+                .credentials_provider(SharedCredentialsProvider::new(
+                    FileCredentialsProvider::new(
+                        files.access_key_id.clone(),
+                        files.secret_access_key.clone(),
+                        Duration::ZERO,
+                    ),
+                ))
+                .endpoint_url("http://s3.test")
+                .force_path_style(true)
+                .region(Region::new("us-east-1"))
+                .build(),
+        );
+
+        assert_eq!(signing_key_id(&client).await, "first-key");
+        files.write("second-key", "second-secret");
+        assert_eq!(signing_key_id(&client).await, "second-key");
+    }
+}
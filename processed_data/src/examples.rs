@@ -0,0 +1,73 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    Executor, Request, Response, ServerError, Variables,
+};
+use std::sync::Arc;
+
+/// Example GraphQL documents, as `(name, document)`
+pub const EXAMPLES: &[(&str, &str)] = &[
+    (
+        "processing_jobs",
+        include_str!("examples/processing_jobs.graphql"),
+    ),
+    (
+        "auto_processing_statistics",
+        include_str!("examples/auto_processing_statistics.graphql"),
+    ),
+    (
+        "download_urls",
+        include_str!("examples/download_urls.graphql"),
+    ),
+];
+
+/// Variables with which each of the [`EXAMPLES`] can be run
+pub const EXAMPLE_VARIABLES: &str =
+    r#"{ "representations": [{ "__typename": "DataCollection", "id": 1 }] }"#;
+
+/// An [`ExtensionFactory`] which skips the execution of operations, such that only parsing and validation take place
+#[derive(Debug, Default)]
+pub struct ValidationOnly;
+
+impl ExtensionFactory for ValidationOnly {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ValidationOnlyExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`ValidationOnly`]
+struct ValidationOnlyExtension;
+
+#[async_trait::async_trait]
+impl Extension for ValidationOnlyExtension {
+    async fn execute(
+        &self,
+        _ctx: &ExtensionContext<'_>,
+        _operation_name: Option<&str>,
+        _next: NextExecute<'_>,
+    ) -> Response {
+        Response::default()
+    }
+}
+
+/// Validates each of the [`EXAMPLES`] against the schema, which must include the [`ValidationOnly`] extension
+pub async fn validate_examples(
+    executor: &impl Executor,
+) -> Result<(), Vec<(&'static str, Vec<ServerError>)>> {
+    let variables = Variables::from_json(
+        serde_json::from_str(EXAMPLE_VARIABLES).expect("Example variables are valid JSON"),
+    );
+    let mut failures = Vec::new();
+    for (name, document) in EXAMPLES {
+        let response = executor
+            .execute(Request::new(*document).variables(variables.clone()))
+            .await;
+        if response.is_err() {
+            failures.push((*name, response.errors));
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures)
+    }
+}
@@ -0,0 +1,120 @@
+use async_graphql::ErrorExtensions;
+use std::{fmt, str::FromStr};
+
+/// A range of the bytes of an object, as in an HTTP `Range` header of the form `bytes=start-end`, `bytes=start-` or `bytes=-length`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteRange {
+    /// The bytes from `start` to `end`, inclusive
+    Bounded {
+        /// The offset of the first byte
+        start: u64,
+        /// The offset of the last byte
+        end: u64,
+    },
+    /// The bytes from `start` to the end of the object
+    From {
+        /// The offset of the first byte
+        start: u64,
+    },
+    /// The final `length` bytes of the object
+    Suffix {
+        /// The number of bytes
+        length: u64,
+    },
+}
+
+impl FromStr for ByteRange {
+    type Err = async_graphql::Error;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            async_graphql::Error::new(format!("Invalid byte range {range}"))
+                .extend_with(|_, extensions| extensions.set("code", "BAD_USER_INPUT"))
+        };
+        let parse = |offset: &str| {
+            if offset.is_empty() || !offset.chars().all(|character| character.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            offset.parse::<u64>().map_err(|_| invalid())
+        };
+        let (start, end) = range
+            .strip_prefix("bytes=")
+            .and_then(|range| range.split_once('-'))
+            .ok_or_else(invalid)?;
+        match (start, end) {
+            ("", length) => match parse(length)? {
+                0 => Err(invalid()),
+                length => Ok(Self::Suffix { length }),
+            },
+            (start, "") => Ok(Self::From {
+                start: parse(start)?,
+            }),
+            (start, end) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(invalid());
+                }
+                Ok(Self::Bounded { start, end })
+            }
+        }
+    }
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bounded { start, end } => write!(f, "bytes={start}-{end}"),
+            Self::From { start } => write!(f, "bytes={start}-"),
+            Self::Suffix { length } => write!(f, "bytes=-{length}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_ranges_are_parsed_and_formatted() {
+        for (range, expected) in [
+            (
+                "bytes=0-1023",
+                ByteRange::Bounded {
+                    start: 0,
+                    end: 1023,
+                },
+            ),
+            ("bytes=7-7", ByteRange::Bounded { start: 7, end: 7 }),
+            ("bytes=1024-", ByteRange::From { start: 1024 }),
+            ("bytes=-500", ByteRange::Suffix { length: 500 }),
+        ] {
+            let parsed = range.parse::<ByteRange>().unwrap();
+            assert_eq!(parsed, expected);
+            assert_eq!(parsed.to_string(), range);
+        }
+    }
+
+    #[test]
+    fn malformed_ranges_are_rejected_as_bad_user_input() {
+        for range in [
+            "",
+            "0-1023",
+            "bytes=",
+            "bytes=-",
+            "bytes=-0",
+            "bytes=10-5",
+            "bytes=a-b",
+            "bytes=+1-2",
+            "bytes=0-1,4-5",
+            "items=0-1023",
+            "bytes=99999999999999999999-",
+        ] {
+            let error = range.parse::<ByteRange>().unwrap_err();
+            assert_eq!(
+                error.extensions.unwrap().get("code"),
+                Some(&async_graphql::Value::from("BAD_USER_INPUT")),
+                "{range}"
+            );
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use super::METRICS_TARGET;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, ExecResult, QueryResult, Statement,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// The message of the error with which queries are rejected whilst the circuit is open
+const CIRCUIT_OPEN_MESSAGE: &str = "The database is unavailable, queries are suspended";
+
+/// The state of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakerState {
+    /// Queries are passed to the database, counting the failures since the last success
+    Closed {
+        /// The number of queries which have failed in a row
+        consecutive_failures: u32,
+    },
+    /// Queries are rejected without reaching the database until the cooldown elapses
+    Open {
+        /// The time at which the cooldown elapses
+        until: Instant,
+    },
+    /// A single probe query has been passed to the database, whilst all others are rejected
+    ///
+    /// Another probe is permitted if the cooldown elapses without an outcome, as when the probe was cancelled.
+    HalfOpen {
+        /// The time after which another probe is permitted
+        until: Instant,
+    },
+}
+
+/// A circuit breaker which, after a number of consecutive database failures, rejects queries for a cooldown period
+/// before passing a single probe query to test whether the database has recovered
+///
+/// The breaker is shared by all requests, such that failures seen by one request protect all others.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    /// The current state of the breaker
+    state: Arc<Mutex<BreakerState>>,
+    /// The number of consecutive failures after which the circuit is opened
+    failure_threshold: u32,
+    /// The period for which the circuit remains open before it is probed
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker which opens after `failure_threshold` consecutive failures, for `cooldown`
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BreakerState::Closed {
+                consecutive_failures: 0,
+            })),
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+        }
+    }
+
+    /// Permits a query unless the circuit is open, moving to half open if the cooldown has elapsed
+    fn acquire(&self) -> Result<(), DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match *state {
+            BreakerState::Closed { .. } => Ok(()),
+            BreakerState::Open { until } | BreakerState::HalfOpen { until } if now >= until => {
+                info!("Probing database after circuit breaker cooldown");
+                *state = BreakerState::HalfOpen {
+                    until: now + self.cooldown,
+                };
+                Ok(())
+            }
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => {
+                tracing::info!(
+                    target: METRICS_TARGET,
+                    monotonic_counter.db_circuit_short_circuits = 1_u64,
+                );
+                Err(DbErr::Custom(CIRCUIT_OPEN_MESSAGE.to_string()))
+            }
+        }
+    }
+
+    /// Records the outcome of a permitted query, opening or closing the circuit as appropriate
+    fn record<T>(&self, result: &Result<T, DbErr>) {
+        let mut state = self.state.lock().unwrap();
+        let previous = *state;
+        *state = match (previous, result) {
+            (_, Ok(_)) => BreakerState::Closed {
+                consecutive_failures: 0,
+            },
+            (
+                BreakerState::Closed {
+                    consecutive_failures,
+                },
+                Err(_),
+            ) if consecutive_failures + 1 < self.failure_threshold => BreakerState::Closed {
+                consecutive_failures: consecutive_failures + 1,
+            },
+            (BreakerState::Open { until }, Err(_)) => BreakerState::Open { until },
+            (_, Err(_)) => BreakerState::Open {
+                until: Instant::now() + self.cooldown,
+            },
+        };
+        let was_open = !matches!(previous, BreakerState::Closed { .. });
+        let is_open = !matches!(*state, BreakerState::Closed { .. });
+        if is_open && !was_open {
+            warn!(
+                failures = self.failure_threshold,
+                cooldown_ms = self.cooldown.as_millis() as u64,
+                "Database circuit breaker opened"
+            );
+            tracing::info!(target: METRICS_TARGET, counter.db_circuit_open = 1_i64);
+        } else if was_open && !is_open {
+            info!("Database circuit breaker closed");
+            tracing::info!(target: METRICS_TARGET, counter.db_circuit_open = -1_i64);
+        }
+    }
+
+    /// Runs the query if the circuit permits it, recording its outcome
+    async fn guard<T>(
+        &self,
+        query: impl std::future::Future<Output = Result<T, DbErr>>,
+    ) -> Result<T, DbErr> {
+        self.acquire()?;
+        let result = query.await;
+        self.record(&result);
+        result
+    }
+}
+
+/// Returns true if the error was raised by a [`CircuitBreaker`] rejecting a query whilst open
+pub fn is_circuit_open(error: &DbErr) -> bool {
+    matches!(error, DbErr::Custom(message) if message == CIRCUIT_OPEN_MESSAGE)
+}
+
+/// A database connection whose queries pass through a [`CircuitBreaker`]
+#[derive(Debug, Clone)]
+pub struct GuardedConnection {
+    /// The underlying database connection
+    database: DatabaseConnection,
+    /// The breaker through which queries pass
+    breaker: CircuitBreaker,
+}
+
+impl GuardedConnection {
+    /// Wraps the connection such that its queries pass through the breaker
+    pub fn new(database: DatabaseConnection, breaker: CircuitBreaker) -> Self {
+        Self { database, breaker }
+    }
+}
+
+#[async_trait::async_trait]
+impl ConnectionTrait for GuardedConnection {
+    fn get_database_backend(&self) -> DbBackend {
+        self.database.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        self.breaker.guard(self.database.execute(stmt)).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.breaker
+            .guard(self.database.execute_unprepared(sql))
+            .await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        self.breaker.guard(self.database.query_one(stmt)).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        self.breaker.guard(self.database.query_all(stmt)).await
+    }
+
+    fn support_returning(&self) -> bool {
+        self.database.support_returning()
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        self.database.is_mock_connection()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The period for which the breakers under test remain open
+    const COOLDOWN: Duration = Duration::from_millis(50);
+
+    /// A query which fails with a connection error
+    async fn failing() -> Result<(), DbErr> {
+        Err(DbErr::Conn(sea_orm::RuntimeErr::Internal(
+            "Connection refused".to_string(),
+        )))
+    }
+
+    /// A query which succeeds
+    async fn succeeding() -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    /// The current state of the breaker
+    fn state(breaker: &CircuitBreaker) -> BreakerState {
+        *breaker.state.lock().unwrap()
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+
+        for consecutive_failures in 1..3 {
+            assert!(!is_circuit_open(
+                &breaker.guard(failing()).await.unwrap_err()
+            ));
+            assert_eq!(
+                state(&breaker),
+                BreakerState::Closed {
+                    consecutive_failures
+                }
+            );
+        }
+        assert!(!is_circuit_open(
+            &breaker.guard(failing()).await.unwrap_err()
+        ));
+        assert!(matches!(state(&breaker), BreakerState::Open { .. }));
+
+        assert!(is_circuit_open(
+            &breaker.guard(succeeding()).await.unwrap_err()
+        ));
+    }
+
+    #[tokio::test]
+    async fn success_resets_the_consecutive_failures() {
+        let breaker = CircuitBreaker::new(2, COOLDOWN);
+
+        breaker.guard(failing()).await.unwrap_err();
+        breaker.guard(succeeding()).await.unwrap();
+        breaker.guard(failing()).await.unwrap_err();
+
+        assert_eq!(
+            state(&breaker),
+            BreakerState::Closed {
+                consecutive_failures: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn circuit_is_probed_after_the_cooldown_and_closes_on_success() {
+        let breaker = CircuitBreaker::new(1, COOLDOWN);
+        breaker.guard(failing()).await.unwrap_err();
+        assert!(matches!(state(&breaker), BreakerState::Open { .. }));
+
+        tokio::time::sleep(COOLDOWN).await;
+        breaker.acquire().unwrap();
+        assert!(matches!(state(&breaker), BreakerState::HalfOpen { .. }));
+        assert!(is_circuit_open(&breaker.acquire().unwrap_err()));
+
+        breaker.record(&succeeding().await);
+        assert_eq!(
+            state(&breaker),
+            BreakerState::Closed {
+                consecutive_failures: 0
+            }
+        );
+        breaker.guard(succeeding()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_probe_reopens_the_circuit() {
+        let breaker = CircuitBreaker::new(1, COOLDOWN);
+        breaker.guard(failing()).await.unwrap_err();
+
+        tokio::time::sleep(COOLDOWN).await;
+        assert!(!is_circuit_open(
+            &breaker.guard(failing()).await.unwrap_err()
+        ));
+
+        assert!(matches!(state(&breaker), BreakerState::Open { .. }));
+        assert!(is_circuit_open(
+            &breaker.guard(succeeding()).await.unwrap_err()
+        ));
+    }
+
+    #[tokio::test]
+    async fn abandoned_probe_is_replaced_after_the_cooldown() {
+        let breaker = CircuitBreaker::new(1, COOLDOWN);
+        breaker.guard(failing()).await.unwrap_err();
+        tokio::time::sleep(COOLDOWN).await;
+        breaker.acquire().unwrap();
+
+        tokio::time::sleep(COOLDOWN).await;
+
+        breaker.guard(succeeding()).await.unwrap();
+        assert_eq!(
+            state(&breaker),
+            BreakerState::Closed {
+                consecutive_failures: 0
+            }
+        );
+    }
+}
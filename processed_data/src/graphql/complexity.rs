@@ -0,0 +1,65 @@
+use super::METRICS_TARGET;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextValidation},
+    Response, ServerError, ValidationResult, Value,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The request header with which clients opt in to receiving the query complexity
+pub const INCLUDE_QUERY_COMPLEXITY_HEADER: &str = "x-include-query-complexity";
+
+/// A marker, added to the request data, indicating the query complexity should be included in the response
+#[derive(Debug, Clone, Copy)]
+pub struct IncludeQueryComplexity;
+
+/// An [`ExtensionFactory`] which records the complexity of each query as a metric and, if requested, in the response extensions
+#[derive(Debug, Default)]
+pub struct QueryComplexityReporter;
+
+impl ExtensionFactory for QueryComplexityReporter {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(QueryComplexityReporterExtension::default())
+    }
+}
+
+/// The per-request [`Extension`] created by [`QueryComplexityReporter`]
+#[derive(Debug, Default)]
+struct QueryComplexityReporterExtension {
+    /// The complexity computed during validation
+    complexity: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl Extension for QueryComplexityReporterExtension {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        tracing::info!(
+            target: METRICS_TARGET,
+            histogram.query_complexity = result.complexity as u64,
+        );
+        self.complexity.store(result.complexity, Ordering::Relaxed);
+        Ok(result)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let response = next.run(ctx, operation_name).await;
+        if ctx.data_opt::<IncludeQueryComplexity>().is_some() {
+            let complexity = self.complexity.load(Ordering::Relaxed) as u64;
+            response.extension("queryComplexity", Value::from(complexity))
+        } else {
+            response
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use super::circuit_breaker::is_circuit_open;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    ErrorExtensionValues, Response,
+};
+use sea_orm::{ConnAcquireErr, DbErr};
+use std::sync::Arc;
+
+/// The number of seconds after which clients are advised to retry once the pool has been exhausted
+const POOL_EXHAUSTED_RETRY_AFTER: u64 = 1;
+
+/// An [`ExtensionFactory`] which reports errors caused by timing out whilst acquiring a database connection as pool exhaustion,
+/// and errors caused by the database circuit breaker being open as such
+#[derive(Debug, Default)]
+pub struct PoolExhaustion;
+
+impl ExtensionFactory for PoolExhaustion {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PoolExhaustionExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`PoolExhaustion`]
+struct PoolExhaustionExtension;
+
+#[async_trait::async_trait]
+impl Extension for PoolExhaustionExtension {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+        for error in &mut response.errors {
+            if let Some(DbErr::ConnectionAcquire(ConnAcquireErr::Timeout)) = error.source::<DbErr>()
+            {
+                error.message =
+                    "No database connection became available, the service is overloaded"
+                        .to_string();
+                let extensions = error
+                    .extensions
+                    .get_or_insert_with(ErrorExtensionValues::default);
+                extensions.set("code", "DB_POOL_EXHAUSTED");
+                extensions.set("retryAfter", POOL_EXHAUSTED_RETRY_AFTER);
+            } else if error.source::<DbErr>().is_some_and(is_circuit_open) {
+                error
+                    .extensions
+                    .get_or_insert_with(ErrorExtensionValues::default)
+                    .set("code", "DB_CIRCUIT_OPEN");
+            }
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::{
+        root_schema_builder, test_database::TestDatabase, AddDataLoadersExt, CircuitBreaker,
+        LoaderSettings, ProcessingStatusCodes,
+    };
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Schema, Value};
+    use sea_orm::{ConnectOptions, Database, DatabaseConnection, TransactionTrait};
+    use std::time::Duration;
+
+    /// A query whose fields fail with database errors
+    struct FailingQuery;
+
+    #[Object]
+    impl FailingQuery {
+        /// Fails as a query would once no connection became available
+        async fn exhausted(&self) -> async_graphql::Result<u32> {
+            Err(DbErr::ConnectionAcquire(ConnAcquireErr::Timeout).into())
+        }
+
+        /// Fails as a query would once the connection was closed
+        async fn closed(&self) -> async_graphql::Result<u32> {
+            Err(DbErr::ConnectionAcquire(ConnAcquireErr::ConnectionClosed).into())
+        }
+    }
+
+    /// The message, code and retry hint of each error of the response
+    fn error_details(response: &Response) -> Vec<(String, Option<String>, Option<u64>)> {
+        response
+            .errors
+            .iter()
+            .map(|error| {
+                let extensions = error.extensions.as_ref();
+                let code = match extensions.and_then(|extensions| extensions.get("code")) {
+                    Some(Value::String(code)) => Some(code.clone()),
+                    _ => None,
+                };
+                let retry_after =
+                    match extensions.and_then(|extensions| extensions.get("retryAfter")) {
+                        Some(Value::Number(retry_after)) => retry_after.as_u64(),
+                        _ => None,
+                    };
+                (error.message.clone(), code, retry_after)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn acquire_timeouts_are_reported_as_pool_exhaustion() {
+        let schema = Schema::build(FailingQuery, EmptyMutation, EmptySubscription)
+            .extension(PoolExhaustion)
+            .finish();
+
+        let response = schema.execute("{ exhausted }").await;
+
+        assert_eq!(
+            error_details(&response),
+            [(
+                "No database connection became available, the service is overloaded".to_string(),
+                Some("DB_POOL_EXHAUSTED".to_string()),
+                Some(POOL_EXHAUSTED_RETRY_AFTER)
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn other_database_errors_are_unchanged() {
+        let schema = Schema::build(FailingQuery, EmptyMutation, EmptySubscription)
+            .extension(PoolExhaustion)
+            .finish();
+
+        let response = schema.execute("{ closed }").await;
+
+        let details = error_details(&response);
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].1, None);
+        assert_eq!(details[0].2, None);
+    }
+
+    #[tokio::test]
+    async fn saturating_the_pool_surfaces_pool_exhaustion() {
+        let database = TestDatabase::start().await;
+        let pool: DatabaseConnection = Database::connect(
+            ConnectOptions::new(database.database_url.to_string())
+                .max_connections(1)
+                .acquire_timeout(Duration::from_millis(200))
+                .to_owned(),
+        )
+        .await
+        .unwrap();
+        let _held = pool.begin().await.unwrap();
+        let schema = root_schema_builder(true).data(pool.clone()).finish();
+
+        let response = schema
+            .execute(
+                Request::new(r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id } }"#)
+                    .add_data_loaders(
+                        pool,
+                        LoaderSettings {
+                            slow_query_threshold: Duration::from_secs(1),
+                            max_batch_size: 100,
+                            circuit_breaker: CircuitBreaker::new(u32::MAX, Duration::ZERO),
+                            status_codes: ProcessingStatusCodes::default(),
+                        },
+                    ),
+            )
+            .await;
+
+        let details = error_details(&response);
+        assert!(!details.is_empty());
+        assert!(details.iter().all(|(_, code, retry_after)| {
+            code.as_deref() == Some("DB_POOL_EXHAUSTED")
+                && *retry_after == Some(POOL_EXHAUSTED_RETRY_AFTER)
+        }));
+    }
+}
@@ -1,9 +1,16 @@
-use async_graphql::{Enum, SimpleObject};
+use super::{
+    object_key::ObjectKeyRewrite,
+    scalars::Timestamp,
+    scopes::{requires_scope, PROCESSING_MESSAGE_SCOPE},
+};
+use async_graphql::{Enum, Interface, SimpleObject};
 use models::{
     auto_proc_program_attachment, auto_proc_scaling, auto_proc_scaling_statistics,
+    data_collection_file_attachment,
     sea_orm_active_enums::{FileType, ScalingStatisticsType},
 };
-use sea_orm::QueryResult;
+use sea_orm::{DbBackend, QueryResult};
+use std::{collections::HashSet, str::FromStr, sync::OnceLock};
 
 /// Combines autoproc integration, autoproc program, autoproc and autoproc scaling
 #[derive(Debug, Clone, SimpleObject)]
@@ -28,6 +35,7 @@ pub struct AutoProcessing {
     /// Processing program status
     pub processing_status: Option<i8>,
     /// Processing program message
+    #[graphql(directive = requires_scope::apply(PROCESSING_MESSAGE_SCOPE.to_string()))]
     pub processing_message: Option<String>,
     /// An opaque unique identifier for the  processing processing job
     pub processing_job_id: Option<u32>,
@@ -57,8 +65,8 @@ impl From<QueryResult> for AutoProcessing {
             auto_proc_integration_id: value.try_get("", "autoProcIntegrationId").unwrap(),
             data_collection_id: value.try_get("", "dataCollectionId").unwrap(),
             auto_proc_program_id: value.try_get("", "autoProcProgramId").unwrap_or(None),
-            refined_x_beam: value.try_get("", "refinedYBeam").unwrap_or(None),
-            refined_y_beam: value.try_get("", "refinedXBeam").unwrap_or(None),
+            refined_x_beam: value.try_get("", "refinedXBeam").unwrap_or(None),
+            refined_y_beam: value.try_get("", "refinedYBeam").unwrap_or(None),
             processing_programs: value.try_get("", "processingPrograms").unwrap_or(None),
             processing_status: value.try_get("", "processingStatus").unwrap_or(None),
             processing_message: value.try_get("", "processingMessage").unwrap_or(None),
@@ -76,6 +84,77 @@ impl From<QueryResult> for AutoProcessing {
     }
 }
 
+/// A position on the detector
+#[derive(Clone, Copy, Debug, PartialEq, SimpleObject)]
+pub struct Point {
+    /// The X coordinate
+    pub x: f32,
+    /// The Y coordinate
+    pub y: f32,
+}
+
+impl Point {
+    /// Creates a point from the coordinates, or returns [`None`] if either is absent
+    pub fn from_coordinates(x: Option<f32>, y: Option<f32>) -> Option<Self> {
+        Some(Self { x: x?, y: y? })
+    }
+}
+
+/// Represents an auto processing run alongside its overall scaling statistics
+#[derive(Debug, Clone, SimpleObject)]
+pub struct AutoProcessingWithOverall {
+    /// The auto processing run
+    pub auto_processing: AutoProcessing,
+    /// The overall scaling statistics of the run
+    pub overall: Option<AutoProcScalingStatics>,
+}
+
+/// The overall statistics of an auto processing run, summarised for comparison between programs
+#[derive(Debug, Clone, PartialEq, SimpleObject)]
+#[graphql(complex)]
+pub struct StatisticsSummary {
+    /// The name of the program which processed the data collection
+    pub program: Option<String>,
+    /// The overall high resolution limit, unrounded
+    #[graphql(skip)]
+    pub resolution: Option<f32>,
+    /// The overall CC(1/2), unrounded
+    #[graphql(skip)]
+    pub cc_half: Option<f32>,
+    /// The overall completeness, unrounded
+    #[graphql(skip)]
+    pub completeness: Option<f32>,
+}
+
+/// The completeness of each shell of an auto processing run, for comparison of the fall-off from inner to outer shell
+#[derive(Debug, Clone, Default, PartialEq, SimpleObject)]
+#[graphql(complex)]
+pub struct CompletenessByShell {
+    /// The overall completeness, unrounded
+    #[graphql(skip)]
+    pub overall: Option<f32>,
+    /// The innershell completeness, unrounded
+    #[graphql(skip)]
+    pub inner: Option<f32>,
+    /// The outershell completeness, unrounded
+    #[graphql(skip)]
+    pub outer: Option<f32>,
+}
+
+impl FromIterator<AutoProcScalingStatics> for CompletenessByShell {
+    fn from_iter<T: IntoIterator<Item = AutoProcScalingStatics>>(iter: T) -> Self {
+        let mut completeness = Self::default();
+        for statistics in iter {
+            match statistics.scaling_statistics_type {
+                StatisticsType::Overall => completeness.overall = statistics.completeness,
+                StatisticsType::InnerShell => completeness.inner = statistics.completeness,
+                StatisticsType::OuterShell => completeness.outer = statistics.completeness,
+            }
+        }
+        completeness
+    }
+}
+
 /// Type of file attachment for auto processing
 #[derive(Enum, Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[allow(clippy::missing_docs_in_private_items)]
@@ -99,6 +178,171 @@ impl ToString for AttachmentFileType {
     }
 }
 
+impl FromStr for AttachmentFileType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "log" => Ok(AttachmentFileType::Log),
+            "result" => Ok(AttachmentFileType::Result),
+            "graph" => Ok(AttachmentFileType::Graph),
+            "debug" => Ok(AttachmentFileType::Debug),
+            "input" => Ok(AttachmentFileType::Input),
+            _ => Err(format!("Unknown attachment file type {value}")),
+        }
+    }
+}
+
+/// The types of file attachment which may be downloaded without the internal attachment scope
+#[derive(Debug, Clone)]
+pub struct DownloadableFileTypes(HashSet<AttachmentFileType>);
+
+impl DownloadableFileTypes {
+    /// Creates the set of downloadable file types
+    pub fn new(file_types: impl IntoIterator<Item = AttachmentFileType>) -> Self {
+        Self(file_types.into_iter().collect())
+    }
+
+    /// Returns true if attachments of the file type may be downloaded
+    pub fn contains(&self, file_type: Option<AttachmentFileType>) -> bool {
+        file_type.is_some_and(|file_type| self.0.contains(&file_type))
+    }
+}
+
+impl Default for DownloadableFileTypes {
+    fn default() -> Self {
+        Self::new([
+            AttachmentFileType::Log,
+            AttachmentFileType::Result,
+            AttachmentFileType::Graph,
+        ])
+    }
+}
+
+/// The outcome of an auto processing run, as derived from the processing program status
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProcessingStatus {
+    /// Processing completed successfully
+    Succeeded,
+    /// Processing failed
+    Failed,
+}
+
+/// The number of auto processing runs of a data collection with an outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SimpleObject)]
+pub struct ProcessingStatusCount {
+    /// The outcome of the runs, or null if processing is in progress or the status is unknown
+    pub status: Option<ProcessingStatus>,
+    /// The number of runs with the outcome
+    pub count: u64,
+}
+
+/// A change to the processing status of an auto processing run of a data collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SimpleObject)]
+pub struct ProcessingStatusChange {
+    /// An opaque unique identifier for the auto processing program
+    pub auto_proc_program_id: u32,
+    /// The outcome of the run after the change, or null if processing is in progress or the status is unknown
+    pub status: Option<ProcessingStatus>,
+}
+
+/// The processing program statuses which indicate that processing succeeded or failed
+#[derive(Debug, Clone)]
+pub struct ProcessingStatusCodes {
+    /// The statuses indicating that processing succeeded
+    success: HashSet<i8>,
+    /// The statuses indicating that processing failed
+    failure: HashSet<i8>,
+}
+
+impl ProcessingStatusCodes {
+    /// Creates the sets of success and failure statuses
+    pub fn new(
+        success: impl IntoIterator<Item = i8>,
+        failure: impl IntoIterator<Item = i8>,
+    ) -> Self {
+        Self {
+            success: success.into_iter().collect(),
+            failure: failure.into_iter().collect(),
+        }
+    }
+
+    /// The statuses indicating the outcome, in ascending order
+    pub fn statuses(&self, outcome: ProcessingStatus) -> Vec<i8> {
+        let mut statuses = match outcome {
+            ProcessingStatus::Succeeded => &self.success,
+            ProcessingStatus::Failed => &self.failure,
+        }
+        .iter()
+        .copied()
+        .collect::<Vec<_>>();
+        statuses.sort_unstable();
+        statuses
+    }
+
+    /// The statuses indicating that processing has completed, whether it succeeded or failed, in ascending order
+    pub fn terminal_statuses(&self) -> Vec<i8> {
+        let mut statuses = self
+            .success
+            .union(&self.failure)
+            .copied()
+            .collect::<Vec<_>>();
+        statuses.sort_unstable();
+        statuses
+    }
+
+    /// Returns true if the status indicates success, false if it indicates failure, or [`None`] if processing is in progress or the status is unknown
+    pub fn succeeded(&self, status: Option<i8>) -> Option<bool> {
+        let status = status?;
+        if self.success.contains(&status) {
+            Some(true)
+        } else if self.failure.contains(&status) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// The outcome indicated by the status, or [`None`] if processing is in progress or the status is unknown
+    pub fn outcome(&self, status: Option<i8>) -> Option<ProcessingStatus> {
+        self.succeeded(status).map(|succeeded| {
+            if succeeded {
+                ProcessingStatus::Succeeded
+            } else {
+                ProcessingStatus::Failed
+            }
+        })
+    }
+}
+
+impl Default for ProcessingStatusCodes {
+    /// ISPyB records a status of 1 on success, 0 on failure, and leaves it null whilst processing is in progress
+    fn default() -> Self {
+        Self::new([1], [0])
+    }
+}
+
+/// The number of characters to which processing messages are truncated when no length is requested
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessingMessageMaxLength(pub usize);
+
+impl Default for ProcessingMessageMaxLength {
+    fn default() -> Self {
+        Self(200)
+    }
+}
+
+impl AutoProcessing {
+    /// Returns the processing message clipped to `max_length` characters, followed by an ellipsis if any were removed
+    pub fn truncated_processing_message(&self, max_length: usize) -> Option<String> {
+        let message = self.processing_message.as_deref()?;
+        match message.char_indices().nth(max_length) {
+            Some((end, _)) => Some(format!("{}…", &message[..end])),
+            None => Some(message.to_string()),
+        }
+    }
+}
+
 impl From<Option<FileType>> for AttachmentFileType {
     fn from(value: Option<FileType>) -> Self {
         match value {
@@ -117,7 +361,6 @@ impl From<Option<FileType>> for AttachmentFileType {
 #[graphql(name = "AutoProcFileAttachment", unresolvable, complex)]
 pub struct AutoProcFileAttachment {
     /// An opaque unique identifier for the autoproc file attachment
-    #[graphql(skip)]
     pub id: u32,
     /// An opaque unique identifier for auto proc program
     #[graphql(skip)]
@@ -125,11 +368,13 @@ pub struct AutoProcFileAttachment {
     /// Type of file attachment
     pub file_type: Option<AttachmentFileType>,
     /// Full name of the file
-    #[graphql(skip)]
     pub file_name: Option<String>,
     /// Path of the file stored in the file system
     #[graphql(skip)]
     pub file_path: Option<String>,
+    /// S3 bucket object key, memoized by [`AutoProcFileAttachment::object_key`]
+    #[graphql(skip)]
+    pub object_key: OnceLock<String>,
 }
 impl From<auto_proc_program_attachment::Model> for AutoProcFileAttachment {
     fn from(value: auto_proc_program_attachment::Model) -> Self {
@@ -139,26 +384,278 @@ impl From<auto_proc_program_attachment::Model> for AutoProcFileAttachment {
             file_type: Some(AttachmentFileType::from(value.file_type)),
             file_name: value.file_name,
             file_path: value.file_path,
+            object_key: OnceLock::new(),
         }
     }
 }
 
-impl AutoProcFileAttachment {
-    /// S3 bucket object key
-    pub fn object_key(&self) -> String {
-        let mut key = std::path::PathBuf::from(
-            <Option<String> as Clone>::clone(&self.file_path)
-                .unwrap()
-                .to_string(),
-        );
-        key.push(<Option<String> as Clone>::clone(&self.file_name).unwrap());
-        key.to_string_lossy().to_string()
+/// A file attached to a data collection or to its auto processing, stored in s3 bucket
+pub trait StoredAttachment {
+    /// S3 bucket object key, computed on first use
+    fn object_key(&self, rewrite: &ObjectKeyRewrite) -> &str;
+
+    /// Name of the file, without its directory
+    fn stored_file_name(&self) -> Option<&str>;
+
+    /// Type of file attachment, if known
+    fn stored_file_type(&self) -> Option<AttachmentFileType>;
+
+    /// The content type with which the file should be served, inferred from the extension of its name or else its type
+    fn inferred_content_type(&self) -> Option<&'static str> {
+        content_type_from_extension(self.stored_file_name()).or(match self.stored_file_type() {
+            Some(AttachmentFileType::Log) => Some("text/plain"),
+            _ => None,
+        })
+    }
+
+    /// The disposition with which the file should be served, inferred from its type
+    fn default_disposition(&self) -> Option<ContentDisposition> {
+        ContentDisposition::for_file_type(self.stored_file_type())
+    }
+}
+
+impl StoredAttachment for AutoProcFileAttachment {
+    fn object_key(&self, rewrite: &ObjectKeyRewrite) -> &str {
+        self.object_key.get_or_init(|| {
+            rewrite.to_object_key(
+                self.file_path.as_deref().unwrap(),
+                self.file_name.as_deref().unwrap(),
+            )
+        })
+    }
+
+    fn stored_file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    fn stored_file_type(&self) -> Option<AttachmentFileType> {
+        self.file_type
+    }
+}
+
+/// Whether a file is displayed by the browser or saved, as set by the `Content-Disposition` with which it is served
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ContentDisposition {
+    /// Display the file in the browser
+    Inline,
+    /// Save the file, under its name
+    Attachment,
+}
+
+impl ContentDisposition {
+    /// The disposition with which files of the type are served by default, graphs being displayed whilst logs and results are saved
+    pub fn for_file_type(file_type: Option<AttachmentFileType>) -> Option<Self> {
+        match file_type? {
+            AttachmentFileType::Graph => Some(Self::Inline),
+            AttachmentFileType::Log | AttachmentFileType::Result => Some(Self::Attachment),
+            AttachmentFileType::Debug | AttachmentFileType::Input => None,
+        }
+    }
+
+    /// The `Content-Disposition` header value, naming the file if a name is supplied
+    ///
+    /// Characters which cannot appear in a quoted ASCII filename are replaced with underscores.
+    pub fn header_value(&self, file_name: Option<&str>) -> String {
+        let disposition = match self {
+            Self::Inline => "inline",
+            Self::Attachment => "attachment",
+        };
+        match file_name {
+            Some(file_name) => {
+                let file_name = file_name
+                    .chars()
+                    .map(|character| match character {
+                        ' ' | '!' | '#'..='[' | ']'..='~' => character,
+                        _ => '_',
+                    })
+                    .collect::<String>();
+                format!("{disposition}; filename=\"{file_name}\"")
+            }
+            None => disposition.to_string(),
+        }
+    }
+}
+
+/// Infers the content type of a file from the extension of its name
+fn content_type_from_extension(file_name: Option<&str>) -> Option<&'static str> {
+    let extension = file_name
+        .and_then(|file_name| file_name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("log" | "txt" | "lp") => Some("text/plain"),
+        Some("png") => Some("image/png"),
+        Some("jpg" | "jpeg") => Some("image/jpeg"),
+        Some("html" | "htm") => Some("text/html"),
+        Some("json") => Some("application/json"),
+        Some("pdf") => Some("application/pdf"),
+        Some("xml") => Some("application/xml"),
+        Some("csv") => Some("text/csv"),
+        Some("gz") => Some("application/gzip"),
+        _ => None,
+    }
+}
+
+/// Represents a file attached directly to a data collection, stored in s3 bucket
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+#[graphql(name = "DataCollectionFileAttachment", complex)]
+pub struct DataCollectionFileAttachment {
+    /// An opaque unique identifier for the data collection file attachment
+    pub id: u32,
+    /// An opaque unique identifier for the data collection
+    #[graphql(skip)]
+    pub data_collection_id: u32,
+    /// Name of the file, without its directory
+    pub file_name: Option<String>,
+    /// Type of file attachment, or null if the type is not shared with auto processing attachments
+    pub file_type: Option<AttachmentFileType>,
+    /// Full path of the file stored in the file system
+    #[graphql(skip)]
+    pub file_full_path: String,
+    /// S3 bucket object key, memoized by [`DataCollectionFileAttachment::object_key`]
+    #[graphql(skip)]
+    pub object_key: OnceLock<String>,
+}
+
+impl From<data_collection_file_attachment::Model> for DataCollectionFileAttachment {
+    fn from(value: data_collection_file_attachment::Model) -> Self {
+        Self {
+            id: value.data_collection_file_attachment_id,
+            data_collection_id: value.data_collection_id,
+            file_name: value.file_full_path.rsplit('/').next().map(String::from),
+            file_type: value
+                .file_type
+                .and_then(|file_type| file_type.parse::<AttachmentFileType>().ok()),
+            file_full_path: value.file_full_path,
+            object_key: OnceLock::new(),
+        }
+    }
+}
+
+impl StoredAttachment for DataCollectionFileAttachment {
+    fn object_key(&self, rewrite: &ObjectKeyRewrite) -> &str {
+        self.object_key.get_or_init(|| {
+            let (file_path, file_name) = self
+                .file_full_path
+                .rsplit_once('/')
+                .unwrap_or(("", &self.file_full_path));
+            rewrite.to_object_key(file_path, file_name)
+        })
+    }
+
+    fn stored_file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    fn stored_file_type(&self) -> Option<AttachmentFileType> {
+        self.file_type
+    }
+}
+
+/// A file attached either directly to a data collection or to its auto processing
+#[derive(Clone, Debug, Interface)]
+#[graphql(
+    name = "Attachment",
+    field(
+        name = "id",
+        ty = "&u32",
+        desc = "An opaque unique identifier for the attachment, unique amongst attachments of the same type"
+    ),
+    field(name = "file_name", ty = "&Option<String>", desc = "Name of the file"),
+    field(
+        name = "file_url",
+        ty = "Option<String>",
+        desc = "Generate downloadable link for the file in s3 bucket",
+        arg(name = "version_id", ty = "Option<String>"),
+        arg(name = "content_type", ty = "Option<String>"),
+        arg(name = "disposition", ty = "Option<ContentDisposition>"),
+        arg(name = "range", ty = "Option<String>")
+    )
+)]
+pub enum Attachment {
+    /// A file attached to the auto processing of a data collection
+    AutoProc(AutoProcFileAttachment),
+    /// A file attached directly to a data collection
+    DataCollection(DataCollectionFileAttachment),
+}
+
+impl Attachment {
+    /// The key by which attachments are ordered
+    ///
+    /// As the ids of the two sources are drawn from different spaces, ties are broken by the source, with attachments
+    /// of the data collection first, such that the combined set has a stable total order.
+    pub fn ordering_key(&self, order: AttachmentOrder) -> (Option<&str>, u32, u8) {
+        let (id, file_name, source) = match self {
+            Self::DataCollection(attachment) => (attachment.id, attachment.file_name.as_deref(), 0),
+            Self::AutoProc(attachment) => (attachment.id, attachment.file_name.as_deref(), 1),
+        };
+        match order {
+            AttachmentOrder::Id => (None, id, source),
+            AttachmentOrder::FileName => (file_name, id, source),
+        }
     }
 }
 
+/// The field by which attachments are ordered
+#[derive(Enum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AttachmentOrder {
+    /// Order by the id of the attachment
+    #[default]
+    Id,
+    /// Order by the name of the file, with unnamed files first
+    FileName,
+}
+
+/// A time-limited link to download a file
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct PresignedUrl {
+    /// The presigned URL of the file
+    pub url: String,
+    /// The time after which the URL is no longer valid
+    pub expires_at: Timestamp,
+}
+
+/// The SQL run by the dataloaders, with bound values replaced by placeholders
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct LoaderQueries {
+    /// The query fetching processing jobs and their parameters
+    pub processing_jobs: String,
+    /// The query fetching auto processing with its program, auto proc and scaling
+    pub auto_processing: String,
+}
+
+/// The kind of database server to which the service is connected
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    /// A MySQL or MariaDB server
+    MySql,
+    /// A PostgreSQL server
+    Postgres,
+    /// An SQLite database
+    Sqlite,
+}
+
+impl From<DbBackend> for DatabaseBackend {
+    fn from(backend: DbBackend) -> Self {
+        match backend {
+            DbBackend::MySql => Self::MySql,
+            DbBackend::Postgres => Self::Postgres,
+            DbBackend::Sqlite => Self::Sqlite,
+        }
+    }
+}
+
+/// The database server to which the service is connected
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct DatabaseInfo {
+    /// The kind of database server
+    pub backend: DatabaseBackend,
+    /// The version reported by the server
+    pub version: String,
+}
+
 /// Represents a processing job
 #[derive(Clone, Debug, PartialEq, SimpleObject)]
-#[graphql(name = "ProcessingJobs", unresolvable)]
+#[graphql(name = "ProcessingJobs", unresolvable, complex)]
 pub struct ProcessingJob {
     /// An opaque unique identifier for the processing job
     pub processing_job_id: Option<u32>,
@@ -194,7 +691,7 @@ impl From<QueryResult> for ProcessingJob {
 
 /// Represents and auto processing scaling
 #[derive(Clone, Debug, PartialEq, SimpleObject)]
-#[graphql(name = "AutoProcScaling", unresolvable)]
+#[graphql(name = "AutoProcScaling", unresolvable, complex)]
 pub struct AutoProcScaling {
     /// An opaque unique identifier for the auto processing scaling
     pub auto_proc_scaling_id: u32,
@@ -239,26 +736,56 @@ impl From<ScalingStatisticsType> for StatisticsType {
     }
 }
 
+/// The number of significant figures to which statistics are rounded, or [`None`] if they are not rounded
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StatsPrecision(pub Option<u32>);
+
+impl StatsPrecision {
+    /// Rounds the value to the configured number of significant figures
+    pub fn round(&self, value: Option<f32>) -> Option<f64> {
+        let value = f64::from(value?);
+        match self.0 {
+            Some(figures) if value != 0.0 && value.is_finite() => {
+                let magnitude = value.abs().log10().floor() as i32;
+                let scale = 10_f64.powi(figures as i32 - 1 - magnitude);
+                Some((value * scale).round() / scale)
+            }
+            _ => Some(value),
+        }
+    }
+}
+
 /// Represents auto processing scaling statics
 #[derive(Clone, Debug, PartialEq, SimpleObject)]
-#[graphql(name = "AutoProcScalingStatics", unresolvable)]
+#[graphql(name = "AutoProcScalingStatics", unresolvable, complex)]
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct AutoProcScalingStatics {
     pub auto_proc_scaling_statistics_id: u32,
     pub auto_proc_scaling_id: Option<u32>,
     pub scaling_statistics_type: StatisticsType,
+    #[graphql(name = "resolutionLimitLowRaw")]
     pub resolution_limit_low: Option<f32>,
+    #[graphql(name = "resolutionLimitHighRaw")]
     pub resolution_limit_high: Option<f32>,
+    #[graphql(name = "rMergeRaw")]
     pub r_merge: Option<f32>,
+    #[graphql(name = "rMeasAllIPlusIMinusRaw")]
     pub r_meas_all_i_plus_i_minus: Option<f32>,
-    pub n_total_observations: Option<i32>,
-    pub n_total_unique_observations: Option<i32>,
+    pub n_total_observations: Option<i64>,
+    pub n_total_unique_observations: Option<i64>,
+    #[graphql(name = "meanIOverSigIRaw")]
     pub mean_i_over_sig_i: Option<f32>,
+    #[graphql(name = "completenessRaw")]
     pub completeness: Option<f32>,
+    #[graphql(name = "multiplicityRaw")]
     pub multiplicity: Option<f32>,
+    #[graphql(name = "anomalousCompletenessRaw")]
     pub anomalous_completeness: Option<f32>,
+    #[graphql(name = "anomalousMultiplicityRaw")]
     pub anomalous_multiplicity: Option<f32>,
+    #[graphql(name = "ccHalfRaw")]
     pub cc_half: Option<f32>,
+    #[graphql(name = "ccAnomalousRaw")]
     pub cc_anomalous: Option<f32>,
 }
 
@@ -271,8 +798,8 @@ impl From<auto_proc_scaling_statistics::Model> for AutoProcScalingStatics {
             resolution_limit_high: value.resolution_limit_high,
             r_merge: value.r_merge,
             r_meas_all_i_plus_i_minus: value.r_meas_all_i_plus_i_minus,
-            n_total_observations: value.n_total_observations,
-            n_total_unique_observations: value.n_total_unique_observations,
+            n_total_observations: value.n_total_observations.map(i64::from),
+            n_total_unique_observations: value.n_total_unique_observations.map(i64::from),
             mean_i_over_sig_i: value.mean_i_over_sig_i,
             completeness: value.completeness,
             multiplicity: value.multiplicity,
@@ -285,6 +812,19 @@ impl From<auto_proc_scaling_statistics::Model> for AutoProcScalingStatics {
     }
 }
 
+impl AutoProcScalingStatics {
+    /// The ratio of total to unique observations, or [`None`] if either count is absent or there are no unique observations
+    pub fn redundancy(&self) -> Option<f32> {
+        match (
+            self.n_total_observations?,
+            self.n_total_unique_observations?,
+        ) {
+            (_, 0) => None,
+            (total, unique) => Some(total as f32 / unique as f32),
+        }
+    }
+}
+
 /// Datasets subgraph extension
 #[derive(SimpleObject)]
 #[graphql(name = "DataCollection", complex)]
@@ -292,3 +832,453 @@ pub struct DataCollection {
     /// An opaque unique identifier for the data collection
     pub id: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An auto processing attachment with the id and file name
+    fn auto_proc(id: u32, file_name: Option<&str>) -> Attachment {
+        Attachment::AutoProc(AutoProcFileAttachment {
+            id,
+            auto_proc_program_id: 1,
+            file_type: None,
+            file_name: file_name.map(String::from),
+            file_path: Some("/dls".to_string()),
+            object_key: OnceLock::new(),
+        })
+    }
+
+    /// A data collection attachment with the id and file name
+    fn data_collection(id: u32, file_name: &str) -> Attachment {
+        Attachment::DataCollection(DataCollectionFileAttachment {
+            id,
+            data_collection_id: 1,
+            file_name: Some(file_name.to_string()),
+            file_type: None,
+            file_full_path: format!("/dls/{file_name}"),
+            object_key: OnceLock::new(),
+        })
+    }
+
+    /// The ids of the attachments once sorted by their ordering keys
+    fn sorted_ids(mut attachments: Vec<Attachment>, order: AttachmentOrder) -> Vec<u32> {
+        attachments
+            .sort_by(|first, second| first.ordering_key(order).cmp(&second.ordering_key(order)));
+        attachments
+            .iter()
+            .map(|attachment| match attachment {
+                Attachment::AutoProc(attachment) => attachment.id,
+                Attachment::DataCollection(attachment) => attachment.id,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn attachments_with_the_same_id_are_ordered_data_collection_first() {
+        let attachments = vec![auto_proc(7, Some("a.log")), data_collection(7, "b.log")];
+        assert!(
+            attachments[1].ordering_key(AttachmentOrder::Id)
+                < attachments[0].ordering_key(AttachmentOrder::Id)
+        );
+    }
+
+    #[test]
+    fn attachments_are_merged_in_id_order_ignoring_file_names() {
+        let attachments = vec![
+            auto_proc(3, Some("a.log")),
+            data_collection(2, "z.log"),
+            auto_proc(1, Some("m.log")),
+        ];
+        assert_eq!(sorted_ids(attachments, AttachmentOrder::Id), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn attachments_are_merged_in_file_name_byte_order_with_unnamed_files_first() {
+        let attachments = vec![
+            auto_proc(1, Some("beta.txt")),
+            data_collection(2, "alpha.png"),
+            data_collection(3, "Zinc.log"),
+            auto_proc(4, None),
+            auto_proc(5, Some("alpha.png")),
+        ];
+        assert_eq!(
+            sorted_ids(attachments, AttachmentOrder::FileName),
+            vec![4, 3, 2, 5, 1]
+        );
+    }
+
+    /// Asserts that the value is present and equal to the expected value, to within floating point error
+    fn assert_rounded(value: Option<f64>, expected: f64) {
+        let value = value.unwrap();
+        assert!(
+            (value - expected).abs() <= expected.abs() * 1e-12,
+            "{value} != {expected}"
+        );
+    }
+
+    #[test]
+    fn statistics_are_not_rounded_by_default() {
+        assert_eq!(StatsPrecision::default().round(Some(1.5)), Some(1.5));
+        assert_eq!(
+            StatsPrecision::default().round(Some(99.8)),
+            Some(f64::from(99.8_f32))
+        );
+    }
+
+    #[test]
+    fn statistics_are_rounded_to_significant_figures() {
+        let precision = StatsPrecision(Some(3));
+        assert_rounded(precision.round(Some(99.8)), 99.8);
+        assert_rounded(precision.round(Some(1.23456)), 1.23);
+        assert_rounded(precision.round(Some(1.23556)), 1.24);
+        assert_rounded(precision.round(Some(0.0123456)), 0.0123);
+        assert_rounded(precision.round(Some(12345.0)), 12300.0);
+        assert_rounded(precision.round(Some(-1.23456)), -1.23);
+    }
+
+    #[test]
+    fn rounding_may_carry_into_the_next_magnitude() {
+        assert_rounded(StatsPrecision(Some(1)).round(Some(0.96)), 1.0);
+        assert_rounded(StatsPrecision(Some(2)).round(Some(99.96)), 100.0);
+    }
+
+    #[test]
+    fn zero_and_absent_statistics_are_unchanged() {
+        assert_eq!(StatsPrecision(Some(3)).round(Some(0.0)), Some(0.0));
+        assert_eq!(StatsPrecision(Some(3)).round(None), None);
+    }
+
+    #[test]
+    fn logs_results_and_graphs_are_downloadable_by_default() {
+        let downloadable = DownloadableFileTypes::default();
+        assert!(downloadable.contains(Some(AttachmentFileType::Log)));
+        assert!(downloadable.contains(Some(AttachmentFileType::Result)));
+        assert!(downloadable.contains(Some(AttachmentFileType::Graph)));
+        assert!(!downloadable.contains(Some(AttachmentFileType::Debug)));
+        assert!(!downloadable.contains(Some(AttachmentFileType::Input)));
+    }
+
+    #[test]
+    fn attachments_of_unknown_type_are_internal() {
+        assert!(!DownloadableFileTypes::default().contains(None));
+    }
+
+    #[test]
+    fn configured_file_types_replace_the_defaults() {
+        let downloadable = DownloadableFileTypes::new([AttachmentFileType::Debug]);
+        assert!(downloadable.contains(Some(AttachmentFileType::Debug)));
+        assert!(!downloadable.contains(Some(AttachmentFileType::Log)));
+    }
+
+    #[test]
+    fn file_types_are_parsed_from_their_names_in_any_case() {
+        for file_type in [
+            AttachmentFileType::Log,
+            AttachmentFileType::Result,
+            AttachmentFileType::Graph,
+            AttachmentFileType::Debug,
+            AttachmentFileType::Input,
+        ] {
+            assert_eq!(file_type.to_string().parse(), Ok(file_type));
+            assert_eq!(file_type.to_string().to_uppercase().parse(), Ok(file_type));
+        }
+        assert!("snapshot".parse::<AttachmentFileType>().is_err());
+    }
+
+    #[test]
+    fn point_requires_both_coordinates() {
+        assert_eq!(
+            Point::from_coordinates(Some(210.5), Some(220.5)),
+            Some(Point { x: 210.5, y: 220.5 })
+        );
+        assert_eq!(Point::from_coordinates(None, Some(220.5)), None);
+        assert_eq!(Point::from_coordinates(Some(210.5), None), None);
+        assert_eq!(Point::from_coordinates(None, None), None);
+    }
+
+    /// An auto processing attachment of the type, with the file name
+    fn typed_auto_proc(
+        file_type: Option<AttachmentFileType>,
+        file_name: &str,
+    ) -> AutoProcFileAttachment {
+        AutoProcFileAttachment {
+            id: 1,
+            auto_proc_program_id: 1,
+            file_type,
+            file_name: Some(file_name.to_string()),
+            file_path: Some("/dls".to_string()),
+            object_key: OnceLock::new(),
+        }
+    }
+
+    #[test]
+    fn content_type_is_inferred_from_the_extension_in_any_case() {
+        for (file_name, content_type) in [
+            ("xia2.log", Some("text/plain")),
+            ("XIA2.LOG", Some("text/plain")),
+            ("INTEGRATE.LP", Some("text/plain")),
+            ("snapshot.png", Some("image/png")),
+            ("snapshot.jpeg", Some("image/jpeg")),
+            ("summary.html", Some("text/html")),
+            ("xia2.json", Some("application/json")),
+            ("AUTOMATIC_DEFAULT_free.mtz", None),
+            ("README", None),
+        ] {
+            assert_eq!(
+                content_type_from_extension(Some(file_name)),
+                content_type,
+                "{file_name}"
+            );
+        }
+        assert_eq!(content_type_from_extension(None), None);
+    }
+
+    #[test]
+    fn content_type_of_logs_without_a_known_extension_is_plain_text() {
+        assert_eq!(
+            typed_auto_proc(Some(AttachmentFileType::Log), "xia2.error").inferred_content_type(),
+            Some("text/plain")
+        );
+        assert_eq!(
+            typed_auto_proc(Some(AttachmentFileType::Result), "xia2.mtz").inferred_content_type(),
+            None
+        );
+        assert_eq!(
+            typed_auto_proc(Some(AttachmentFileType::Log), "xia2.html").inferred_content_type(),
+            Some("text/html")
+        );
+    }
+
+    #[test]
+    fn success_and_failure_follow_the_ispyb_statuses_by_default() {
+        let status_codes = ProcessingStatusCodes::default();
+        assert_eq!(status_codes.succeeded(Some(1)), Some(true));
+        assert_eq!(status_codes.succeeded(Some(0)), Some(false));
+        assert_eq!(status_codes.succeeded(None), None);
+        assert_eq!(status_codes.succeeded(Some(2)), None);
+        assert_eq!(status_codes.succeeded(Some(-1)), None);
+    }
+
+    #[test]
+    fn configured_status_codes_replace_the_defaults() {
+        let status_codes = ProcessingStatusCodes::new([1, 2], [-1]);
+        assert_eq!(status_codes.succeeded(Some(1)), Some(true));
+        assert_eq!(status_codes.succeeded(Some(2)), Some(true));
+        assert_eq!(status_codes.succeeded(Some(-1)), Some(false));
+        assert_eq!(status_codes.succeeded(Some(0)), None);
+    }
+
+    /// Overall statistics with only the total and unique observation counts present
+    fn observations(total: Option<i64>, unique: Option<i64>) -> AutoProcScalingStatics {
+        AutoProcScalingStatics {
+            auto_proc_scaling_statistics_id: 1,
+            auto_proc_scaling_id: Some(1),
+            scaling_statistics_type: StatisticsType::Overall,
+            resolution_limit_low: None,
+            resolution_limit_high: None,
+            r_merge: None,
+            r_meas_all_i_plus_i_minus: None,
+            n_total_observations: total,
+            n_total_unique_observations: unique,
+            mean_i_over_sig_i: None,
+            completeness: None,
+            multiplicity: None,
+            anomalous_completeness: None,
+            anomalous_multiplicity: None,
+            cc_half: None,
+            cc_anomalous: None,
+        }
+    }
+
+    #[test]
+    fn redundancy_is_the_ratio_of_total_to_unique_observations() {
+        assert_eq!(
+            observations(Some(100000), Some(25000)).redundancy(),
+            Some(4.0)
+        );
+        assert_eq!(observations(Some(3), Some(2)).redundancy(), Some(1.5));
+    }
+
+    #[test]
+    fn redundancy_is_absent_without_both_counts() {
+        assert_eq!(observations(None, Some(25000)).redundancy(), None);
+        assert_eq!(observations(Some(100000), None).redundancy(), None);
+        assert_eq!(observations(None, None).redundancy(), None);
+    }
+
+    #[test]
+    fn redundancy_is_absent_without_unique_observations() {
+        assert_eq!(observations(Some(100000), Some(0)).redundancy(), None);
+        assert_eq!(observations(Some(0), Some(0)).redundancy(), None);
+    }
+
+    /// An auto processing run with the processing message
+    fn processed_with_message(processing_message: Option<&str>) -> AutoProcessing {
+        AutoProcessing {
+            auto_proc_integration_id: 1,
+            data_collection_id: 1,
+            auto_proc_program_id: Some(1),
+            refined_x_beam: None,
+            refined_y_beam: None,
+            processing_programs: None,
+            processing_status: None,
+            processing_message: processing_message.map(String::from),
+            processing_job_id: None,
+            auto_proc_id: None,
+            space_group: None,
+            refined_cell_a: None,
+            refined_cell_b: None,
+            refined_cell_c: None,
+            refined_cell_alpha: None,
+            refined_cell_beta: None,
+            refined_cell_gamma: None,
+            auto_proc_scaling_id: None,
+        }
+    }
+
+    #[test]
+    fn messages_no_longer_than_the_cap_are_unchanged() {
+        let auto_processing = processed_with_message(Some("indexing failed"));
+        assert_eq!(
+            auto_processing.truncated_processing_message(15).as_deref(),
+            Some("indexing failed")
+        );
+        assert_eq!(
+            auto_processing.truncated_processing_message(200).as_deref(),
+            Some("indexing failed")
+        );
+    }
+
+    #[test]
+    fn messages_longer_than_the_cap_are_clipped_with_an_ellipsis() {
+        let auto_processing = processed_with_message(Some("indexing failed"));
+        assert_eq!(
+            auto_processing.truncated_processing_message(14).as_deref(),
+            Some("indexing faile…")
+        );
+        assert_eq!(
+            auto_processing.truncated_processing_message(0).as_deref(),
+            Some("…")
+        );
+    }
+
+    #[test]
+    fn messages_are_clipped_on_character_boundaries() {
+        let auto_processing = processed_with_message(Some("Å → Ångström"));
+        assert_eq!(
+            auto_processing.truncated_processing_message(3).as_deref(),
+            Some("Å →…")
+        );
+    }
+
+    #[test]
+    fn absent_messages_remain_absent() {
+        assert_eq!(
+            processed_with_message(None).truncated_processing_message(10),
+            None
+        );
+    }
+
+    #[test]
+    fn disposition_defaults_by_file_type() {
+        assert_eq!(
+            ContentDisposition::for_file_type(Some(AttachmentFileType::Graph)),
+            Some(ContentDisposition::Inline)
+        );
+        assert_eq!(
+            ContentDisposition::for_file_type(Some(AttachmentFileType::Log)),
+            Some(ContentDisposition::Attachment)
+        );
+        assert_eq!(
+            ContentDisposition::for_file_type(Some(AttachmentFileType::Result)),
+            Some(ContentDisposition::Attachment)
+        );
+        assert_eq!(
+            ContentDisposition::for_file_type(Some(AttachmentFileType::Debug)),
+            None
+        );
+        assert_eq!(
+            ContentDisposition::for_file_type(Some(AttachmentFileType::Input)),
+            None
+        );
+        assert_eq!(ContentDisposition::for_file_type(None), None);
+    }
+
+    #[test]
+    fn disposition_header_names_the_file() {
+        assert_eq!(
+            ContentDisposition::Attachment.header_value(Some("xia2.txt")),
+            r#"attachment; filename="xia2.txt""#
+        );
+        assert_eq!(
+            ContentDisposition::Inline.header_value(Some("fast_dp.png")),
+            r#"inline; filename="fast_dp.png""#
+        );
+        assert_eq!(ContentDisposition::Inline.header_value(None), "inline");
+        assert_eq!(
+            ContentDisposition::Attachment.header_value(Some("a \"b\"\\c\u{e9}.log")),
+            r#"attachment; filename="a _b__c_.log""#
+        );
+    }
+
+    /// A data collection attachment model of the file, with the type as recorded in the database
+    fn data_collection_model(
+        file_full_path: &str,
+        file_type: Option<&str>,
+    ) -> data_collection_file_attachment::Model {
+        data_collection_file_attachment::Model {
+            data_collection_file_attachment_id: 92,
+            data_collection_id: 1,
+            file_full_path: file_full_path.to_string(),
+            file_type: file_type.map(String::from),
+        }
+    }
+
+    #[test]
+    fn data_collection_attachment_types_are_mapped_where_shared() {
+        for (file_type, expected) in [
+            (Some("log"), Some(AttachmentFileType::Log)),
+            (Some("LOG"), Some(AttachmentFileType::Log)),
+            (Some("snapshot"), None),
+            (Some(""), None),
+            (None, None),
+        ] {
+            let attachment = DataCollectionFileAttachment::from(data_collection_model(
+                "/dls/i03/data/2024/cm12345-1/collection",
+                file_type,
+            ));
+            assert_eq!(attachment.id, 92);
+            assert_eq!(attachment.data_collection_id, 1);
+            assert_eq!(attachment.file_name.as_deref(), Some("collection"));
+            assert_eq!(attachment.file_type, expected, "{file_type:?}");
+        }
+    }
+
+    #[test]
+    fn data_collection_attachment_type_informs_how_it_is_served() {
+        let log = DataCollectionFileAttachment::from(data_collection_model(
+            "/dls/i03/data/2024/cm12345-1/collection",
+            Some("log"),
+        ));
+        assert_eq!(log.inferred_content_type(), Some("text/plain"));
+        assert_eq!(
+            log.default_disposition(),
+            Some(ContentDisposition::Attachment)
+        );
+
+        let untyped = DataCollectionFileAttachment::from(data_collection_model(
+            "/dls/i03/data/2024/cm12345-1/collection",
+            None,
+        ));
+        assert_eq!(untyped.inferred_content_type(), None);
+        assert_eq!(untyped.default_disposition(), None);
+
+        let snapshot = DataCollectionFileAttachment::from(data_collection_model(
+            "/dls/i03/data/2024/cm12345-1/snapshot.png",
+            Some("snapshot"),
+        ));
+        assert_eq!(snapshot.inferred_content_type(), Some("image/png"));
+        assert_eq!(snapshot.default_disposition(), None);
+    }
+}
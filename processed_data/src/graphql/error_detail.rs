@@ -0,0 +1,182 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    ErrorExtensionValues, Response,
+};
+use opentelemetry::trace::TraceContextExt;
+use std::sync::Arc;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// The message with which errors lacking a code are replaced under [`ErrorDetail::Minimal`]
+const MASKED_ERROR_MESSAGE: &str = "Internal server error";
+
+/// The amount of detail included in the errors returned to clients
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorDetail {
+    /// Errors are returned as raised, including the messages of underlying database and S3 errors
+    #[default]
+    Full,
+    /// Errors which were not deliberately raised with a code are replaced by a generic message
+    Minimal,
+}
+
+/// An [`ExtensionFactory`] which applies the [`ErrorDetail`] in the schema data to the errors of each operation, tagging them with the request id
+#[derive(Debug, Default)]
+pub struct ErrorMasking;
+
+impl ExtensionFactory for ErrorMasking {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ErrorMaskingExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`ErrorMasking`]
+struct ErrorMaskingExtension;
+
+#[async_trait::async_trait]
+impl Extension for ErrorMaskingExtension {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+        if response.errors.is_empty() {
+            return response;
+        }
+        let error_detail = ctx.data_opt::<ErrorDetail>().copied().unwrap_or_default();
+        let span_context = Span::current().context().span().span_context().clone();
+        let request_id = span_context
+            .is_valid()
+            .then(|| span_context.trace_id().to_string());
+        for error in &mut response.errors {
+            let extensions = error
+                .extensions
+                .get_or_insert_with(ErrorExtensionValues::default);
+            if error_detail == ErrorDetail::Minimal && extensions.get("code").is_none() {
+                error.message = MASKED_ERROR_MESSAGE.to_string();
+                error.source = None;
+                extensions.set("code", "INTERNAL_SERVER_ERROR");
+            }
+            if let Some(request_id) = &request_id {
+                extensions.set("requestId", request_id.as_str());
+            }
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, ErrorExtensions, Object, Schema};
+    use opentelemetry::trace::TracerProvider;
+    use tracing::Instrument;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// A query whose fields fail with and without an error code
+    struct FailingQuery;
+
+    #[Object]
+    impl FailingQuery {
+        /// Fails as an underlying database error would
+        async fn internal(&self) -> async_graphql::Result<u32> {
+            Err(async_graphql::Error::new(
+                "Connection refused: mysql://ispyb:secret@db:3306",
+            ))
+        }
+
+        /// Fails deliberately, with an error code
+        async fn forbidden(&self) -> async_graphql::Result<u32> {
+            Err(async_graphql::Error::new("Missing scope")
+                .extend_with(|_, extensions| extensions.set("code", "FORBIDDEN")))
+        }
+    }
+
+    /// Executes the query with the level of error detail, returning the message and code of each error
+    async fn errors(
+        error_detail: Option<ErrorDetail>,
+        query: &str,
+    ) -> Vec<(String, Option<async_graphql::Value>)> {
+        let mut schema =
+            Schema::build(FailingQuery, EmptyMutation, EmptySubscription).extension(ErrorMasking);
+        if let Some(error_detail) = error_detail {
+            schema = schema.data(error_detail);
+        }
+        schema
+            .finish()
+            .execute(query)
+            .await
+            .errors
+            .into_iter()
+            .map(|error| {
+                let code = error
+                    .extensions
+                    .and_then(|extensions| extensions.get("code").cloned());
+                (error.message, code)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn internal_errors_are_returned_in_full_by_default() {
+        for error_detail in [None, Some(ErrorDetail::Full)] {
+            assert_eq!(
+                errors(error_detail, "{ internal }").await,
+                [(
+                    "Connection refused: mysql://ispyb:secret@db:3306".to_string(),
+                    None
+                )]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn internal_errors_are_masked_with_minimal_detail() {
+        assert_eq!(
+            errors(Some(ErrorDetail::Minimal), "{ internal }").await,
+            [(
+                MASKED_ERROR_MESSAGE.to_string(),
+                Some("INTERNAL_SERVER_ERROR".into())
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_with_a_code_are_never_masked() {
+        for error_detail in [ErrorDetail::Full, ErrorDetail::Minimal] {
+            assert_eq!(
+                errors(Some(error_detail), "{ forbidden }").await,
+                [("Missing scope".to_string(), Some("FORBIDDEN".into()))]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn errors_are_tagged_with_the_trace_id_of_the_request() {
+        let tracer_provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let span = tracing::info_span!("request");
+        let trace_id = span.context().span().span_context().trace_id().to_string();
+        let schema = Schema::build(FailingQuery, EmptyMutation, EmptySubscription)
+            .extension(ErrorMasking)
+            .data(ErrorDetail::Minimal)
+            .finish();
+
+        let response = schema
+            .execute("{ internal forbidden }")
+            .instrument(span)
+            .await;
+
+        assert_eq!(response.errors.len(), 2);
+        for error in response.errors {
+            assert_eq!(
+                error.extensions.unwrap().get("requestId"),
+                Some(&trace_id.as_str().into())
+            );
+        }
+    }
+}
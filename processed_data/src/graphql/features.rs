@@ -0,0 +1,132 @@
+use async_graphql::{Context, ErrorExtensions};
+use clap::ValueEnum;
+use std::collections::HashSet;
+
+/// An optional capability of the service, enabled per deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Feature {
+    /// The diagnostic query returning the SQL run by the dataloaders
+    Explain,
+    /// Subscriptions streaming newly written attachments
+    Subscriptions,
+}
+
+impl Feature {
+    /// The name by which the feature is enabled
+    fn name(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_else(|| format!("{self:?}"))
+    }
+}
+
+/// The features enabled in this deployment, injected into the schema as data
+#[derive(Debug, Clone, Default)]
+pub struct FeatureSet(HashSet<Feature>);
+
+impl FeatureSet {
+    /// Creates a set of the supplied features
+    pub fn new(features: impl IntoIterator<Item = Feature>) -> Self {
+        Self(features.into_iter().collect())
+    }
+
+    /// Returns true if the feature is enabled
+    pub fn contains(&self, feature: Feature) -> bool {
+        self.0.contains(&feature)
+    }
+}
+
+/// Returns true if the feature is enabled in the [`FeatureSet`] in the schema data
+pub fn feature_enabled(ctx: &Context<'_>, feature: Feature) -> bool {
+    ctx.data_opt::<FeatureSet>()
+        .is_some_and(|features| features.contains(feature))
+}
+
+/// Rejects the use of a capability with the `FEATURE_DISABLED` code unless the feature is enabled
+pub fn require_feature(ctx: &Context<'_>, feature: Feature) -> async_graphql::Result<()> {
+    if feature_enabled(ctx, feature) {
+        Ok(())
+    } else {
+        Err(
+            async_graphql::Error::new(format!("The {} feature is not enabled", feature.name()))
+                .extend_with(|_, extensions| extensions.set("code", "FEATURE_DISABLED")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Schema};
+
+    /// A query exposing a capability which requires the explain feature
+    struct TestQuery;
+
+    #[Object]
+    impl TestQuery {
+        /// Succeeds only if the explain feature is enabled
+        async fn explain(&self, ctx: &Context<'_>) -> async_graphql::Result<bool> {
+            require_feature(ctx, Feature::Explain)?;
+            Ok(true)
+        }
+    }
+
+    /// Executes the explain query with the features in the schema data, if any
+    async fn explain(features: Option<FeatureSet>) -> async_graphql::Response {
+        let mut schema = Schema::build(TestQuery, EmptyMutation, EmptySubscription);
+        if let Some(features) = features {
+            schema = schema.data(features);
+        }
+        schema.finish().execute(Request::new("{ explain }")).await
+    }
+
+    #[test]
+    fn features_are_parsed_by_name() {
+        assert_eq!(Feature::from_str("explain", false), Ok(Feature::Explain));
+        assert_eq!(
+            Feature::from_str("subscriptions", false),
+            Ok(Feature::Subscriptions)
+        );
+        assert!(Feature::from_str("uploads", false).is_err());
+        assert_eq!(Feature::Explain.name(), "explain");
+        assert_eq!(Feature::Subscriptions.name(), "subscriptions");
+    }
+
+    #[test]
+    fn feature_set_contains_only_the_supplied_features() {
+        let features = FeatureSet::new([Feature::Subscriptions]);
+        assert!(features.contains(Feature::Subscriptions));
+        assert!(!features.contains(Feature::Explain));
+        assert!(!FeatureSet::default().contains(Feature::Subscriptions));
+    }
+
+    #[tokio::test]
+    async fn enabled_features_may_be_used() {
+        let response = explain(Some(FeatureSet::new([Feature::Explain]))).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "explain": true })
+        );
+    }
+
+    #[tokio::test]
+    async fn disabled_features_are_rejected() {
+        for features in [
+            None,
+            Some(FeatureSet::default()),
+            Some(FeatureSet::new([Feature::Subscriptions])),
+        ] {
+            let response = explain(features).await;
+            assert_eq!(response.errors.len(), 1);
+            assert_eq!(
+                response.errors[0].message,
+                "The explain feature is not enabled"
+            );
+            assert_eq!(
+                response.errors[0].extensions.as_ref().unwrap().get("code"),
+                Some(&async_graphql::Value::from("FEATURE_DISABLED"))
+            );
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    Context, ErrorExtensions, Pos, ServerResult, Value,
+};
+use clap::ValueEnum;
+use std::{collections::HashSet, sync::Arc};
+
+/// An entity which the subgraph can resolve for the federation router
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum FederationEntity {
+    /// Data collections, keyed on their id
+    DataCollection,
+}
+
+impl FederationEntity {
+    /// The name of the GraphQL type of the entity
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::DataCollection => "DataCollection",
+        }
+    }
+}
+
+/// The entities resolved by this deployment, injected into the schema as data
+#[derive(Debug, Clone)]
+pub struct FederationEntities(HashSet<FederationEntity>);
+
+impl FederationEntities {
+    /// Creates a set of the supplied entities
+    pub fn new(entities: impl IntoIterator<Item = FederationEntity>) -> Self {
+        Self(entities.into_iter().collect())
+    }
+
+    /// Returns true if the entity is resolved
+    pub fn contains(&self, entity: FederationEntity) -> bool {
+        self.0.contains(&entity)
+    }
+
+    /// Removes the `@key` directives of the entities which are not resolved from the SDL, such that they are not
+    /// advertised to the router
+    pub fn omit_disabled(&self, sdl: &str) -> String {
+        let disabled = FederationEntity::value_variants()
+            .iter()
+            .filter(|entity| !self.contains(**entity))
+            .map(FederationEntity::type_name)
+            .collect::<Vec<_>>();
+        sdl.split('\n')
+            .map(|line| {
+                let type_name = line
+                    .strip_prefix("extend ")
+                    .unwrap_or(line)
+                    .strip_prefix("type ")
+                    .and_then(|definition| definition.split([' ', '{']).next());
+                match type_name {
+                    Some(type_name) if disabled.contains(&type_name) => strip_keys(line),
+                    _ => line.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Default for FederationEntities {
+    /// All entities are resolved by default
+    fn default() -> Self {
+        Self::new(FederationEntity::value_variants().iter().copied())
+    }
+}
+
+/// Removes all `@key(...)` directives from the line of a type definition
+fn strip_keys(line: &str) -> String {
+    let mut line = line.to_string();
+    while let Some(start) = line.find(" @key(") {
+        let end = line[start..]
+            .find(')')
+            .map_or(line.len(), |end| start + end + 1);
+        line.replace_range(start..end, "");
+    }
+    line
+}
+
+/// Rejects the resolution of the entity with the `ENTITY_DISABLED` code unless it is enabled in the [`FederationEntities`] in the schema data
+pub fn require_entity(ctx: &Context<'_>, entity: FederationEntity) -> async_graphql::Result<()> {
+    if ctx
+        .data_opt::<FederationEntities>()
+        .cloned()
+        .unwrap_or_default()
+        .contains(entity)
+    {
+        Ok(())
+    } else {
+        Err(async_graphql::Error::new(format!(
+            "The {} entity is not resolved by this deployment",
+            entity.type_name()
+        ))
+        .extend_with(|_, extensions| extensions.set("code", "ENTITY_DISABLED")))
+    }
+}
+
+/// An [`ExtensionFactory`] which omits the entities which are not resolved from the SDL served to the router through `_service`
+#[derive(Debug, Default)]
+pub struct EntityFilter;
+
+impl ExtensionFactory for EntityFilter {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(EntityFilterExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`EntityFilter`]
+struct EntityFilterExtension;
+
+#[async_trait::async_trait]
+impl Extension for EntityFilterExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let is_service_sdl = info.parent_type == "_Service" && info.name == "sdl";
+        let value = next.run(ctx, info).await?;
+        match (is_service_sdl, value) {
+            (true, Some(Value::String(sdl))) => {
+                let entities = ctx
+                    .data_opt::<FederationEntities>()
+                    .cloned()
+                    .unwrap_or_default();
+                Ok(Some(Value::String(entities.omit_disabled(&sdl))))
+            }
+            (_, value) => Ok(value),
+        }
+    }
+}
+
+/// The fields added to the query root whenever the schema has entities, whether or not federation is enabled
+const FEDERATION_FIELDS: [&str; 2] = ["_entities", "_service"];
+
+/// The types added to the schema alongside the [`FEDERATION_FIELDS`]
+const FEDERATION_TYPES: [&str; 3] = ["scalar _Any", "union _Entity", "type _Service"];
+
+/// Removes the [`FEDERATION_FIELDS`] and [`FEDERATION_TYPES`] from the SDL of a standalone deployment
+pub fn omit_federation(sdl: &str) -> String {
+    let mut in_federation_type = false;
+    sdl.split('\n')
+        .filter(|line| {
+            if in_federation_type {
+                in_federation_type = line.trim() != "}";
+                return false;
+            }
+            if FEDERATION_TYPES
+                .iter()
+                .any(|definition| line.starts_with(definition))
+            {
+                in_federation_type = line.trim_end().ends_with('{');
+                return false;
+            }
+            let field = line.trim_start();
+            !FEDERATION_FIELDS.iter().any(|name| {
+                field
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.starts_with(['(', ':']))
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An [`ExtensionFactory`] which rejects the [`FEDERATION_FIELDS`] of a standalone deployment with the
+/// `FEDERATION_DISABLED` code
+#[derive(Debug, Default)]
+pub struct StandaloneGuard;
+
+impl ExtensionFactory for StandaloneGuard {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(StandaloneGuardExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`StandaloneGuard`]
+struct StandaloneGuardExtension;
+
+#[async_trait::async_trait]
+impl Extension for StandaloneGuardExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if info.parent_type == "Query" && FEDERATION_FIELDS.contains(&info.name) {
+            return Err(async_graphql::Error::new(format!(
+                "{} is not served by a standalone deployment",
+                info.name
+            ))
+            .extend_with(|_, extensions| extensions.set("code", "FEDERATION_DISABLED"))
+            .into_server_error(Pos::default()));
+        }
+        next.run(ctx, info).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::{root_schema_builder, schema_sdl};
+    use async_graphql::Request;
+
+    #[test]
+    fn federation_fields_and_types_are_omitted() {
+        let sdl = [
+            "type DataCollection {",
+            "\tid: Int!",
+            "}",
+            "type Query {",
+            "\tdataCollection(id: Int!): DataCollection",
+            "\t_service: _Service!",
+            "\t_entities(representations: [_Any!]!): [_Entity]!",
+            "}",
+            "scalar _Any",
+            "union _Entity = DataCollection",
+            "type _Service {",
+            "\tsdl: String",
+            "}",
+            "schema {",
+            "\tquery: Query",
+            "}",
+        ]
+        .join("\n");
+
+        assert_eq!(
+            omit_federation(&sdl),
+            [
+                "type DataCollection {",
+                "\tid: Int!",
+                "}",
+                "type Query {",
+                "\tdataCollection(id: Int!): DataCollection",
+                "}",
+                "schema {",
+                "\tquery: Query",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn keys_of_disabled_entities_are_omitted() {
+        let sdl = [
+            r#"type DataCollection @key(fields: "id") {"#,
+            "\tid: Int!",
+            "}",
+            r#"extend type DataCollection @key(fields: "id") @key(fields: "name") {"#,
+            "}",
+            "type Query {",
+            "\tdataCollection(id: Int!): DataCollection",
+            "}",
+        ]
+        .join("\n");
+
+        assert_eq!(FederationEntities::default().omit_disabled(&sdl), sdl);
+        assert_eq!(
+            FederationEntities::new([FederationEntity::DataCollection]).omit_disabled(&sdl),
+            sdl
+        );
+        assert_eq!(
+            FederationEntities::new([]).omit_disabled(&sdl),
+            [
+                "type DataCollection {",
+                "\tid: Int!",
+                "}",
+                "extend type DataCollection {",
+                "}",
+                "type Query {",
+                "\tdataCollection(id: Int!): DataCollection",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn sdl_advertises_only_the_enabled_entities() {
+        let schema = root_schema_builder(false).finish();
+        let key = r#"DataCollection @key(fields: "id")"#;
+
+        let sdl = schema_sdl(&schema, false, &FederationEntities::default());
+        assert!(sdl.contains(key), "{sdl}");
+        let sdl = schema_sdl(&schema, false, &FederationEntities::new([]));
+        assert!(!sdl.contains("@key"), "{sdl}");
+
+        for (entities, advertised) in [
+            (FederationEntities::default(), true),
+            (FederationEntities::new([]), false),
+        ] {
+            let response = schema
+                .execute(Request::new("{ _service { sdl } }").data(entities))
+                .await;
+            let data = response.data.into_json().unwrap();
+            let sdl = data["_service"]["sdl"].as_str().unwrap();
+            assert_eq!(sdl.contains(key), advertised, "{sdl}");
+        }
+    }
+
+    #[tokio::test]
+    async fn disabled_entities_are_not_resolved() {
+        let schema = root_schema_builder(false).finish();
+        let query = r#"{ _entities(representations: [{ __typename: "DataCollection", id: 1 }]) { __typename } }"#;
+
+        let response = schema
+            .execute(Request::new(query).data(FederationEntities::new([])))
+            .await;
+        assert_eq!(
+            response.errors[0].extensions.as_ref().unwrap().get("code"),
+            Some(&Value::from("ENTITY_DISABLED"))
+        );
+
+        let response = schema.execute(Request::new(query)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "_entities": [{ "__typename": "DataCollection" }] })
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::S3Bucket;
+use async_graphql::{Enum, SimpleObject};
+use aws_sdk_s3::Client;
+use sea_orm::DatabaseConnection;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// The health of a dependency of the service
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DependencyStatus {
+    /// The dependency responded within the expected latency
+    Ok,
+    /// The dependency responded, but slower than the expected latency
+    Degraded,
+    /// The dependency failed to respond, or responded with an error
+    Down,
+}
+
+/// The outcome of a check of a dependency
+#[derive(Debug, Clone, SimpleObject)]
+pub struct DependencyHealth {
+    /// The health of the dependency
+    pub status: DependencyStatus,
+    /// The time taken for the dependency to respond, or for the check to time out, in milliseconds
+    pub latency_ms: f64,
+}
+
+/// The health of each dependency of the service
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ServiceReadiness {
+    /// The health of the ISPyB database
+    pub database: DependencyHealth,
+    /// The health of the S3 bucket in which files are stored
+    pub s3: DependencyHealth,
+}
+
+/// Checks the health of the dependencies of the service
+#[derive(Debug, Clone)]
+pub struct DependencyChecks {
+    /// The client with which the bucket is checked
+    s3_client: Client,
+    /// The bucket in which files are stored
+    bucket: S3Bucket,
+    /// The duration after which a check is abandoned and the dependency reported down
+    timeout: Duration,
+    /// The latency above which a responsive dependency is reported degraded
+    degraded_latency: Duration,
+}
+
+impl DependencyChecks {
+    /// Creates checks of the database and of `bucket`, abandoned after `timeout` and reported degraded above `degraded_latency`
+    pub fn new(
+        s3_client: Client,
+        bucket: S3Bucket,
+        timeout: Duration,
+        degraded_latency: Duration,
+    ) -> Self {
+        Self {
+            s3_client,
+            bucket,
+            timeout,
+            degraded_latency,
+        }
+    }
+
+    /// Checks the database and the bucket concurrently
+    pub async fn check(&self, database: &DatabaseConnection) -> ServiceReadiness {
+        let (database, s3) = tokio::join!(
+            self.time(database.ping()),
+            self.time(self.s3_client.head_bucket().bucket(&*self.bucket).send()),
+        );
+        ServiceReadiness { database, s3 }
+    }
+
+    /// Times the check, reporting the dependency down if it fails or does not complete before the timeout
+    async fn time<T, E>(&self, check: impl Future<Output = Result<T, E>>) -> DependencyHealth {
+        let start = Instant::now();
+        let outcome = tokio::time::timeout(self.timeout, check).await;
+        let latency = start.elapsed();
+        let status = match outcome {
+            Ok(Ok(_)) if latency <= self.degraded_latency => DependencyStatus::Ok,
+            Ok(Ok(_)) => DependencyStatus::Degraded,
+            Ok(Err(_)) | Err(_) => DependencyStatus::Down,
+        };
+        DependencyHealth {
+            status,
+            latency_ms: latency.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::test_database::TestDatabase;
+    use aws_credential_types::Credentials;
+    use aws_sdk_s3::config::Region;
+    use axum::{http::StatusCode, routing::get, Router};
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+
+    /// A client of a local mock S3 endpoint, answering every request for a bucket with the status
+    async fn mock_s3_client(status: StatusCode) -> Client {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let socket_addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/:bucket", get(move || async move { status }));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Client::from_conf(
+            aws_sdk_s3::config::Builder::new()
+                .credentials_provider(Credentials::new("key", "secret", None, None, "test"))
+                .endpoint_url(format!("http://{socket_addr}"))
+                .force_path_style(true)
+                .region(Region::new("us-east-1"))
+                .build(),
+        )
+    }
+
+    /// Checks of the bucket through the client, abandoned after a second and degraded above half a second
+    fn checks(s3_client: Client) -> DependencyChecks {
+        DependencyChecks::new(
+            s3_client,
+            "processed".parse().unwrap(),
+            Duration::from_secs(1),
+            Duration::from_millis(500),
+        )
+    }
+
+    #[tokio::test]
+    async fn responsive_dependencies_are_ok() {
+        let database = TestDatabase::start().await;
+        let checks = checks(mock_s3_client(StatusCode::OK).await);
+
+        let readiness = checks.check(&database.connection).await;
+
+        assert_eq!(readiness.database.status, DependencyStatus::Ok);
+        assert_eq!(readiness.s3.status, DependencyStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn unreachable_database_is_down_whilst_s3_is_ok() {
+        let checks = checks(mock_s3_client(StatusCode::OK).await);
+
+        let readiness = checks.check(&DatabaseConnection::Disconnected).await;
+
+        assert_eq!(readiness.database.status, DependencyStatus::Down);
+        assert_eq!(readiness.s3.status, DependencyStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn missing_bucket_is_down() {
+        let checks = checks(mock_s3_client(StatusCode::NOT_FOUND).await);
+
+        let readiness = checks.check(&DatabaseConnection::Disconnected).await;
+
+        assert_eq!(readiness.s3.status, DependencyStatus::Down);
+    }
+
+    #[tokio::test]
+    async fn slow_dependency_is_degraded() {
+        let checks = checks(mock_s3_client(StatusCode::OK).await);
+
+        let health = checks
+            .time(async {
+                tokio::time::sleep(Duration::from_millis(600)).await;
+                Ok::<_, ()>(())
+            })
+            .await;
+
+        assert_eq!(health.status, DependencyStatus::Degraded);
+        assert!(health.latency_ms >= 600.0, "{}", health.latency_ms);
+    }
+
+    #[tokio::test]
+    async fn unresponsive_dependency_is_down_once_the_check_times_out() {
+        let checks = checks(mock_s3_client(StatusCode::OK).await);
+
+        let health = checks.time(std::future::pending::<Result<(), ()>>()).await;
+
+        assert_eq!(health.status, DependencyStatus::Down);
+        assert!(health.latency_ms >= 1000.0, "{}", health.latency_ms);
+        assert!(health.latency_ms < 1500.0, "{}", health.latency_ms);
+    }
+}
@@ -0,0 +1,137 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    ErrorExtensions, Pos, Response,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// A shared switch which, when enabled, causes all GraphQL operations to be rejected
+#[derive(Debug, Clone)]
+pub struct MaintenanceMode {
+    /// Whether maintenance mode is currently enabled
+    enabled: Arc<AtomicBool>,
+    /// The period clients are advised to wait before retrying
+    retry_after: Duration,
+}
+
+impl MaintenanceMode {
+    /// Creates a maintenance mode switch in the supplied initial state
+    pub fn new(enabled: bool, retry_after: Duration) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            retry_after,
+        }
+    }
+
+    /// Returns true if maintenance mode is currently enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables maintenance mode
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed)
+    }
+}
+
+/// An [`ExtensionFactory`] which rejects operations whilst the [`MaintenanceMode`] in the schema data is enabled
+#[derive(Debug, Default)]
+pub struct MaintenanceModeGuard;
+
+impl ExtensionFactory for MaintenanceModeGuard {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(MaintenanceModeGuardExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`MaintenanceModeGuard`]
+struct MaintenanceModeGuardExtension;
+
+#[async_trait::async_trait]
+impl Extension for MaintenanceModeGuardExtension {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        match ctx.data_opt::<MaintenanceMode>() {
+            Some(maintenance_mode) if maintenance_mode.is_enabled() => {
+                let retry_after = maintenance_mode.retry_after.as_secs();
+                Response::from_errors(vec![async_graphql::Error::new(
+                    "Service is undergoing maintenance",
+                )
+                .extend_with(|_, extensions| {
+                    extensions.set("code", "SERVICE_UNAVAILABLE");
+                    extensions.set("retryAfter", retry_after);
+                })
+                .into_server_error(Pos::default())])
+            }
+            _ => next.run(ctx, operation_name).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::root_schema_builder;
+
+    /// The code in the extensions of the first error of the response
+    fn error_code(response: &Response) -> Option<async_graphql::Value> {
+        response.errors[0]
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code").cloned())
+    }
+
+    #[tokio::test]
+    async fn operations_are_rejected_whilst_enabled() {
+        let maintenance_mode = MaintenanceMode::new(true, Duration::from_secs(30));
+        let schema = root_schema_builder(true).data(maintenance_mode).finish();
+
+        let response = schema.execute("{ __typename }").await;
+
+        assert_eq!(response.data, async_graphql::Value::Null);
+        assert_eq!(error_code(&response), Some("SERVICE_UNAVAILABLE".into()));
+        assert_eq!(
+            response.errors[0]
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.get("retryAfter").cloned()),
+            Some(30.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn operations_are_executed_whilst_disabled() {
+        let maintenance_mode = MaintenanceMode::new(false, Duration::from_secs(30));
+        let schema = root_schema_builder(true).data(maintenance_mode).finish();
+
+        let response = schema.execute("{ __typename }").await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "__typename": "Query" })
+        );
+    }
+
+    #[tokio::test]
+    async fn toggling_takes_effect_on_the_next_operation() {
+        let maintenance_mode = MaintenanceMode::new(false, Duration::from_secs(30));
+        let schema = root_schema_builder(true)
+            .data(maintenance_mode.clone())
+            .finish();
+
+        maintenance_mode.set_enabled(true);
+        assert!(!schema.execute("{ __typename }").await.errors.is_empty());
+        maintenance_mode.set_enabled(false);
+        assert!(schema.execute("{ __typename }").await.errors.is_empty());
+    }
+}
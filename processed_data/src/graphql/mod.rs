@@ -1,190 +1,1449 @@
+/// Parsing of ranges of the bytes of objects
+mod byte_range;
+/// Suspension of database queries whilst the database is failing
+mod circuit_breaker;
+/// Reporting of query complexity
+mod complexity;
+/// Reporting of exhaustion of the database connection pool
+mod db_pool;
 /// Collection of graphql entities
 mod entities;
-use crate::S3Bucket;
+/// Masking of the details of internal errors
+mod error_detail;
+/// Optional capabilities enabled per deployment
+mod features;
+/// Selection of the entities resolved for the federation router
+mod federation;
+/// Checks of the health of the dependencies of the service
+mod health;
+/// Rejection of operations whilst the service is under maintenance
+mod maintenance;
+/// Mapping of file system paths to S3 object keys
+mod object_key;
+/// Concurrent fetching of the metadata of many objects
+mod object_metadata;
+/// Validation of the arguments of paginated fields
+mod pagination;
+/// Limiting of the number of URLs presigned per request
+mod presign_limit;
+/// Concurrent presigning of the download links of many objects
+mod presign_loader;
+/// Grading of the quality of processed data
+mod quality;
+/// Limiting of the rate of requests from each client
+mod rate_limit;
+/// Spawning of dataloader tasks which are aborted along with their request
+mod request_tasks;
+/// Limiting of the number of S3 operations in flight across the process
+mod s3_concurrency;
+/// Custom scalars, with the URLs of their specifications
+mod scalars;
+/// Scopes required to access fields
+mod scopes;
+/// Signing of tokens referencing auto processing
+mod share;
+/// Broadcasting of changes to the processing status of auto processing
+mod status_events;
+/// Streaming of newly written data
+mod subscription;
+/// Limiting of the number of subscriptions active on each connection
+mod subscription_limit;
+/// A containerised database loaded with fixture rows, for tests of the dataloaders
+#[cfg(test)]
+mod test_database;
+/// Reporting of the time taken to handle requests
+mod timing;
+/// Parsing of visit identifiers
+mod visit;
 use async_graphql::{
     dataloader::{DataLoader, Loader},
-    ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Schema, SchemaBuilder,
+    ComplexObject, Context, EmptyMutation, ErrorExtensions, Object, SDLExportOptions, Schema,
+    SchemaBuilder,
 };
-use aws_sdk_s3::presigning::PresigningConfig;
+pub use circuit_breaker::CircuitBreaker;
+pub use complexity::{IncludeQueryComplexity, INCLUDE_QUERY_COMPLEXITY_HEADER};
+pub use entities::{
+    AttachmentFileType, DownloadableFileTypes, ProcessingMessageMaxLength, ProcessingStatusCodes,
+    StatsPrecision,
+};
+pub use error_detail::ErrorDetail;
+pub use features::{Feature, FeatureSet};
+pub use federation::{FederationEntities, FederationEntity};
+pub use health::DependencyChecks;
+pub use maintenance::MaintenanceMode;
+pub use object_key::ObjectKeyRewrite;
+pub use object_metadata::ObjectMetadataDataLoader;
+pub use pagination::MaxPageSize;
+pub use presign_limit::MaxPresignsPerRequest;
+pub use presign_loader::PresignDataLoader;
+pub use quality::{
+    AnomalousSignificanceThreshold, EffectiveResolutionExponent, GradeThresholds,
+    QualityGradeThresholds,
+};
+pub use rate_limit::{RateLimitKey, RateLimiter};
+pub use s3_concurrency::S3ConcurrencyLimit;
+pub use scopes::{GrantedScopes, GRANTED_SCOPES_HEADER};
+pub use share::ShareTokenSigner;
+pub use status_events::{spawn_notification_source, ProcessingStatusEvents};
+pub use subscription::AttachmentPollInterval;
+pub use subscription_limit::{ConnectionSubscriptions, MaxSubscriptionsPerConnection};
+pub use timing::{IncludeTiming, INCLUDE_TIMING_HEADER};
+
+use byte_range::ByteRange;
+use circuit_breaker::GuardedConnection;
+use complexity::QueryComplexityReporter;
+use db_pool::PoolExhaustion;
+use derive_more::Deref;
 use entities::{
-    AutoProcFileAttachment, AutoProcScalingStatics, AutoProcessing, DataCollection, ProcessingJob,
-    StatisticsType,
+    Attachment, AttachmentOrder, AutoProcFileAttachment, AutoProcScaling, AutoProcScalingStatics,
+    AutoProcessing, AutoProcessingWithOverall, CompletenessByShell, ContentDisposition,
+    DataCollection, DataCollectionFileAttachment, DatabaseInfo, LoaderQueries, Point, PresignedUrl,
+    ProcessingJob, ProcessingStatus, ProcessingStatusCount, StatisticsSummary, StatisticsType,
+    StoredAttachment,
 };
+use error_detail::ErrorMasking;
+use features::{feature_enabled, require_feature};
+use federation::{omit_federation, require_entity, EntityFilter, StandaloneGuard};
+use health::ServiceReadiness;
+use maintenance::MaintenanceModeGuard;
 use models::{
     auto_proc, auto_proc_integration, auto_proc_program, auto_proc_program_attachment,
-    auto_proc_scaling, auto_proc_scaling_statistics, processing_job, processing_job_parameter,
+    auto_proc_scaling, auto_proc_scaling_statistics, bl_session, data_collection,
+    data_collection_file_attachment, data_collection_group, processing_job,
+    processing_job_parameter, proposal,
+};
+use object_metadata::ObjectMetadata;
+use pagination::Page;
+use presign_limit::PresignLimit;
+use presign_loader::PresignKey;
+use quality::QualityGrade;
+use rate_limit::RateLimitGuard;
+use request_tasks::RequestTasks;
+use scalars::{JsonObject, Timestamp};
+use scopes::{
+    requires_scope, RequiredScopes, ADMIN_SCOPE, INTERNAL_ATTACHMENT_SCOPE,
+    PROCESSING_MESSAGE_SCOPE,
 };
 use sea_orm::{
-    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, Statement,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
+    IdenStatic, Iterable, QueryFilter, QueryOrder, QuerySelect, Statement,
 };
-use sea_query::{self, Asterisk, Expr};
-use std::time::Duration;
-use std::{collections::HashMap, ops::Deref};
-use tracing::{instrument, Span};
-use url::Url;
+use sea_query::{
+    self, Alias, Asterisk, Cond, Expr, Func, JoinType, Order, SelectStatement, SimpleExpr,
+};
+use share::ShareReference;
+use std::time::{Duration, Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
+use subscription::Subscription;
+use subscription_limit::SubscriptionLimit;
+use timing::ExecutionTimer;
+use tracing::{instrument, warn, Span};
+use visit::Visit;
 
 /// The GraphQL schema exposed by the service
-pub type RootSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+pub type RootSchema = Schema<Query, EmptyMutation, Subscription>;
+
+/// Settings applied to the dataloaders of each request
+#[derive(Debug, Clone)]
+pub struct LoaderSettings {
+    /// The duration after which dataloader queries are logged as slow
+    pub slow_query_threshold: Duration,
+    /// The maximum number of keys loaded by a single query, larger batches are split across several queries
+    pub max_batch_size: usize,
+    /// The breaker through which dataloader queries pass, shared by all requests
+    pub circuit_breaker: CircuitBreaker,
+    /// The processing statuses which indicate that processing succeeded or failed
+    pub status_codes: ProcessingStatusCodes,
+}
 
 /// router handler extension
 pub trait AddDataLoadersExt {
     /// Adds dataloader to graphql request
-    fn add_data_loaders(self, database: DatabaseConnection) -> Self;
+    fn add_data_loaders(self, database: DatabaseConnection, settings: LoaderSettings) -> Self;
 }
 
 impl AddDataLoadersExt for async_graphql::Request {
-    fn add_data_loaders(self, database: DatabaseConnection) -> Self {
-        self.data(DataLoader::new(
-            FileAttachmentDataLoader::new(database.clone()),
-            tokio::spawn,
+    fn add_data_loaders(self, database: DatabaseConnection, settings: LoaderSettings) -> Self {
+        let LoaderSettings {
+            slow_query_threshold,
+            max_batch_size,
+            circuit_breaker,
+            status_codes,
+        } = settings;
+        let database = GuardedConnection::new(database, circuit_breaker);
+        let tasks = RequestTasks::default();
+        self.data(
+            DataLoader::new(
+                FileAttachmentDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                FileAttachmentByIdDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                ProcessingJobDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                LatestProcessingJobsDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                ProcessingJobByIdDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                ProcessingJobParametersDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                AutoProcessingDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                AutoProcessingWithOverallDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                AttachmentCountDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                ProcessingProgramsDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                SpaceGroupsDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                BestResolutionDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                ProcessingStatusCountsDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                ProcessingInProgressDataLoader::new(
+                    database.clone(),
+                    slow_query_threshold,
+                    &status_codes,
+                ),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                StatisticsSummaryDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                AutoProcScalingByAutoProcIdDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(
+            DataLoader::new(
+                AutoProcScalingDataLoader::new(database.clone(), slow_query_threshold),
+                tasks.spawner(),
+            )
+            .max_batch_size(max_batch_size),
+        )
+        .data(database)
+    }
+}
+
+/// The target of events recorded as metrics rather than logged
+pub const METRICS_TARGET: &str = "processed_data::metrics";
+
+/// Records the batch size and query duration of a dataloader as metrics, warning if the query was slow
+fn record_loader_metrics(
+    loader: &'static str,
+    batch_size: usize,
+    query_duration: Duration,
+    slow_query_threshold: Duration,
+) {
+    tracing::info!(
+        target: METRICS_TARGET,
+        histogram.dataloader_batch_size = batch_size as u64,
+        histogram.dataloader_query_duration_ms = query_duration.as_secs_f64() * 1000.0,
+        loader,
+    );
+    if query_duration > slow_query_threshold {
+        tracing::warn!(
+            loader,
+            keys = batch_size,
+            duration_ms = query_duration.as_millis() as u64,
+            "Slow dataloader query"
+        );
+    }
+}
+
+/// Builds the query fetching processing jobs, joined with their parameters, for the requested data collections
+fn processing_jobs_query(keys: &[(u32, Option<bool>)]) -> SelectStatement {
+    let mut condition = Cond::any();
+    for automatic in [None, Some(true), Some(false)] {
+        let data_collection_ids = keys
+            .iter()
+            .filter(|(_, key_automatic)| *key_automatic == automatic)
+            .map(|(data_collection_id, _)| *data_collection_id)
+            .collect::<Vec<_>>();
+        if data_collection_ids.is_empty() {
+            continue;
+        }
+        let in_data_collections =
+            Expr::col(processing_job::Column::DataCollectionId).is_in(data_collection_ids);
+        condition = condition.add(match automatic {
+            None => Cond::all().add(in_data_collections),
+            Some(true) => Cond::all()
+                .add(in_data_collections)
+                .add(Expr::col(processing_job::Column::Automatic).ne(0)),
+            Some(false) => Cond::all()
+                .add(in_data_collections)
+                .add(Expr::col(processing_job::Column::Automatic).eq(0)),
+        });
+    }
+
+    sea_query::Query::select()
+        .column(Asterisk)
+        .from(processing_job::Entity)
+        .left_join(
+            processing_job_parameter::Entity,
+            Expr::col((
+                processing_job::Entity,
+                processing_job::Column::ProcessingJobId,
+            ))
+            .equals((
+                processing_job_parameter::Entity,
+                processing_job_parameter::Column::ProcessingJobId,
+            )),
+        )
+        .cond_where(condition)
+        .to_owned()
+}
+
+/// Builds the query fetching, for each program which processed the requested data collections, the job with the greatest id, ordered by program name
+fn latest_processing_jobs_query(keys: Vec<u32>) -> SelectStatement {
+    let latest_job = Alias::new("latest_job");
+    let latest_program = Alias::new("latest_program");
+    let latest_job_id = sea_query::Query::select()
+        .expr(Expr::col((latest_job.clone(), processing_job::Column::ProcessingJobId)).max())
+        .from_as(processing_job::Entity, latest_job.clone())
+        .join_as(
+            JoinType::InnerJoin,
+            auto_proc_program::Entity,
+            latest_program.clone(),
+            Expr::col((
+                latest_program.clone(),
+                auto_proc_program::Column::ProcessingJobId,
+            ))
+            .equals((latest_job.clone(), processing_job::Column::ProcessingJobId)),
+        )
+        .and_where(
+            Expr::col((latest_job, processing_job::Column::DataCollectionId)).equals((
+                processing_job::Entity,
+                processing_job::Column::DataCollectionId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                latest_program,
+                auto_proc_program::Column::ProcessingPrograms,
+            ))
+            .equals((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingPrograms,
+            )),
+        )
+        .to_owned();
+
+    sea_query::Query::select()
+        .distinct()
+        .column((processing_job::Entity, Asterisk))
+        .column((
+            auto_proc_program::Entity,
+            auto_proc_program::Column::ProcessingPrograms,
         ))
-        .data(DataLoader::new(
-            ProcessingJobDataLoader::new(database.clone()),
-            tokio::spawn,
+        .from(processing_job::Entity)
+        .inner_join(
+            auto_proc_program::Entity,
+            Expr::col((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingJobId,
+            ))
+            .equals((
+                processing_job::Entity,
+                processing_job::Column::ProcessingJobId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                processing_job::Entity,
+                processing_job::Column::DataCollectionId,
+            ))
+            .is_in(keys),
+        )
+        .and_where(
+            Expr::col((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingPrograms,
+            ))
+            .is_not_null(),
+        )
+        .and_where(
+            Expr::col((
+                processing_job::Entity,
+                processing_job::Column::ProcessingJobId,
+            ))
+            .in_subquery(latest_job_id),
+        )
+        .order_by(
+            (
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingPrograms,
+            ),
+            Order::Asc,
+        )
+        .to_owned()
+}
+
+/// Builds the query fetching auto processing, joined with its program, auto proc and scaling, for the requested data collections
+fn auto_processing_query(keys: Vec<u32>) -> SelectStatement {
+    sea_query::Query::select()
+        .column(Asterisk)
+        .from(auto_proc_integration::Entity)
+        .left_join(
+            auto_proc_program::Entity,
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            ))
+            .equals((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            )),
+        )
+        .left_join(
+            auto_proc::Entity,
+            Expr::col((auto_proc::Entity, auto_proc::Column::AutoProcProgramId)).equals((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            )),
+        )
+        .left_join(
+            auto_proc_scaling::Entity,
+            Expr::col((
+                auto_proc_scaling::Entity,
+                auto_proc_scaling::Column::AutoProcId,
+            ))
+            .equals((auto_proc::Entity, auto_proc::Column::AutoProcId)),
+        )
+        .and_where(Expr::col(auto_proc_integration::Column::DataCollectionId).is_in(keys))
+        .to_owned()
+}
+
+/// Restricts the auto processing fetched by the [`AutoProcessingDataLoader`] by processing program status
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AutoProcessingFilter {
+    /// The statuses of which one must be held, or any status if omitted
+    statuses: Option<Vec<i8>>,
+    /// The statuses which must not be held, auto processing without a status is never excluded
+    excluded_statuses: Vec<i8>,
+}
+
+impl AutoProcessingFilter {
+    /// Returns true if auto processing with the status passes the filter
+    fn matches(&self, status: Option<i8>) -> bool {
+        let included = match &self.statuses {
+            Some(statuses) => status.is_some_and(|status| statuses.contains(&status)),
+            None => true,
+        };
+        included && status.map_or(true, |status| !self.excluded_statuses.contains(&status))
+    }
+
+    /// The condition on the processing program status applied by the filter
+    fn condition(&self) -> Cond {
+        let status = || {
+            Expr::col((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingStatus,
+            ))
+        };
+        let mut condition = Cond::all();
+        if let Some(statuses) = &self.statuses {
+            condition = condition.add(status().is_in(statuses.iter().copied()));
+        }
+        if !self.excluded_statuses.is_empty() {
+            condition = condition.add(
+                Cond::any()
+                    .add(status().is_null())
+                    .add(status().is_not_in(self.excluded_statuses.iter().copied())),
+            );
+        }
+        condition
+    }
+}
+
+/// Builds the query fetching auto processing, as [`auto_processing_query`], restricted by the filter of each key
+fn filtered_auto_processing_query(keys: &[(u32, AutoProcessingFilter)]) -> SelectStatement {
+    let mut condition = Cond::any();
+    for filter in keys
+        .iter()
+        .map(|(_, filter)| filter)
+        .collect::<HashSet<_>>()
+    {
+        let data_collection_ids = keys
+            .iter()
+            .filter(|(_, key_filter)| key_filter == filter)
+            .map(|(data_collection_id, _)| *data_collection_id)
+            .collect::<Vec<_>>();
+        condition = condition.add(
+            Cond::all()
+                .add(
+                    Expr::col((
+                        auto_proc_integration::Entity,
+                        auto_proc_integration::Column::DataCollectionId,
+                    ))
+                    .is_in(data_collection_ids),
+                )
+                .add(filter.condition()),
+        );
+    }
+    auto_processing_query(
+        keys.iter()
+            .map(|(data_collection_id, _)| *data_collection_id)
+            .collect(),
+    )
+    .cond_where(condition)
+    .to_owned()
+}
+
+/// The prefix of the aliased overall statistics columns in [`auto_processing_with_overall_query`]
+const OVERALL_COLUMN_PREFIX: &str = "overall_";
+
+/// Builds the query fetching auto processing, as [`auto_processing_query`], joined with its overall scaling statistics
+fn auto_processing_with_overall_query(keys: Vec<u32>) -> SelectStatement {
+    let mut query = auto_processing_query(keys);
+    query
+        .clear_selects()
+        .column((auto_proc_integration::Entity, Asterisk))
+        .column((auto_proc_program::Entity, Asterisk))
+        .column((auto_proc::Entity, Asterisk))
+        .column((auto_proc_scaling::Entity, Asterisk))
+        .left_join(
+            auto_proc_scaling_statistics::Entity,
+            Cond::all()
+                .add(
+                    Expr::col((
+                        auto_proc_scaling_statistics::Entity,
+                        auto_proc_scaling_statistics::Column::AutoProcScalingId,
+                    ))
+                    .equals((
+                        auto_proc_scaling::Entity,
+                        auto_proc_scaling::Column::AutoProcScalingId,
+                    )),
+                )
+                .add(
+                    Expr::col((
+                        auto_proc_scaling_statistics::Entity,
+                        auto_proc_scaling_statistics::Column::ScalingStatisticsType,
+                    ))
+                    .eq(StatisticsType::Overall.to_string()),
+                ),
+        );
+    for column in auto_proc_scaling_statistics::Column::iter() {
+        query.expr_as(
+            Expr::col((auto_proc_scaling_statistics::Entity, column)),
+            Alias::new(format!("{OVERALL_COLUMN_PREFIX}{}", column.as_str())),
+        );
+    }
+    query
+}
+
+/// Builds the query fetching the distinct, alphabetically ordered, names of programs which processed the requested data collections
+fn processing_programs_query(keys: Vec<u32>) -> SelectStatement {
+    sea_query::Query::select()
+        .distinct()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
         ))
-        .data(DataLoader::new(
-            AutoProcessingDataLoader::new(database.clone()),
-            tokio::spawn,
+        .column((
+            auto_proc_program::Entity,
+            auto_proc_program::Column::ProcessingPrograms,
         ))
-        .data(DataLoader::new(
-            AutoProcScalingDataLoader::new(database.clone()),
-            tokio::spawn,
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc_program::Entity,
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            ))
+            .equals((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            )),
+        )
+        .and_where(Expr::col(auto_proc_integration::Column::DataCollectionId).is_in(keys))
+        .and_where(
+            Expr::col((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingPrograms,
+            ))
+            .is_not_null(),
+        )
+        .order_by(
+            (
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingPrograms,
+            ),
+            Order::Asc,
+        )
+        .to_owned()
+}
+
+/// Builds the query fetching the distinct, alphabetically ordered, space groups determined by auto processing of the requested data collections
+fn space_groups_query(keys: Vec<u32>) -> SelectStatement {
+    sea_query::Query::select()
+        .distinct()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
         ))
-        .data(database)
+        .column((auto_proc::Entity, auto_proc::Column::SpaceGroup))
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc::Entity,
+            Expr::col((auto_proc::Entity, auto_proc::Column::AutoProcProgramId)).equals((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            )),
+        )
+        .and_where(Expr::col(auto_proc_integration::Column::DataCollectionId).is_in(keys))
+        .and_where(Expr::col((auto_proc::Entity, auto_proc::Column::SpaceGroup)).is_not_null())
+        .order_by(
+            (auto_proc::Entity, auto_proc::Column::SpaceGroup),
+            Order::Asc,
+        )
+        .to_owned()
+}
+
+/// Builds the query fetching, for each of the requested data collections, the best overall high resolution limit achieved by any auto processing
+fn best_resolution_query(keys: Vec<u32>) -> SelectStatement {
+    sea_query::Query::select()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .expr_as(
+            Func::min(Expr::col((
+                auto_proc_scaling_statistics::Entity,
+                auto_proc_scaling_statistics::Column::ResolutionLimitHigh,
+            ))),
+            Alias::new("bestResolution"),
+        )
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc::Entity,
+            Expr::col((auto_proc::Entity, auto_proc::Column::AutoProcProgramId)).equals((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            )),
+        )
+        .inner_join(
+            auto_proc_scaling::Entity,
+            Expr::col((
+                auto_proc_scaling::Entity,
+                auto_proc_scaling::Column::AutoProcId,
+            ))
+            .equals((auto_proc::Entity, auto_proc::Column::AutoProcId)),
+        )
+        .inner_join(
+            auto_proc_scaling_statistics::Entity,
+            Expr::col((
+                auto_proc_scaling_statistics::Entity,
+                auto_proc_scaling_statistics::Column::AutoProcScalingId,
+            ))
+            .equals((
+                auto_proc_scaling::Entity,
+                auto_proc_scaling::Column::AutoProcScalingId,
+            )),
+        )
+        .and_where(Expr::col(auto_proc_integration::Column::DataCollectionId).is_in(keys))
+        .and_where(
+            Expr::col((
+                auto_proc_scaling_statistics::Entity,
+                auto_proc_scaling_statistics::Column::ScalingStatisticsType,
+            ))
+            .eq(StatisticsType::Overall.to_string()),
+        )
+        .group_by_col((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .to_owned()
+}
+
+/// Builds the query fetching, for each of the requested data collections, the overall resolution, CC(1/2) and completeness of each program which processed it
+fn statistics_summary_query(keys: Vec<u32>) -> SelectStatement {
+    sea_query::Query::select()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .column((
+            auto_proc_program::Entity,
+            auto_proc_program::Column::ProcessingPrograms,
+        ))
+        .column((
+            auto_proc_scaling_statistics::Entity,
+            auto_proc_scaling_statistics::Column::ResolutionLimitHigh,
+        ))
+        .column((
+            auto_proc_scaling_statistics::Entity,
+            auto_proc_scaling_statistics::Column::CcHalf,
+        ))
+        .column((
+            auto_proc_scaling_statistics::Entity,
+            auto_proc_scaling_statistics::Column::Completeness,
+        ))
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc_program::Entity,
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            ))
+            .equals((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            )),
+        )
+        .inner_join(
+            auto_proc::Entity,
+            Expr::col((auto_proc::Entity, auto_proc::Column::AutoProcProgramId)).equals((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            )),
+        )
+        .inner_join(
+            auto_proc_scaling::Entity,
+            Expr::col((
+                auto_proc_scaling::Entity,
+                auto_proc_scaling::Column::AutoProcId,
+            ))
+            .equals((auto_proc::Entity, auto_proc::Column::AutoProcId)),
+        )
+        .inner_join(
+            auto_proc_scaling_statistics::Entity,
+            Expr::col((
+                auto_proc_scaling_statistics::Entity,
+                auto_proc_scaling_statistics::Column::AutoProcScalingId,
+            ))
+            .equals((
+                auto_proc_scaling::Entity,
+                auto_proc_scaling::Column::AutoProcScalingId,
+            )),
+        )
+        .and_where(Expr::col(auto_proc_integration::Column::DataCollectionId).is_in(keys))
+        .and_where(
+            Expr::col((
+                auto_proc_scaling_statistics::Entity,
+                auto_proc_scaling_statistics::Column::ScalingStatisticsType,
+            ))
+            .eq(StatisticsType::Overall.to_string()),
+        )
+        .order_by(
+            (
+                auto_proc_program::Entity,
+                auto_proc_program::Column::ProcessingPrograms,
+            ),
+            Order::Asc,
+        )
+        .to_owned()
+}
+
+/// Builds the queries counting, for each of the requested data collections, its file attachments and the file attachments of its auto processing
+fn attachment_counts_queries(keys: Vec<u32>) -> [SelectStatement; 2] {
+    let data_collection_attachments = sea_query::Query::select()
+        .column(data_collection_file_attachment::Column::DataCollectionId)
+        .expr_as(
+            Expr::col(data_collection_file_attachment::Column::DataCollectionFileAttachmentId)
+                .count(),
+            Alias::new("count"),
+        )
+        .from(data_collection_file_attachment::Entity)
+        .and_where(
+            Expr::col(data_collection_file_attachment::Column::DataCollectionId)
+                .is_in(keys.clone()),
+        )
+        .group_by_col(data_collection_file_attachment::Column::DataCollectionId)
+        .to_owned();
+    let auto_proc_program_attachments = sea_query::Query::select()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .expr_as(
+            Expr::col((
+                auto_proc_program_attachment::Entity,
+                auto_proc_program_attachment::Column::AutoProcProgramAttachmentId,
+            ))
+            .count_distinct(),
+            Alias::new("count"),
+        )
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc_program_attachment::Entity,
+            Expr::col((
+                auto_proc_program_attachment::Entity,
+                auto_proc_program_attachment::Column::AutoProcProgramId,
+            ))
+            .equals((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::DataCollectionId,
+            ))
+            .is_in(keys),
+        )
+        .group_by_col((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .to_owned();
+    [data_collection_attachments, auto_proc_program_attachments]
+}
+
+/// Builds the query counting, for each of the requested data collections, its auto processing runs with each processing status
+fn processing_status_counts_query(keys: Vec<u32>) -> SelectStatement {
+    sea_query::Query::select()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .column((
+            auto_proc_program::Entity,
+            auto_proc_program::Column::ProcessingStatus,
+        ))
+        .expr_as(
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcIntegrationId,
+            ))
+            .count(),
+            Alias::new("count"),
+        )
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc_program::Entity,
+            Expr::col((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            ))
+            .equals((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::DataCollectionId,
+            ))
+            .is_in(keys),
+        )
+        .group_by_col((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .group_by_col((
+            auto_proc_program::Entity,
+            auto_proc_program::Column::ProcessingStatus,
+        ))
+        .to_owned()
+}
+
+/// Builds the query fetching the requested data collections with an auto processing run in progress, having a status
+/// which is neither null nor one of the terminal statuses
+fn processing_in_progress_query(keys: Vec<u32>, terminal_statuses: &[i8]) -> SelectStatement {
+    let status = || {
+        Expr::col((
+            auto_proc_program::Entity,
+            auto_proc_program::Column::ProcessingStatus,
+        ))
+    };
+    let mut in_progress = Cond::any().add(status().is_null());
+    if !terminal_statuses.is_empty() {
+        in_progress = in_progress.add(status().is_not_in(terminal_statuses.iter().copied()));
+    }
+    sea_query::Query::select()
+        .distinct()
+        .column((
+            auto_proc_integration::Entity,
+            auto_proc_integration::Column::DataCollectionId,
+        ))
+        .from(auto_proc_integration::Entity)
+        .inner_join(
+            auto_proc_program::Entity,
+            Expr::col((
+                auto_proc_program::Entity,
+                auto_proc_program::Column::AutoProcProgramId,
+            ))
+            .equals((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::AutoProcProgramId,
+            )),
+        )
+        .and_where(
+            Expr::col((
+                auto_proc_integration::Entity,
+                auto_proc_integration::Column::DataCollectionId,
+            ))
+            .is_in(keys),
+        )
+        .cond_where(in_progress)
+        .to_owned()
+}
+
+/// Whether failures to presign download links are reported as S3 being unavailable, rather than with the underlying error
+#[derive(Debug, Clone, Copy, Deref)]
+pub struct S3SoftFail(pub bool);
+
+/// Whether the bucket retains previous versions of its objects, as determined at startup
+#[derive(Debug, Clone, Copy, Deref)]
+pub struct BucketVersioned(pub bool);
+
+/// The range of periods for which clients may request presigned download links to remain valid
+#[derive(Debug, Clone, Copy)]
+pub struct PresignExpiryBounds {
+    /// The shortest period which may be requested
+    pub min: Duration,
+    /// The longest period which may be requested, limited by S3
+    pub max: Duration,
+}
+
+impl PresignExpiryBounds {
+    /// Returns the requested expiry if within the bounds, or an error with the `INVALID_EXPIRY` code if not
+    fn validate(&self, expiry_seconds: u64) -> async_graphql::Result<Duration> {
+        let expiry = Duration::from_secs(expiry_seconds);
+        if expiry < self.min || expiry > self.max {
+            return Err(async_graphql::Error::new(format!(
+                "Expiry must be between {} and {} seconds",
+                self.min.as_secs(),
+                self.max.as_secs()
+            ))
+            .extend_with(|_, extensions| extensions.set("code", "INVALID_EXPIRY")));
+        }
+        Ok(expiry)
+    }
+}
+
+/// Returns true if failures to presign download links are reported as S3 being unavailable
+fn s3_soft_fail(ctx: &Context<'_>) -> bool {
+    ctx.data_opt::<S3SoftFail>()
+        .is_some_and(|s3_soft_fail| **s3_soft_fail)
+}
+
+/// Returns true if the diagnostic `explain` query has been enabled
+fn explain_enabled(ctx: &Context<'_>) -> bool {
+    feature_enabled(ctx, Feature::Explain)
+}
+
+/// A query retrieving the full introspection of the schema
+pub const INTROSPECTION_QUERY: &str = include_str!("introspection.graphql");
+
+/// Produces the SDL of the schema, including federation directives for the resolved entities unless built for a standalone deployment
+pub fn schema_sdl(schema: &RootSchema, standalone: bool, entities: &FederationEntities) -> String {
+    if standalone {
+        omit_federation(&schema.sdl_with_options(SDLExportOptions::new()))
+    } else {
+        entities.omit_disabled(&schema.sdl_with_options(SDLExportOptions::new().federation()))
     }
 }
 
-/// A schema builder for the service
-pub fn root_schema_builder() -> SchemaBuilder<Query, EmptyMutation, EmptySubscription> {
-    Schema::build(Query, EmptyMutation, EmptySubscription).enable_federation()
+/// A schema builder for the service, federated unless built for a standalone deployment
+pub fn root_schema_builder(standalone: bool) -> SchemaBuilder<Query, EmptyMutation, Subscription> {
+    let schema_builder = Schema::build(Query, EmptyMutation, Subscription)
+        .extension(ExecutionTimer)
+        .extension(MaintenanceModeGuard)
+        .extension(RateLimitGuard)
+        .extension(QueryComplexityReporter)
+        .extension(RequiredScopes)
+        .extension(PresignLimit)
+        .extension(SubscriptionLimit)
+        .extension(ErrorMasking)
+        .extension(PoolExhaustion)
+        .extension(EntityFilter);
+    if standalone {
+        schema_builder.extension(StandaloneGuard)
+    } else {
+        schema_builder.enable_federation()
+    }
 }
 
+/// The maximum number of attachments which may be fetched by id in a single field
+const MAX_ATTACHMENT_IDS: usize = 100;
+
 /// The root query of the service
 #[derive(Debug, Clone, Default)]
 pub struct Query;
 /// DataLoader for Processed Data
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct FileAttachmentDataLoader {
-    database: DatabaseConnection,
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for auto processing file attachments, keyed on the attachment id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct FileAttachmentByIdDataLoader {
+    database: GuardedConnection,
     parent_span: Span,
+    slow_query_threshold: Duration,
 }
 /// DataLoader for Process Job
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct ProcessingJobDataLoader {
-    database: DatabaseConnection,
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for Process Job, keyed on the processing job id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct ProcessingJobByIdDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the latest Process Job of each program
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct LatestProcessingJobsDataLoader {
+    database: GuardedConnection,
     parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the parameters of processing jobs, as a JSON object keyed on the processing job id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct ProcessingJobParametersDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
 }
 /// DataLoader for AutoProcessing
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct AutoProcessingDataLoader {
-    database: DatabaseConnection,
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for AutoProcessing joined with its overall statistics
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct AutoProcessingWithOverallDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the total number of file attachments, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct AttachmentCountDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the names of processing programs, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct ProcessingProgramsDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the distinct space groups determined by auto processing, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct SpaceGroupsDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the best overall high resolution limit, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct BestResolutionDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for the number of auto processing runs with each processing status, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct ProcessingStatusCountsDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for whether any auto processing run is in progress, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct ProcessingInProgressDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+    terminal_statuses: Vec<i8>,
+}
+/// DataLoader for the overall statistics of each processing program, keyed on the data collection id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct StatisticsSummaryDataLoader {
+    database: GuardedConnection,
+    parent_span: Span,
+    slow_query_threshold: Duration,
+}
+/// DataLoader for AutoProcScaling, keyed on the auto processing id
+#[allow(clippy::missing_docs_in_private_items)]
+pub struct AutoProcScalingByAutoProcIdDataLoader {
+    database: GuardedConnection,
     parent_span: Span,
+    slow_query_threshold: Duration,
 }
 /// DataLoader for overall statistics type
 #[allow(clippy::missing_docs_in_private_items)]
 pub struct AutoProcScalingDataLoader {
-    database: DatabaseConnection,
+    database: GuardedConnection,
     parent_span: Span,
+    slow_query_threshold: Duration,
 }
 
 #[allow(clippy::missing_docs_in_private_items)]
 impl ProcessingJobDataLoader {
-    fn new(database: DatabaseConnection) -> Self {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
         Self {
             database,
             parent_span: Span::current(),
+            slow_query_threshold,
         }
     }
 }
 
 #[allow(clippy::missing_docs_in_private_items)]
-impl FileAttachmentDataLoader {
-    fn new(database: DatabaseConnection) -> Self {
+impl ProcessingJobByIdDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
         Self {
             database,
             parent_span: Span::current(),
+            slow_query_threshold,
         }
     }
 }
 
 #[allow(clippy::missing_docs_in_private_items)]
-impl AutoProcessingDataLoader {
-    fn new(database: DatabaseConnection) -> Self {
+impl ProcessingJobParametersDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
         Self {
             database,
             parent_span: Span::current(),
+            slow_query_threshold,
         }
     }
 }
 
 #[allow(clippy::missing_docs_in_private_items)]
-impl AutoProcScalingDataLoader {
-    fn new(database: DatabaseConnection) -> Self {
+impl LatestProcessingJobsDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
         Self {
             database,
             parent_span: Span::current(),
+            slow_query_threshold,
         }
     }
 }
 
-impl Loader<u32> for FileAttachmentDataLoader {
-    type Value = Vec<AutoProcFileAttachment>;
-    type Error = async_graphql::Error;
+#[allow(clippy::missing_docs_in_private_items)]
+impl FileAttachmentByIdDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
 
-    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
-        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_proc_file_attachment");
-        let _span = span.enter();
-        let mut results = HashMap::new();
-        let keys_vec: Vec<u32> = keys.to_vec();
-        let records = auto_proc_program_attachment::Entity::find()
-            .filter(auto_proc_program_attachment::Column::AutoProcProgramId.is_in(keys_vec))
-            .all(&self.database)
-            .await?;
+#[allow(clippy::missing_docs_in_private_items)]
+impl FileAttachmentDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
 
-        for record in records {
-            let id = record.auto_proc_program_id;
-            let data = AutoProcFileAttachment::from(record);
+#[allow(clippy::missing_docs_in_private_items)]
+impl AutoProcessingDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
 
-            results.entry(id).or_insert(Vec::new()).push(data);
+#[allow(clippy::missing_docs_in_private_items)]
+impl AutoProcessingWithOverallDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
         }
+    }
+}
 
-        Ok(results)
+#[allow(clippy::missing_docs_in_private_items)]
+impl AttachmentCountDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
     }
 }
 
-impl Loader<u32> for ProcessingJobDataLoader {
-    type Value = Vec<ProcessingJob>;
+#[allow(clippy::missing_docs_in_private_items)]
+impl ProcessingProgramsDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl SpaceGroupsDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl BestResolutionDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl ProcessingStatusCountsDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl ProcessingInProgressDataLoader {
+    fn new(
+        database: GuardedConnection,
+        slow_query_threshold: Duration,
+        status_codes: &ProcessingStatusCodes,
+    ) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+            terminal_statuses: status_codes.terminal_statuses(),
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl StatisticsSummaryDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl AutoProcScalingByAutoProcIdDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+#[allow(clippy::missing_docs_in_private_items)]
+impl AutoProcScalingDataLoader {
+    fn new(database: GuardedConnection, slow_query_threshold: Duration) -> Self {
+        Self {
+            database,
+            parent_span: Span::current(),
+            slow_query_threshold,
+        }
+    }
+}
+
+impl Loader<u32> for FileAttachmentDataLoader {
+    type Value = Vec<AutoProcFileAttachment>;
     type Error = async_graphql::Error;
 
-    #[instrument(name = "load_process_job", skip(self))]
     async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
-        let span = tracing::info_span!(parent: &self.parent_span, "load_process_job");
+        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_proc_file_attachment");
         let _span = span.enter();
         let mut results = HashMap::new();
         let keys_vec: Vec<u32> = keys.to_vec();
+        let start = Instant::now();
+        let records = auto_proc_program_attachment::Entity::find()
+            .filter(auto_proc_program_attachment::Column::AutoProcProgramId.is_in(keys_vec))
+            .all(&self.database)
+            .await?;
+        record_loader_metrics(
+            "load_auto_proc_file_attachment",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
 
-        let query = sea_query::Query::select()
-            .column(Asterisk)
-            .from(processing_job::Entity)
-            .left_join(
-                processing_job_parameter::Entity,
-                Expr::col((
-                    processing_job::Entity,
-                    processing_job::Column::ProcessingJobId,
-                ))
-                .equals((
-                    processing_job_parameter::Entity,
-                    processing_job_parameter::Column::ProcessingJobId,
-                )),
+        for record in records {
+            let id = record.auto_proc_program_id;
+            let data = AutoProcFileAttachment::from(record);
+
+            results.entry(id).or_insert(Vec::new()).push(data);
+        }
+
+        Ok(results)
+    }
+}
+
+impl Loader<u32> for FileAttachmentByIdDataLoader {
+    type Value = AutoProcFileAttachment;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_auto_proc_file_attachment_by_id", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span =
+            tracing::info_span!(parent: &self.parent_span, "load_auto_proc_file_attachment_by_id");
+        let _span = span.enter();
+        let start = Instant::now();
+        let records = auto_proc_program_attachment::Entity::find()
+            .filter(
+                auto_proc_program_attachment::Column::AutoProcProgramAttachmentId
+                    .is_in(keys.to_vec()),
             )
-            .and_where(Expr::col(processing_job::Column::DataCollectionId).is_in(keys_vec.clone()))
-            .build_any(
-                self.database
-                    .get_database_backend()
-                    .get_query_builder()
-                    .deref(),
-            );
+            .all(&self.database)
+            .await?;
+        record_loader_metrics(
+            "load_auto_proc_file_attachment_by_id",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                (
+                    record.auto_proc_program_attachment_id,
+                    AutoProcFileAttachment::from(record),
+                )
+            })
+            .collect())
+    }
+}
+
+impl Loader<(u32, Option<bool>)> for ProcessingJobDataLoader {
+    type Value = Vec<ProcessingJob>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_process_job", skip(self))]
+    async fn load(
+        &self,
+        keys: &[(u32, Option<bool>)],
+    ) -> Result<HashMap<(u32, Option<bool>), Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_process_job");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+        let keys_set: HashSet<(u32, Option<bool>)> = keys.iter().copied().collect();
+
+        let query = processing_jobs_query(keys).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
 
+        let start = Instant::now();
         let records = self
             .database
             .query_all(Statement::from_sql_and_values(
@@ -196,60 +1455,53 @@ impl Loader<u32> for ProcessingJobDataLoader {
             .into_iter()
             .map(ProcessingJob::from)
             .collect::<Vec<_>>();
+        record_loader_metrics(
+            "load_process_job",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
 
         for record in records {
             let data_collection_id = record.data_collection_id.unwrap();
-            results
-                .entry(data_collection_id)
-                .or_insert_with(Vec::new)
-                .push(record)
+            let mut record_keys = vec![(data_collection_id, None)];
+            if let Some(automatic) = record.automatic {
+                record_keys.push((data_collection_id, Some(automatic != 0)));
+            }
+            for key in record_keys.into_iter().filter(|key| keys_set.contains(key)) {
+                results
+                    .entry(key)
+                    .or_insert_with(Vec::new)
+                    .push(record.clone())
+            }
         }
         Ok(results)
     }
 }
 
-impl Loader<u32> for AutoProcessingDataLoader {
-    type Value = Vec<AutoProcessing>;
+impl Loader<u32> for ProcessingJobParametersDataLoader {
+    type Value = serde_json::Map<String, serde_json::Value>;
     type Error = async_graphql::Error;
 
-    #[instrument(name = "load_auto_processing", skip(self))]
+    #[instrument(name = "load_processing_job_parameters", skip(self))]
     async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
-        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_processing");
+        let span = tracing::info_span!(parent: &self.parent_span, "load_processing_job_parameters");
         let _span = span.enter();
         let mut results = HashMap::new();
-        let keys_vec: Vec<u32> = keys.to_vec();
 
         let query = sea_query::Query::select()
-            .column(Asterisk)
-            .from(auto_proc_integration::Entity)
-            .left_join(
-                auto_proc_program::Entity,
-                Expr::col((
-                    auto_proc_integration::Entity,
-                    auto_proc_integration::Column::AutoProcProgramId,
-                ))
-                .equals((
-                    auto_proc_program::Entity,
-                    auto_proc_program::Column::AutoProcProgramId,
-                )),
-            )
-            .left_join(
-                auto_proc::Entity,
-                Expr::col((auto_proc::Entity, auto_proc::Column::AutoProcProgramId)).equals((
-                    auto_proc_program::Entity,
-                    auto_proc_program::Column::AutoProcProgramId,
-                )),
-            )
-            .left_join(
-                auto_proc_scaling::Entity,
-                Expr::col((
-                    auto_proc_scaling::Entity,
-                    auto_proc_scaling::Column::AutoProcId,
-                ))
-                .equals((auto_proc::Entity, auto_proc::Column::AutoProcId)),
-            )
+            .columns([
+                processing_job_parameter::Column::ProcessingJobId,
+                processing_job_parameter::Column::ParameterKey,
+                processing_job_parameter::Column::ParameterValue,
+            ])
+            .from(processing_job_parameter::Entity)
             .and_where(
-                Expr::col(auto_proc_integration::Column::DataCollectionId).is_in(keys_vec.clone()),
+                Expr::col(processing_job_parameter::Column::ProcessingJobId).is_in(keys.to_vec()),
+            )
+            .order_by(
+                processing_job_parameter::Column::ProcessingJobParameterId,
+                Order::Asc,
             )
             .build_any(
                 self.database
@@ -258,6 +1510,76 @@ impl Loader<u32> for AutoProcessingDataLoader {
                     .deref(),
             );
 
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_processing_job_parameters",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let (Some(processing_job_id), Some(parameter_key)) = (
+                record.try_get::<Option<u32>>("", "processingJobId")?,
+                record.try_get::<Option<String>>("", "parameterKey")?,
+            ) else {
+                continue;
+            };
+            let parameter_value = record
+                .try_get::<Option<String>>("", "parameterValue")?
+                .map_or(serde_json::Value::Null, serde_json::Value::String);
+            insert_parameter(
+                results.entry(processing_job_id).or_default(),
+                parameter_key,
+                parameter_value,
+            );
+        }
+
+        Ok(results)
+    }
+}
+
+/// Inserts a parameter into the JSON object, collecting the values of repeated keys into an array in the order they were inserted
+fn insert_parameter(
+    parameters: &mut serde_json::Map<String, serde_json::Value>,
+    key: String,
+    value: serde_json::Value,
+) {
+    match parameters.get_mut(&key) {
+        Some(serde_json::Value::Array(values)) => values.push(value),
+        Some(existing) => *existing = serde_json::Value::Array(vec![existing.take(), value]),
+        None => {
+            parameters.insert(key, value);
+        }
+    }
+}
+
+impl Loader<u32> for LatestProcessingJobsDataLoader {
+    type Value = Vec<ProcessingJob>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_latest_processing_jobs", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_latest_processing_jobs");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = latest_processing_jobs_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
         let records = self
             .database
             .query_all(Statement::from_sql_and_values(
@@ -267,11 +1589,17 @@ impl Loader<u32> for AutoProcessingDataLoader {
             ))
             .await?
             .into_iter()
-            .map(AutoProcessing::from)
+            .map(ProcessingJob::from)
             .collect::<Vec<_>>();
+        record_loader_metrics(
+            "load_latest_processing_jobs",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
 
         for record in records {
-            let data_collection_id = record.data_collection_id;
+            let data_collection_id = record.data_collection_id.unwrap();
             results
                 .entry(data_collection_id)
                 .or_insert_with(Vec::new)
@@ -282,32 +1610,21 @@ impl Loader<u32> for AutoProcessingDataLoader {
     }
 }
 
-impl Loader<(u32, StatisticsType)> for AutoProcScalingDataLoader {
-    type Value = AutoProcScalingStatics;
+impl Loader<u32> for ProcessingJobByIdDataLoader {
+    type Value = ProcessingJob;
     type Error = async_graphql::Error;
 
-    #[instrument(name = "load_auto_proc_scaling", skip(self))]
-    #[allow(clippy::type_complexity)]
-    async fn load(
-        &self,
-        keys: &[(u32, StatisticsType)],
-    ) -> Result<HashMap<(u32, StatisticsType), Self::Value>, Self::Error> {
-        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_proc_scaling");
+    #[instrument(name = "load_process_job_by_id", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_process_job_by_id");
         let _span = span.enter();
         let mut results = HashMap::new();
+        let keys_vec: Vec<u32> = keys.to_vec();
+
         let query = sea_query::Query::select()
             .column(Asterisk)
-            .from(auto_proc_scaling_statistics::Entity)
-            .and_where(
-                Expr::tuple([
-                    Expr::col(auto_proc_scaling_statistics::Column::AutoProcScalingId).into(),
-                    Expr::col(auto_proc_scaling_statistics::Column::ScalingStatisticsType).into(),
-                ])
-                .in_tuples(
-                    keys.iter()
-                        .map(|(id, stat_type)| (*id, stat_type.to_string())),
-                ),
-            )
+            .from(processing_job::Entity)
+            .and_where(Expr::col(processing_job::Column::ProcessingJobId).is_in(keys_vec))
             .build_any(
                 self.database
                     .get_database_backend()
@@ -315,124 +1632,3277 @@ impl Loader<(u32, StatisticsType)> for AutoProcScalingDataLoader {
                     .deref(),
             );
 
-        let records = auto_proc_scaling_statistics::Entity::find()
-            .from_raw_sql(Statement::from_sql_and_values(
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
                 self.database.get_database_backend(),
-                query.0,
+                &query.0,
                 query.1,
             ))
-            .all(&self.database)
-            .await?;
+            .await?
+            .into_iter()
+            .map(ProcessingJob::from)
+            .collect::<Vec<_>>();
+        record_loader_metrics(
+            "load_process_job_by_id",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
 
         for record in records {
-            let keys: (u32, StatisticsType) = (
-                record.auto_proc_scaling_id.unwrap(),
-                record.scaling_statistics_type.into(),
-            );
-            let data = AutoProcScalingStatics::from(record);
-            results.insert(keys, data);
+            if let Some(processing_job_id) = record.processing_job_id {
+                results.insert(processing_job_id, record);
+            }
         }
-
         Ok(results)
     }
 }
 
-#[ComplexObject]
-impl DataCollection {
-    /// Fetched all the processing jobs
-    async fn processing_jobs(
-        &self,
-        ctx: &Context<'_>,
-    ) -> async_graphql::Result<Option<Vec<ProcessingJob>>, async_graphql::Error> {
-        let loader = ctx.data_unchecked::<DataLoader<ProcessingJobDataLoader>>();
-        loader.load_one(self.id).await
-    }
+impl Loader<(u32, AutoProcessingFilter)> for AutoProcessingDataLoader {
+    type Value = Vec<AutoProcessing>;
+    type Error = async_graphql::Error;
 
-    /// Fetches all the automatic process
-    async fn auto_processing(
+    #[instrument(name = "load_auto_processing", skip(self))]
+    async fn load(
         &self,
-        ctx: &Context<'_>,
-    ) -> async_graphql::Result<Option<Vec<AutoProcessing>>, async_graphql::Error> {
-        let loader = ctx.data_unchecked::<DataLoader<AutoProcessingDataLoader>>();
-        loader.load_one(self.id).await
-    }
-}
+        keys: &[(u32, AutoProcessingFilter)],
+    ) -> Result<HashMap<(u32, AutoProcessingFilter), Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_processing");
+        let _span = span.enter();
+        let mut results = HashMap::new();
 
-#[ComplexObject]
-impl AutoProcessing {
-    /// Fetches the overall scaling statistics type
-    async fn overall(
-        &self,
-        ctx: &Context<'_>,
-    ) -> async_graphql::Result<Option<AutoProcScalingStatics>> {
-        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
-        match self.auto_proc_id {
-            Some(id) => loader.load_one((id, StatisticsType::Overall)).await,
-            None => Ok(None),
+        let query = filtered_auto_processing_query(keys).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?
+            .into_iter()
+            .map(AutoProcessing::from)
+            .collect::<Vec<_>>();
+        record_loader_metrics(
+            "load_auto_processing",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            for key in keys.iter().filter(|(data_collection_id, filter)| {
+                *data_collection_id == record.data_collection_id
+                    && filter.matches(record.processing_status)
+            }) {
+                results
+                    .entry(key.clone())
+                    .or_insert_with(Vec::new)
+                    .push(record.clone())
+            }
         }
+
+        Ok(results)
     }
+}
 
-    /// Fetches the innershell scaling statistics type
-    async fn inner_shell(
-        &self,
-        ctx: &Context<'_>,
-    ) -> async_graphql::Result<Option<AutoProcScalingStatics>> {
-        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
-        match self.auto_proc_id {
-            Some(id) => loader.load_one((id, StatisticsType::InnerShell)).await,
-            None => Ok(None),
+impl Loader<u32> for AutoProcessingWithOverallDataLoader {
+    type Value = Vec<AutoProcessingWithOverall>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_auto_processing_with_overall", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span =
+            tracing::info_span!(parent: &self.parent_span, "load_auto_processing_with_overall");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = auto_processing_with_overall_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_auto_processing_with_overall",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let overall = auto_proc_scaling_statistics::Model::from_query_result_optional(
+                &record,
+                OVERALL_COLUMN_PREFIX,
+            )?
+            .map(AutoProcScalingStatics::from);
+            let auto_processing = AutoProcessing::from(record);
+            results
+                .entry(auto_processing.data_collection_id)
+                .or_insert_with(Vec::new)
+                .push(AutoProcessingWithOverall {
+                    auto_processing,
+                    overall,
+                })
         }
+
+        Ok(results)
     }
+}
 
-    /// Fetches the outershell scaling statistics type
-    async fn outer_shell(
-        &self,
-        ctx: &Context<'_>,
-    ) -> async_graphql::Result<Option<AutoProcScalingStatics>> {
-        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
-        match self.auto_proc_id {
-            Some(id) => loader.load_one((id, StatisticsType::OuterShell)).await,
-            None => Ok(None),
+impl Loader<u32> for AttachmentCountDataLoader {
+    type Value = u64;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_attachment_count", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_attachment_count");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let start = Instant::now();
+        for query in attachment_counts_queries(keys.to_vec()) {
+            let query = query.build_any(
+                self.database
+                    .get_database_backend()
+                    .get_query_builder()
+                    .deref(),
+            );
+            let records = self
+                .database
+                .query_all(Statement::from_sql_and_values(
+                    self.database.get_database_backend(),
+                    &query.0,
+                    query.1,
+                ))
+                .await?;
+            for record in records {
+                let data_collection_id = record.try_get::<u32>("", "dataCollectionId")?;
+                let count = record.try_get::<i64>("", "count")?;
+                *results.entry(data_collection_id).or_insert(0) += count as u64;
+            }
         }
+        record_loader_metrics(
+            "load_attachment_count",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        Ok(results)
     }
+}
 
-    /// Fetches all the file attachments
-    async fn file_attachments(
-        &self,
-        ctx: &Context<'_>,
-    ) -> Result<Option<Vec<AutoProcFileAttachment>>, async_graphql::Error> {
-        let loader = ctx.data_unchecked::<DataLoader<FileAttachmentDataLoader>>();
-        match self.auto_proc_program_id {
-            Some(id) => loader.load_one(id).await,
-            None => Ok(None),
+impl Loader<u32> for ProcessingProgramsDataLoader {
+    type Value = Vec<String>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_processing_programs", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_processing_programs");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = processing_programs_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_processing_programs",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let data_collection_id = record.try_get::<u32>("", "dataCollectionId")?;
+            let processing_programs = record.try_get::<String>("", "processingPrograms")?;
+            results
+                .entry(data_collection_id)
+                .or_insert_with(Vec::new)
+                .push(processing_programs)
         }
+
+        Ok(results)
     }
 }
 
-#[ComplexObject]
-impl AutoProcFileAttachment {
-    /// Generate downloadable link for files in s3 bucket
-    async fn file_url(&self, ctx: &Context<'_>) -> async_graphql::Result<String> {
-        let s3_client = ctx.data::<aws_sdk_s3::Client>()?;
-        let bucket = ctx.data::<S3Bucket>()?;
-        let object_uri = s3_client
-            .get_object()
-            .bucket(bucket.clone())
-            .key(self.object_key())
-            .presigned(PresigningConfig::expires_in(Duration::from_secs(10 * 60))?)
-            .await?
-            .uri()
-            .clone();
-        let object_url = Url::parse(&object_uri.to_string())?;
-        Ok(object_url.to_string())
+impl Loader<u32> for SpaceGroupsDataLoader {
+    type Value = Vec<String>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_space_groups", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_space_groups");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = space_groups_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_space_groups",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let data_collection_id = record.try_get::<u32>("", "dataCollectionId")?;
+            let space_group = record.try_get::<String>("", "spaceGroup")?;
+            results
+                .entry(data_collection_id)
+                .or_insert_with(Vec::new)
+                .push(space_group)
+        }
+
+        Ok(results)
     }
 }
 
-#[Object]
-impl Query {
-    /// Reference datasets resolver for the router
-    #[graphql(entity)]
-    async fn router_data_collection(&self, id: u32) -> DataCollection {
-        DataCollection { id }
+impl Loader<u32> for BestResolutionDataLoader {
+    type Value = f32;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_best_resolution", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_best_resolution");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = best_resolution_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_best_resolution",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let data_collection_id = record.try_get::<u32>("", "dataCollectionId")?;
+            if let Some(best_resolution) = record.try_get::<Option<f32>>("", "bestResolution")? {
+                results.insert(data_collection_id, best_resolution);
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+impl Loader<u32> for ProcessingStatusCountsDataLoader {
+    type Value = Vec<(Option<i8>, u64)>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_processing_status_counts", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_processing_status_counts");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = processing_status_counts_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_processing_status_counts",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let data_collection_id = record.try_get::<u32>("", "dataCollectionId")?;
+            let status = record.try_get::<Option<i8>>("", "processingStatus")?;
+            let count = record.try_get::<i64>("", "count")?;
+            results
+                .entry(data_collection_id)
+                .or_insert_with(Vec::new)
+                .push((status, count as u64));
+        }
+
+        Ok(results)
+    }
+}
+
+impl Loader<u32> for ProcessingInProgressDataLoader {
+    type Value = bool;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_processing_in_progress", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_processing_in_progress");
+        let _span = span.enter();
+        let mut results = keys
+            .iter()
+            .map(|data_collection_id| (*data_collection_id, false))
+            .collect::<HashMap<_, _>>();
+
+        let query = processing_in_progress_query(keys.to_vec(), &self.terminal_statuses).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_processing_in_progress",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            results.insert(record.try_get::<u32>("", "dataCollectionId")?, true);
+        }
+
+        Ok(results)
+    }
+}
+
+impl Loader<u32> for StatisticsSummaryDataLoader {
+    type Value = Vec<StatisticsSummary>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_statistics_summary", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_statistics_summary");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+
+        let query = statistics_summary_query(keys.to_vec()).build_any(
+            self.database
+                .get_database_backend()
+                .get_query_builder()
+                .deref(),
+        );
+
+        let start = Instant::now();
+        let records = self
+            .database
+            .query_all(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                &query.0,
+                query.1,
+            ))
+            .await?;
+        record_loader_metrics(
+            "load_statistics_summary",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let data_collection_id = record.try_get::<u32>("", "dataCollectionId")?;
+            results
+                .entry(data_collection_id)
+                .or_insert_with(Vec::new)
+                .push(StatisticsSummary {
+                    program: record.try_get("", "processingPrograms")?,
+                    resolution: record.try_get("", "resolutionLimitHigh")?,
+                    cc_half: record.try_get("", "ccHalf")?,
+                    completeness: record.try_get("", "completeness")?,
+                })
+        }
+
+        Ok(results)
+    }
+}
+
+impl Loader<u32> for AutoProcScalingByAutoProcIdDataLoader {
+    type Value = AutoProcScaling;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_auto_proc_scaling_by_auto_proc_id", skip(self))]
+    async fn load(&self, keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_proc_scaling_by_auto_proc_id");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+        let keys_vec: Vec<u32> = keys.to_vec();
+        let start = Instant::now();
+        let records = auto_proc_scaling::Entity::find()
+            .filter(auto_proc_scaling::Column::AutoProcId.is_in(keys_vec))
+            .all(&self.database)
+            .await?;
+        record_loader_metrics(
+            "load_auto_proc_scaling_by_auto_proc_id",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            if let Some(auto_proc_id) = record.auto_proc_id {
+                results.insert(auto_proc_id, AutoProcScaling::from(record));
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+impl Loader<(u32, StatisticsType)> for AutoProcScalingDataLoader {
+    type Value = AutoProcScalingStatics;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_auto_proc_scaling", skip(self))]
+    #[allow(clippy::type_complexity)]
+    async fn load(
+        &self,
+        keys: &[(u32, StatisticsType)],
+    ) -> Result<HashMap<(u32, StatisticsType), Self::Value>, Self::Error> {
+        let span = tracing::info_span!(parent: &self.parent_span, "load_auto_proc_scaling");
+        let _span = span.enter();
+        let mut results = HashMap::new();
+        let query = sea_query::Query::select()
+            .column(Asterisk)
+            .from(auto_proc_scaling_statistics::Entity)
+            .and_where(
+                Expr::tuple([
+                    Expr::col(auto_proc_scaling_statistics::Column::AutoProcScalingId).into(),
+                    Expr::col(auto_proc_scaling_statistics::Column::ScalingStatisticsType).into(),
+                ])
+                .in_tuples(
+                    keys.iter()
+                        .map(|(id, stat_type)| (*id, stat_type.to_string())),
+                ),
+            )
+            .build_any(
+                self.database
+                    .get_database_backend()
+                    .get_query_builder()
+                    .deref(),
+            );
+
+        let start = Instant::now();
+        let records = auto_proc_scaling_statistics::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                self.database.get_database_backend(),
+                query.0,
+                query.1,
+            ))
+            .all(&self.database)
+            .await?;
+        record_loader_metrics(
+            "load_auto_proc_scaling",
+            keys.len(),
+            start.elapsed(),
+            self.slow_query_threshold,
+        );
+
+        for record in records {
+            let keys: (u32, StatisticsType) = (
+                record.auto_proc_scaling_id.unwrap(),
+                record.scaling_statistics_type.into(),
+            );
+            let data = AutoProcScalingStatics::from(record);
+            results.insert(keys, data);
+        }
+
+        Ok(results)
+    }
+}
+
+/// Loads the overall, innershell and outershell scaling statistics in a single batch, omitting missing shells
+async fn load_shell_statistics(
+    ctx: &Context<'_>,
+    auto_proc_scaling_id: u32,
+) -> async_graphql::Result<Vec<AutoProcScalingStatics>> {
+    let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
+    let keys = [
+        StatisticsType::Overall,
+        StatisticsType::InnerShell,
+        StatisticsType::OuterShell,
+    ]
+    .map(|statistics_type| (auto_proc_scaling_id, statistics_type));
+    let mut statistics = loader.load_many(keys).await?;
+    Ok(keys
+        .iter()
+        .filter_map(|key| statistics.remove(key))
+        .collect())
+}
+
+/// Sums the present values, returning [`None`] if there are none
+fn sum_present(values: impl IntoIterator<Item = Option<i64>>) -> Option<i64> {
+    values
+        .into_iter()
+        .flatten()
+        .fold(None, |sum, value| Some(sum.unwrap_or(0) + value))
+}
+
+#[ComplexObject]
+impl DataCollection {
+    /// Fetched all the processing jobs, optionally only those which were, or were not, automatic
+    async fn processing_jobs(
+        &self,
+        ctx: &Context<'_>,
+        automatic: Option<bool>,
+    ) -> async_graphql::Result<Option<Vec<ProcessingJob>>, async_graphql::Error> {
+        let loader = ctx.data_unchecked::<DataLoader<ProcessingJobDataLoader>>();
+        loader.load_one((self.id, automatic)).await
+    }
+
+    /// Fetches the most recent processing job of each program, ordered by program name
+    async fn latest_processing_jobs(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<ProcessingJob>> {
+        let loader = ctx.data_unchecked::<DataLoader<LatestProcessingJobsDataLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    /// Fetches all the automatic process, optionally only those with a status, or excluding those which failed
+    async fn auto_processing(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<ProcessingStatus>,
+        #[graphql(default)] exclude_failed: bool,
+    ) -> async_graphql::Result<Option<Vec<AutoProcessing>>, async_graphql::Error> {
+        let status_codes = ctx
+            .data_opt::<ProcessingStatusCodes>()
+            .cloned()
+            .unwrap_or_default();
+        let filter = AutoProcessingFilter {
+            statuses: status.map(|status| status_codes.statuses(status)),
+            excluded_statuses: if exclude_failed {
+                status_codes.statuses(ProcessingStatus::Failed)
+            } else {
+                Vec::new()
+            },
+        };
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcessingDataLoader>>();
+        loader.load_one((self.id, filter)).await
+    }
+
+    /// Fetches all the automatic process, each joined with its overall statistics in a single query
+    async fn auto_processing_with_overall(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<Vec<AutoProcessingWithOverall>>, async_graphql::Error> {
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcessingWithOverallDataLoader>>();
+        loader.load_one(self.id).await
+    }
+
+    /// Counts the file attachments of the data collection and of its auto processing
+    async fn total_attachment_count(&self, ctx: &Context<'_>) -> async_graphql::Result<u64> {
+        let loader = ctx.data_unchecked::<DataLoader<AttachmentCountDataLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    /// Fetches the distinct names of the programs which processed the data collection, in alphabetical order
+    async fn processing_programs(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<String>> {
+        let loader = ctx.data_unchecked::<DataLoader<ProcessingProgramsDataLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    /// Fetches the distinct space groups determined by auto processing of the data collection, in alphabetical order
+    async fn space_groups(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<String>> {
+        let loader = ctx.data_unchecked::<DataLoader<SpaceGroupsDataLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    /// The best overall high resolution limit achieved by any auto processing of the data collection, rounded to the configured precision, or null if no statistics exist
+    async fn best_resolution(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<f64>> {
+        let loader = ctx.data_unchecked::<DataLoader<BestResolutionDataLoader>>();
+        Ok(stats_precision(ctx).round(loader.load_one(self.id).await?))
+    }
+
+    /// Counts the auto processing runs of the data collection by outcome, succeeded first, then failed, then those in progress or of unknown status
+    async fn processing_status_counts(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<ProcessingStatusCount>> {
+        let status_codes = ctx
+            .data_opt::<ProcessingStatusCodes>()
+            .cloned()
+            .unwrap_or_default();
+        let loader = ctx.data_unchecked::<DataLoader<ProcessingStatusCountsDataLoader>>();
+        let mut counts = Vec::<ProcessingStatusCount>::new();
+        for (status, count) in loader.load_one(self.id).await?.unwrap_or_default() {
+            let status = status_codes.outcome(status);
+            match counts.iter_mut().find(|existing| existing.status == status) {
+                Some(existing) => existing.count += count,
+                None => counts.push(ProcessingStatusCount { status, count }),
+            }
+        }
+        counts.sort_by_key(|count| match count.status {
+            Some(ProcessingStatus::Succeeded) => 0,
+            Some(ProcessingStatus::Failed) => 1,
+            None => 2,
+        });
+        Ok(counts)
+    }
+
+    /// Whether any auto processing run of the data collection is in progress, having neither succeeded nor failed
+    async fn is_processing(&self, ctx: &Context<'_>) -> async_graphql::Result<bool> {
+        let loader = ctx.data_unchecked::<DataLoader<ProcessingInProgressDataLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+
+    /// Fetches the overall resolution, CC(1/2) and completeness of each program which processed the data collection, ordered by program
+    async fn statistics_summary(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<StatisticsSummary>> {
+        let loader = ctx.data_unchecked::<DataLoader<StatisticsSummaryDataLoader>>();
+        Ok(loader.load_one(self.id).await?.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl ProcessingJob {
+    /// Fetches all parameters of the processing job as a JSON object, with the values of repeated keys collected into arrays
+    async fn parameters_json(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<JsonObject>> {
+        let Some(processing_job_id) = self.processing_job_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data_unchecked::<DataLoader<ProcessingJobParametersDataLoader>>();
+        Ok(Some(
+            loader
+                .load_one(processing_job_id)
+                .await?
+                .unwrap_or_default()
+                .into(),
+        ))
+    }
+}
+
+#[ComplexObject]
+impl AutoProcessing {
+    /// The refined position of the beam, if both coordinates are present
+    async fn refined_beam_center(&self) -> Option<Point> {
+        Point::from_coordinates(self.refined_x_beam, self.refined_y_beam)
+    }
+
+    /// Produces a signed token from which the auto processing can later be resolved
+    async fn share_token(&self, ctx: &Context<'_>) -> async_graphql::Result<String> {
+        let signer = ctx
+            .data_opt::<ShareTokenSigner>()
+            .ok_or_else(|| async_graphql::Error::new("Share tokens are not enabled"))?;
+        signer.sign(ShareReference {
+            data_collection_id: self.data_collection_id,
+            auto_proc_integration_id: self.auto_proc_integration_id,
+        })
+    }
+
+    /// Fetches the overall scaling statistics type
+    #[graphql(
+        deprecation = "Use `scaling { statistics }`, or the `scalingStatistics` query, which return all shells together"
+    )]
+    async fn overall(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<AutoProcScalingStatics>> {
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
+        match self.auto_proc_scaling_id {
+            Some(id) => loader.load_one((id, StatisticsType::Overall)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Whether processing succeeded, as derived from the processing status, or null if processing is in progress or the status is unknown
+    async fn succeeded(&self, ctx: &Context<'_>) -> Option<bool> {
+        match ctx.data_opt::<ProcessingStatusCodes>() {
+            Some(status_codes) => status_codes.succeeded(self.processing_status),
+            None => ProcessingStatusCodes::default().succeeded(self.processing_status),
+        }
+    }
+
+    /// Processing program message, truncated to `max_length` characters or the configured default, followed by an ellipsis if clipped
+    #[graphql(directive = requires_scope::apply(PROCESSING_MESSAGE_SCOPE.to_string()))]
+    async fn processing_message_truncated(
+        &self,
+        ctx: &Context<'_>,
+        max_length: Option<usize>,
+    ) -> Option<String> {
+        let max_length = max_length.unwrap_or_else(|| {
+            ctx.data_opt::<ProcessingMessageMaxLength>()
+                .copied()
+                .unwrap_or_default()
+                .0
+        });
+        self.truncated_processing_message(max_length)
+    }
+
+    /// Grades the quality of the data from the overall scaling statistics, if the completeness, resolution and CC(1/2) are present
+    async fn quality_grade(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<QualityGrade>> {
+        let Some(id) = self.auto_proc_scaling_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
+        let thresholds = ctx
+            .data_opt::<QualityGradeThresholds>()
+            .copied()
+            .unwrap_or_default();
+        Ok(loader
+            .load_one((id, StatisticsType::Overall))
+            .await?
+            .and_then(|overall| thresholds.grade(&overall)))
+    }
+
+    /// The overall high resolution limit penalised for incompleteness, as `resolution * (100 / completeness)^(1 / exponent)` with a configurable exponent, rounded to the configured precision, or null if either is absent
+    async fn effective_resolution(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<f64>> {
+        let Some(id) = self.auto_proc_scaling_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
+        let exponent = ctx
+            .data_opt::<EffectiveResolutionExponent>()
+            .copied()
+            .unwrap_or_default();
+        Ok(stats_precision(ctx).round(
+            loader
+                .load_one((id, StatisticsType::Overall))
+                .await?
+                .and_then(|overall| exponent.effective_resolution(&overall)),
+        ))
+    }
+
+    /// Fetches the innershell scaling statistics type
+    #[graphql(
+        deprecation = "Use `scaling { statistics }`, or the `scalingStatistics` query, which return all shells together"
+    )]
+    async fn inner_shell(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<AutoProcScalingStatics>> {
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
+        match self.auto_proc_scaling_id {
+            Some(id) => loader.load_one((id, StatisticsType::InnerShell)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches the outershell scaling statistics type
+    #[graphql(
+        deprecation = "Use `scaling { statistics }`, or the `scalingStatistics` query, which return all shells together"
+    )]
+    async fn outer_shell(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<AutoProcScalingStatics>> {
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingDataLoader>>();
+        match self.auto_proc_scaling_id {
+            Some(id) => loader.load_one((id, StatisticsType::OuterShell)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Sums the total observations across all scaling statistics shells
+    async fn total_observations(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<i64>> {
+        match self.auto_proc_scaling_id {
+            Some(id) => Ok(sum_present(
+                load_shell_statistics(ctx, id)
+                    .await?
+                    .iter()
+                    .map(|statistics| statistics.n_total_observations),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches the overall, innershell and outershell completeness together, with null components for missing shells
+    async fn completeness_by_shell(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<CompletenessByShell>> {
+        match self.auto_proc_scaling_id {
+            Some(id) => Ok(Some(
+                load_shell_statistics(ctx, id).await?.into_iter().collect(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Sums the unique observations across all scaling statistics shells
+    async fn unique_observations(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<i64>> {
+        match self.auto_proc_scaling_id {
+            Some(id) => Ok(sum_present(
+                load_shell_statistics(ctx, id)
+                    .await?
+                    .iter()
+                    .map(|statistics| statistics.n_total_unique_observations),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches the auto processing scaling
+    async fn scaling(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<AutoProcScaling>, async_graphql::Error> {
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcScalingByAutoProcIdDataLoader>>();
+        match self.auto_proc_id {
+            Some(id) => loader.load_one(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches the processing job which produced the auto processing
+    async fn processing_job(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<ProcessingJob>, async_graphql::Error> {
+        let loader = ctx.data_unchecked::<DataLoader<ProcessingJobByIdDataLoader>>();
+        match self.processing_job_id {
+            Some(id) => loader.load_one(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches all the file attachments
+    async fn file_attachments(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<Vec<AutoProcFileAttachment>>, async_graphql::Error> {
+        let loader = ctx.data_unchecked::<DataLoader<FileAttachmentDataLoader>>();
+        match self.auto_proc_program_id {
+            Some(id) => loader.load_one(id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[ComplexObject]
+impl AutoProcScaling {
+    /// Fetches the overall, innershell and outershell scaling statistics
+    async fn statistics(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AutoProcScalingStatics>> {
+        load_shell_statistics(ctx, self.auto_proc_scaling_id).await
+    }
+}
+
+/// Orders by the bytes of a string, rather than by the collation of its column, such that the order of the database
+/// agrees with the order of [`Attachment::ordering_key`] and the first rows of each source are those of the merged set
+fn binary_order(expr: impl Into<SimpleExpr>) -> SimpleExpr {
+    Expr::cust_with_expr("CAST($1 AS BINARY)", expr)
+}
+
+/// Returns true if the type of the attachment is downloadable without the internal attachment scope
+fn downloadable(ctx: &Context<'_>, attachment: &impl StoredAttachment) -> bool {
+    match ctx.data_opt::<DownloadableFileTypes>() {
+        Some(downloadable_file_types) => {
+            downloadable_file_types.contains(attachment.stored_file_type())
+        }
+        None => DownloadableFileTypes::default().contains(attachment.stored_file_type()),
+    }
+}
+
+/// Presigns a download link for the attachment in the s3 bucket, returning it alongside the time at which it expires
+///
+/// Attachments of types which are not downloadable by default are only presigned if the internal attachment scope has
+/// been granted, otherwise an error with the `FORBIDDEN` code is returned.
+async fn presign_attachment(
+    ctx: &Context<'_>,
+    attachment: &impl StoredAttachment,
+    version_id: Option<String>,
+    content_type: Option<String>,
+    disposition: Option<ContentDisposition>,
+    range: Option<String>,
+    expiry_seconds: Option<u64>,
+) -> async_graphql::Result<PresignedUrl> {
+    if !downloadable(ctx, attachment)
+        && !ctx
+            .data_opt::<GrantedScopes>()
+            .is_some_and(|granted_scopes| granted_scopes.contains(INTERNAL_ATTACHMENT_SCOPE))
+    {
+        return Err(async_graphql::Error::new(format!(
+            "The {INTERNAL_ATTACHMENT_SCOPE} scope is required to download this file"
+        ))
+        .extend_with(|_, extensions| extensions.set("code", "FORBIDDEN")));
+    }
+    presign_object(
+        ctx,
+        attachment.object_key(ctx.data::<ObjectKeyRewrite>()?),
+        version_id,
+        content_type.or_else(|| attachment.inferred_content_type().map(String::from)),
+        disposition
+            .or_else(|| attachment.default_disposition())
+            .map(|disposition| disposition.header_value(attachment.stored_file_name())),
+        range.as_deref().map(str::parse::<ByteRange>).transpose()?,
+        expiry_seconds
+            .map(|expiry_seconds| ctx.data::<PresignExpiryBounds>()?.validate(expiry_seconds))
+            .transpose()?,
+    )
+    .await
+}
+
+/// Presigns a download link for the object in the s3 bucket, returning it alongside the time at which it expires
+///
+/// If [`S3SoftFail`] is enabled, failures to presign are reported with the `S3_UNAVAILABLE` code in place of the underlying error.
+/// Links are presigned through the [`PresignDataLoader`], such that the links of many attachments are presigned concurrently.
+/// Links to a version of the object are refused if the bucket is known not to be versioned, as they could never be followed
+async fn presign_object(
+    ctx: &Context<'_>,
+    object_key: &str,
+    version_id: Option<String>,
+    content_type: Option<String>,
+    content_disposition: Option<String>,
+    range: Option<ByteRange>,
+    expiry: Option<Duration>,
+) -> async_graphql::Result<PresignedUrl> {
+    if version_id.is_some()
+        && ctx
+            .data_opt::<BucketVersioned>()
+            .is_some_and(|bucket_versioned| !**bucket_versioned)
+    {
+        return Err(async_graphql::Error::new(
+            "Versions of objects cannot be linked as the bucket does not have versioning enabled",
+        ));
+    }
+    let loader = ctx.data::<DataLoader<PresignDataLoader>>()?;
+    let presigned_url = loader
+        .load_one(PresignKey {
+            object_key: object_key.to_string(),
+            version_id,
+            content_type,
+            content_disposition,
+            range,
+            expiry,
+        })
+        .await?
+        .ok_or_else(|| {
+            async_graphql::Error::new(format!("Object {object_key} was not presigned"))
+        })?;
+    match presigned_url {
+        Err(err) if s3_soft_fail(ctx) => {
+            warn!("Failed to presign {object_key}: {}", err.message);
+            Err(async_graphql::Error::new("S3 is unavailable")
+                .extend_with(|_, extensions| extensions.set("code", "S3_UNAVAILABLE")))
+        }
+        presigned_url => presigned_url,
+    }
+}
+
+/// Fetches the metadata of the object of the attachment through the [`ObjectMetadataDataLoader`], such that the metadata of many attachments is fetched concurrently
+///
+/// Returns `Some(None)` if the object does not exist, or [`None`] if its metadata could not be fetched.
+async fn object_metadata(
+    ctx: &Context<'_>,
+    attachment: &impl StoredAttachment,
+) -> async_graphql::Result<Option<Option<ObjectMetadata>>> {
+    let object_key = attachment.object_key(ctx.data::<ObjectKeyRewrite>()?);
+    let loader = ctx.data::<DataLoader<ObjectMetadataDataLoader>>()?;
+    loader.load_one(object_key.to_string()).await
+}
+
+/// Fetches the configured precision of statistics, defaulting to no rounding
+fn stats_precision(ctx: &Context<'_>) -> StatsPrecision {
+    ctx.data_opt::<StatsPrecision>()
+        .copied()
+        .unwrap_or_default()
+}
+
+#[ComplexObject]
+impl StatisticsSummary {
+    /// The overall high resolution limit, rounded to the configured precision
+    async fn resolution(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.resolution)
+    }
+
+    /// The overall CC(1/2), rounded to the configured precision
+    async fn cc_half(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.cc_half)
+    }
+
+    /// The overall completeness, rounded to the configured precision
+    async fn completeness(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.completeness)
+    }
+}
+
+#[ComplexObject]
+impl CompletenessByShell {
+    /// The overall completeness, rounded to the configured precision
+    async fn overall(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.overall)
+    }
+
+    /// The innershell completeness, rounded to the configured precision
+    async fn inner(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.inner)
+    }
+
+    /// The outershell completeness, rounded to the configured precision
+    async fn outer(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.outer)
+    }
+}
+
+#[ComplexObject]
+impl AutoProcScalingStatics {
+    /// The low resolution limit, rounded to the configured precision
+    async fn resolution_limit_low(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.resolution_limit_low)
+    }
+
+    /// The high resolution limit, rounded to the configured precision
+    async fn resolution_limit_high(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.resolution_limit_high)
+    }
+
+    /// The Rmerge, rounded to the configured precision
+    async fn r_merge(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.r_merge)
+    }
+
+    /// The Rmeas, over all I+ and I-, rounded to the configured precision
+    async fn r_meas_all_i_plus_i_minus(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.r_meas_all_i_plus_i_minus)
+    }
+
+    /// The mean I/sigma(I), rounded to the configured precision
+    async fn mean_i_over_sig_i(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.mean_i_over_sig_i)
+    }
+
+    /// The completeness, rounded to the configured precision
+    async fn completeness(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.completeness)
+    }
+
+    /// The multiplicity, rounded to the configured precision
+    async fn multiplicity(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.multiplicity)
+    }
+
+    /// The anomalous completeness, rounded to the configured precision
+    async fn anomalous_completeness(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.anomalous_completeness)
+    }
+
+    /// The anomalous multiplicity, rounded to the configured precision
+    async fn anomalous_multiplicity(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.anomalous_multiplicity)
+    }
+
+    /// The CC(1/2), rounded to the configured precision
+    async fn cc_half(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.cc_half)
+    }
+
+    /// The anomalous CC, rounded to the configured precision
+    async fn cc_anomalous(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.cc_anomalous)
+    }
+
+    /// Whether the anomalous CC of the shell exceeds the configured threshold, indicating a significant anomalous signal, or null if the anomalous CC is absent
+    async fn anomalous_significant(&self, ctx: &Context<'_>) -> Option<bool> {
+        ctx.data_opt::<AnomalousSignificanceThreshold>()
+            .copied()
+            .unwrap_or_default()
+            .is_significant(self)
+    }
+
+    /// The redundancy, as the ratio of total to unique observations, rounded to the configured precision, or null if either count is absent or there are no unique observations
+    async fn redundancy(&self, ctx: &Context<'_>) -> Option<f64> {
+        stats_precision(ctx).round(self.redundancy())
+    }
+}
+
+#[ComplexObject]
+impl AutoProcFileAttachment {
+    /// Whether the file may be downloaded without the internal attachment scope
+    async fn is_downloadable(&self, ctx: &Context<'_>) -> bool {
+        downloadable(ctx, self)
+    }
+
+    /// Generate downloadable link for files in s3 bucket, optionally for a specific object version, served with a content type and disposition, inferred from the file if omitted, and restricted to a byte range such as `bytes=0-1023`, or null if it could not be presigned
+    async fn file_url(
+        &self,
+        ctx: &Context<'_>,
+        version_id: Option<String>,
+        content_type: Option<String>,
+        disposition: Option<ContentDisposition>,
+        range: Option<String>,
+    ) -> async_graphql::Result<Option<String>> {
+        let presigned_url = presign_attachment(
+            ctx,
+            self,
+            version_id,
+            content_type,
+            disposition,
+            range,
+            None,
+        )
+        .await?;
+        Ok(Some(presigned_url.url))
+    }
+
+    /// Generate downloadable link for files in s3 bucket, along with the time at which it expires, served with a content type and disposition inferred from the file if omitted, restricted to a byte range such as `bytes=0-1023`, and valid for `expirySeconds` if supplied, or null if it could not be presigned
+    async fn presigned_file_url(
+        &self,
+        ctx: &Context<'_>,
+        version_id: Option<String>,
+        content_type: Option<String>,
+        disposition: Option<ContentDisposition>,
+        range: Option<String>,
+        expiry_seconds: Option<u64>,
+    ) -> async_graphql::Result<Option<PresignedUrl>> {
+        presign_attachment(
+            ctx,
+            self,
+            version_id,
+            content_type,
+            disposition,
+            range,
+            expiry_seconds,
+        )
+        .await
+        .map(Some)
+    }
+
+    /// The size of the file in the s3 bucket, in bytes, or null if it does not exist or could not be checked
+    async fn size_bytes(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<i64>> {
+        Ok(object_metadata(ctx, self)
+            .await?
+            .flatten()
+            .map(|metadata| metadata.size_bytes))
+    }
+
+    /// The time at which the file in the s3 bucket was last modified, or null if it does not exist or could not be checked
+    async fn last_modified(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Timestamp>> {
+        Ok(object_metadata(ctx, self)
+            .await?
+            .flatten()
+            .and_then(|metadata| metadata.last_modified))
+    }
+
+    /// Whether the file exists in the s3 bucket, or null if it could not be checked
+    async fn exists(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<bool>> {
+        Ok(object_metadata(ctx, self)
+            .await?
+            .map(|metadata| metadata.is_some()))
+    }
+}
+
+#[ComplexObject]
+impl DataCollectionFileAttachment {
+    /// Whether the file may be downloaded without the internal attachment scope
+    async fn is_downloadable(&self, ctx: &Context<'_>) -> bool {
+        downloadable(ctx, self)
+    }
+
+    /// Generate downloadable link for files in s3 bucket, optionally for a specific object version, served with a content type and disposition, inferred from the file if omitted, and restricted to a byte range such as `bytes=0-1023`, or null if it could not be presigned
+    async fn file_url(
+        &self,
+        ctx: &Context<'_>,
+        version_id: Option<String>,
+        content_type: Option<String>,
+        disposition: Option<ContentDisposition>,
+        range: Option<String>,
+    ) -> async_graphql::Result<Option<String>> {
+        let presigned_url = presign_attachment(
+            ctx,
+            self,
+            version_id,
+            content_type,
+            disposition,
+            range,
+            None,
+        )
+        .await?;
+        Ok(Some(presigned_url.url))
+    }
+
+    /// Generate downloadable link for files in s3 bucket, along with the time at which it expires, served with a content type and disposition inferred from the file if omitted, restricted to a byte range such as `bytes=0-1023`, and valid for `expirySeconds` if supplied, or null if it could not be presigned
+    async fn presigned_file_url(
+        &self,
+        ctx: &Context<'_>,
+        version_id: Option<String>,
+        content_type: Option<String>,
+        disposition: Option<ContentDisposition>,
+        range: Option<String>,
+        expiry_seconds: Option<u64>,
+    ) -> async_graphql::Result<Option<PresignedUrl>> {
+        presign_attachment(
+            ctx,
+            self,
+            version_id,
+            content_type,
+            disposition,
+            range,
+            expiry_seconds,
+        )
+        .await
+        .map(Some)
+    }
+
+    /// The size of the file in the s3 bucket, in bytes, or null if it does not exist or could not be checked
+    async fn size_bytes(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<i64>> {
+        Ok(object_metadata(ctx, self)
+            .await?
+            .flatten()
+            .map(|metadata| metadata.size_bytes))
+    }
+
+    /// The time at which the file in the s3 bucket was last modified, or null if it does not exist or could not be checked
+    async fn last_modified(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Timestamp>> {
+        Ok(object_metadata(ctx, self)
+            .await?
+            .flatten()
+            .and_then(|metadata| metadata.last_modified))
+    }
+
+    /// Whether the file exists in the s3 bucket, or null if it could not be checked
+    async fn exists(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<bool>> {
+        Ok(object_metadata(ctx, self)
+            .await?
+            .map(|metadata| metadata.is_some()))
+    }
+}
+
+#[Object]
+impl Query {
+    /// Returns the SQL the dataloaders would run for a data collection, with bound values redacted
+    #[graphql(visible = "explain_enabled")]
+    async fn explain(
+        &self,
+        ctx: &Context<'_>,
+        data_collection_id: u32,
+    ) -> async_graphql::Result<LoaderQueries> {
+        require_feature(ctx, Feature::Explain)?;
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query_builder = database.get_database_backend().get_query_builder();
+        Ok(LoaderQueries {
+            processing_jobs: processing_jobs_query(&[(data_collection_id, None)])
+                .build_any(query_builder.deref())
+                .0,
+            auto_processing: auto_processing_query(vec![data_collection_id])
+                .build_any(query_builder.deref())
+                .0,
+        })
+    }
+
+    /// Fetches the auto processing referenced by a share token, rejecting tampered or expired tokens
+    async fn resolve_share_token(
+        &self,
+        ctx: &Context<'_>,
+        token: String,
+    ) -> async_graphql::Result<Option<AutoProcessing>> {
+        let signer = ctx
+            .data_opt::<ShareTokenSigner>()
+            .ok_or_else(|| async_graphql::Error::new("Share tokens are not enabled"))?;
+        let reference = signer.verify(&token)?;
+        let loader = ctx.data_unchecked::<DataLoader<AutoProcessingDataLoader>>();
+        Ok(loader
+            .load_one((
+                reference.data_collection_id,
+                AutoProcessingFilter::default(),
+            ))
+            .await?
+            .and_then(|auto_processing| {
+                auto_processing.into_iter().find(|auto_processing| {
+                    auto_processing.auto_proc_integration_id == reference.auto_proc_integration_id
+                })
+            }))
+    }
+
+    /// Fetches the auto processing file attachments with the ids, in the order requested, omitting ids which do not exist
+    async fn attachments_by_ids(
+        &self,
+        ctx: &Context<'_>,
+        ids: Vec<u32>,
+    ) -> async_graphql::Result<Vec<AutoProcFileAttachment>> {
+        if ids.len() > MAX_ATTACHMENT_IDS {
+            return Err(async_graphql::Error::new(format!(
+                "At most {MAX_ATTACHMENT_IDS} attachments may be fetched by id"
+            ))
+            .extend_with(|_, extensions| extensions.set("code", "BAD_USER_INPUT")));
+        }
+        let loader = ctx.data_unchecked::<DataLoader<FileAttachmentByIdDataLoader>>();
+        let attachments = loader.load_many(ids.iter().copied()).await?;
+        Ok(ids
+            .iter()
+            .filter_map(|id| attachments.get(id).cloned())
+            .collect())
+    }
+
+    /// Fetches the data collections of a visit, e.g. `cm12345-6`, or null if the visit does not exist
+    async fn data_collections_for_visit(
+        &self,
+        ctx: &Context<'_>,
+        visit: String,
+    ) -> async_graphql::Result<Option<Vec<DataCollection>>> {
+        let visit = visit.parse::<Visit>()?;
+        let database = ctx.data::<DatabaseConnection>()?;
+        let backend = database.get_database_backend();
+        let session_query = sea_query::Query::select()
+            .column((bl_session::Entity, bl_session::Column::SessionId))
+            .from(bl_session::Entity)
+            .inner_join(
+                proposal::Entity,
+                Expr::col((proposal::Entity, proposal::Column::ProposalId))
+                    .equals((bl_session::Entity, bl_session::Column::ProposalId)),
+            )
+            .and_where(
+                Expr::col((proposal::Entity, proposal::Column::ProposalCode))
+                    .eq(visit.proposal_code),
+            )
+            .and_where(
+                Expr::col((proposal::Entity, proposal::Column::ProposalNumber))
+                    .eq(visit.proposal_number),
+            )
+            .and_where(
+                Expr::col((bl_session::Entity, bl_session::Column::VisitNumber))
+                    .eq(visit.visit_number),
+            )
+            .build_any(backend.get_query_builder().deref());
+        let Some(session) = database
+            .query_one(Statement::from_sql_and_values(
+                backend,
+                &session_query.0,
+                session_query.1,
+            ))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let session_id = session.try_get::<u32>("", "sessionId")?;
+
+        let data_collections_query = sea_query::Query::select()
+            .column((
+                data_collection::Entity,
+                data_collection::Column::DataCollectionId,
+            ))
+            .from(data_collection::Entity)
+            .inner_join(
+                data_collection_group::Entity,
+                Expr::col((
+                    data_collection_group::Entity,
+                    data_collection_group::Column::DataCollectionGroupId,
+                ))
+                .equals((
+                    data_collection::Entity,
+                    data_collection::Column::DataCollectionGroupId,
+                )),
+            )
+            .and_where(
+                Expr::col((
+                    data_collection_group::Entity,
+                    data_collection_group::Column::SessionId,
+                ))
+                .eq(session_id),
+            )
+            .order_by(
+                (
+                    data_collection::Entity,
+                    data_collection::Column::DataCollectionId,
+                ),
+                Order::Asc,
+            )
+            .build_any(backend.get_query_builder().deref());
+        database
+            .query_all(Statement::from_sql_and_values(
+                backend,
+                &data_collections_query.0,
+                data_collections_query.1,
+            ))
+            .await?
+            .into_iter()
+            .map(|record| {
+                Ok(DataCollection {
+                    id: record.try_get("", "dataCollectionId")?,
+                })
+            })
+            .collect::<async_graphql::Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    /// Reports the health of the database and S3 bucket, checked concurrently, along with the latency of each
+    async fn readiness(&self, ctx: &Context<'_>) -> async_graphql::Result<ServiceReadiness> {
+        let checks = ctx.data::<DependencyChecks>()?;
+        let database = ctx.data::<DatabaseConnection>()?;
+        Ok(checks.check(database).await)
+    }
+
+    /// Reports the kind and version of the database server, to correlate behaviour with specific server versions
+    #[graphql(directive = requires_scope::apply(ADMIN_SCOPE.to_string()))]
+    async fn database_info(&self, ctx: &Context<'_>) -> async_graphql::Result<DatabaseInfo> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let backend = database.get_database_backend();
+        let sql = match backend {
+            DbBackend::MySql | DbBackend::Postgres => "SELECT VERSION() AS version",
+            DbBackend::Sqlite => "SELECT sqlite_version() AS version",
+        };
+        let version = database
+            .query_one(Statement::from_string(backend, sql))
+            .await?
+            .ok_or_else(|| async_graphql::Error::new("The database did not report its version"))?
+            .try_get("", "version")?;
+        Ok(DatabaseInfo {
+            backend: backend.into(),
+            version,
+        })
+    }
+
+    /// Fetches the overall, innershell and outershell statistics of an auto proc scaling, omitting missing shells
+    async fn scaling_statistics(
+        &self,
+        ctx: &Context<'_>,
+        auto_proc_scaling_id: u32,
+    ) -> async_graphql::Result<Vec<AutoProcScalingStatics>> {
+        load_shell_statistics(ctx, auto_proc_scaling_id).await
+    }
+
+    /// Fetches the files attached to a data collection, both directly and to its auto processing
+    ///
+    /// The attachments of both sources are merged and ordered together, before `offset` attachments are skipped and at
+    /// most `limit` returned. Attachments of all types are returned unless a `file_type` is given.
+    async fn attachments(
+        &self,
+        ctx: &Context<'_>,
+        data_collection_id: u32,
+        #[graphql(default)] order_by: AttachmentOrder,
+        limit: Option<i64>,
+        #[graphql(default)] offset: i64,
+        file_type: Option<AttachmentFileType>,
+    ) -> async_graphql::Result<Vec<Attachment>> {
+        let page = Page::from_arguments(ctx, limit, offset)?;
+        let database = ctx.data::<DatabaseConnection>()?;
+        let backend = database.get_database_backend();
+        // Neither source can contribute more than the first offset + limit attachments of the merged set
+        let source_limit = page.end();
+        let mut auto_proc_attachments_query = sea_query::Query::select();
+        auto_proc_attachments_query
+            .column((auto_proc_program_attachment::Entity, Asterisk))
+            .from(auto_proc_program_attachment::Entity)
+            .inner_join(
+                auto_proc_integration::Entity,
+                Expr::col((
+                    auto_proc_integration::Entity,
+                    auto_proc_integration::Column::AutoProcProgramId,
+                ))
+                .equals((
+                    auto_proc_program_attachment::Entity,
+                    auto_proc_program_attachment::Column::AutoProcProgramId,
+                )),
+            )
+            .and_where(
+                Expr::col((
+                    auto_proc_integration::Entity,
+                    auto_proc_integration::Column::DataCollectionId,
+                ))
+                .eq(data_collection_id),
+            );
+        if let Some(file_type) = file_type {
+            auto_proc_attachments_query.and_where(
+                Expr::col((
+                    auto_proc_program_attachment::Entity,
+                    auto_proc_program_attachment::Column::FileType,
+                ))
+                .eq(file_type.to_string()),
+            );
+        }
+        if let Some(source_limit) = source_limit {
+            if order_by == AttachmentOrder::FileName {
+                auto_proc_attachments_query.order_by_expr(
+                    binary_order(Expr::col((
+                        auto_proc_program_attachment::Entity,
+                        auto_proc_program_attachment::Column::FileName,
+                    ))),
+                    Order::Asc,
+                );
+            }
+            auto_proc_attachments_query
+                .order_by(
+                    (
+                        auto_proc_program_attachment::Entity,
+                        auto_proc_program_attachment::Column::AutoProcProgramAttachmentId,
+                    ),
+                    Order::Asc,
+                )
+                .limit(source_limit);
+        }
+        let auto_proc_attachments_query =
+            auto_proc_attachments_query.build_any(backend.get_query_builder().deref());
+        let auto_proc_attachments = auto_proc_program_attachment::Entity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                backend,
+                &auto_proc_attachments_query.0,
+                auto_proc_attachments_query.1,
+            ))
+            .all(database)
+            .await?;
+        let mut data_collection_attachments_query = data_collection_file_attachment::Entity::find()
+            .filter(
+                data_collection_file_attachment::Column::DataCollectionId.eq(data_collection_id),
+            );
+        // The types of data collection attachments are recorded in lower case, unlike those of auto processing
+        if let Some(file_type) = file_type {
+            data_collection_attachments_query = data_collection_attachments_query.filter(
+                Expr::expr(Func::lower(Expr::col(
+                    data_collection_file_attachment::Column::FileType,
+                )))
+                .eq(file_type.to_string().to_lowercase()),
+            );
+        }
+        if let Some(source_limit) = source_limit {
+            // The file name is not stored, but taken from the end of the full path
+            if order_by == AttachmentOrder::FileName {
+                data_collection_attachments_query = data_collection_attachments_query.order_by(
+                    binary_order(Expr::cust_with_expr(
+                        "SUBSTRING_INDEX($1, '/', -1)",
+                        Expr::col(data_collection_file_attachment::Column::FileFullPath),
+                    )),
+                    Order::Asc,
+                );
+            }
+            data_collection_attachments_query = data_collection_attachments_query
+                .order_by_asc(
+                    data_collection_file_attachment::Column::DataCollectionFileAttachmentId,
+                )
+                .limit(source_limit);
+        }
+        let data_collection_attachments = data_collection_attachments_query.all(database).await?;
+        let mut attachments = data_collection_attachments
+            .into_iter()
+            .map(|attachment| Attachment::DataCollection(attachment.into()))
+            .chain(
+                auto_proc_attachments
+                    .into_iter()
+                    .map(|attachment| Attachment::AutoProc(attachment.into())),
+            )
+            .collect::<Vec<_>>();
+        attachments.sort_by(|first, second| {
+            first
+                .ordering_key(order_by)
+                .cmp(&second.ordering_key(order_by))
+        });
+        Ok(attachments
+            .into_iter()
+            .skip(page.skip())
+            .take(page.take())
+            .collect())
+    }
+
+    /// Reference datasets resolver for the router
+    #[graphql(entity)]
+    async fn router_data_collection(
+        &self,
+        ctx: &Context<'_>,
+        id: u32,
+    ) -> async_graphql::Result<DataCollection> {
+        require_entity(ctx, FederationEntity::DataCollection)?;
+        Ok(DataCollection { id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_database::TestDatabase;
+    use super::*;
+    use crate::presigner::{
+        PresignExpiry, PresignFuture, Presigner, SharedPresigner, UnsignedObjectUrls,
+    };
+    use async_graphql::Request;
+    use aws_credential_types::Credentials;
+    use aws_sdk_s3::{config::Region, Client};
+    use std::sync::Arc;
+    use url::Url;
+
+    /// The value of a field of each auto processing of the data collection, keyed on the auto processing program id
+    fn auto_processing_field(
+        data: &serde_json::Value,
+        data_collection_id: u32,
+        field: &str,
+    ) -> HashMap<u64, serde_json::Value> {
+        data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|data_collection| data_collection["id"] == data_collection_id)
+            .unwrap()["autoProcessing"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|auto_processing| {
+                (
+                    auto_processing["autoProcProgramId"].as_u64().unwrap(),
+                    auto_processing[field].clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// The type name and id of each attachment returned by the attachments query, in order
+    fn attachment_keys(data: &serde_json::Value) -> Vec<(String, u64)> {
+        data["attachments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|attachment| {
+                (
+                    attachment["__typename"].as_str().unwrap().to_string(),
+                    attachment["id"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    /// A schema presigning unsigned links to the objects, under which the listed types of file are downloadable
+    fn presigning_schema(
+        database: &TestDatabase,
+        downloadable_file_types: impl IntoIterator<Item = AttachmentFileType>,
+    ) -> RootSchema {
+        presigning_schema_with(
+            database,
+            downloadable_file_types,
+            Arc::new(UnsignedObjectUrls::new(
+                Url::parse("http://s3.test").unwrap(),
+                true,
+            )),
+        )
+    }
+
+    /// A schema presigning links to the objects through the presigner, under which the listed types of file are downloadable
+    fn presigning_schema_with(
+        database: &TestDatabase,
+        downloadable_file_types: impl IntoIterator<Item = AttachmentFileType>,
+        presigner: SharedPresigner,
+    ) -> RootSchema {
+        let expiry = PresignExpiry::new(Duration::from_secs(60), Duration::ZERO);
+        database
+            .schema_builder()
+            .data(ObjectKeyRewrite::default())
+            .data(DownloadableFileTypes::new(downloadable_file_types))
+            .data(PresignExpiryBounds {
+                min: Duration::from_secs(60),
+                max: expiry.max_expiry(),
+            })
+            .data(DataLoader::new(
+                PresignDataLoader::new(
+                    presigner,
+                    "processed".parse().unwrap(),
+                    expiry,
+                    1,
+                    S3ConcurrencyLimit::default(),
+                ),
+                tokio::spawn,
+            ))
+            .finish()
+    }
+
+    /// The ids of the auto processing programs loaded for each key, sorted
+    fn program_ids<K: Eq + std::hash::Hash>(
+        results: &HashMap<K, Vec<AutoProcessing>>,
+        key: &K,
+    ) -> Option<Vec<u32>> {
+        results.get(key).map(|auto_processings| {
+            let mut ids = auto_processings
+                .iter()
+                .filter_map(|auto_processing| auto_processing.auto_proc_program_id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        })
+    }
+
+    /// The distinct ids of the processing jobs loaded for each key, sorted
+    fn job_ids<K: Eq + std::hash::Hash>(
+        results: &HashMap<K, Vec<ProcessingJob>>,
+        key: &K,
+    ) -> Option<Vec<u32>> {
+        results.get(key).map(|jobs| {
+            let mut ids = jobs
+                .iter()
+                .filter_map(|job| job.processing_job_id)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids.dedup();
+            ids
+        })
+    }
+
+    #[tokio::test]
+    async fn auto_processing_is_grouped_by_data_collection_and_filter() {
+        let database = TestDatabase::start().await;
+        let loader = AutoProcessingDataLoader::new(database.guarded(), Duration::from_secs(1));
+        let successful = AutoProcessingFilter {
+            statuses: Some(vec![1]),
+            excluded_statuses: Vec::new(),
+        };
+        let not_failed = AutoProcessingFilter {
+            statuses: None,
+            excluded_statuses: vec![0],
+        };
+        let keys = [
+            (1, AutoProcessingFilter::default()),
+            (2, AutoProcessingFilter::default()),
+            (3, AutoProcessingFilter::default()),
+            (1, successful.clone()),
+            (1, not_failed.clone()),
+        ];
+
+        let results = loader.load(&keys).await.unwrap();
+
+        assert_eq!(program_ids(&results, &keys[0]), Some(vec![31, 32, 33]));
+        assert_eq!(program_ids(&results, &keys[1]), Some(vec![34]));
+        assert_eq!(program_ids(&results, &keys[2]), None);
+        assert_eq!(program_ids(&results, &(1, successful)), Some(vec![31]));
+        assert_eq!(program_ids(&results, &(1, not_failed)), Some(vec![31, 33]));
+    }
+
+    #[tokio::test]
+    async fn processing_jobs_are_grouped_by_data_collection_and_automatic() {
+        let database = TestDatabase::start().await;
+        let loader = ProcessingJobDataLoader::new(database.guarded(), Duration::from_secs(1));
+        let keys = [
+            (1, None),
+            (1, Some(true)),
+            (1, Some(false)),
+            (2, None),
+            (3, None),
+        ];
+
+        let results = loader.load(&keys).await.unwrap();
+
+        assert_eq!(results[&(1, None)].len(), 4);
+        assert_eq!(job_ids(&results, &(1, None)), Some(vec![11, 12, 13]));
+        assert_eq!(job_ids(&results, &(1, Some(true))), Some(vec![11, 12]));
+        assert_eq!(job_ids(&results, &(1, Some(false))), Some(vec![13]));
+        assert_eq!(job_ids(&results, &(2, None)), Some(vec![14]));
+        assert_eq!(job_ids(&results, &(3, None)), None);
+    }
+
+    #[tokio::test]
+    async fn quality_grade_is_derived_from_the_overall_statistics_of_the_scaling() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId qualityGrade } } }"#,
+            )
+            .await;
+
+        let grades = auto_processing_field(&data, 1, "qualityGrade");
+        assert_eq!(grades[&31], "A");
+        assert_eq!(grades[&32], "C");
+        assert_eq!(grades[&33], serde_json::Value::Null);
+        assert_eq!(auto_processing_field(&data, 2, "qualityGrade")[&34], "B");
+    }
+
+    #[tokio::test]
+    async fn attachments_are_filtered_by_file_type_across_both_sources() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let all = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 1) { __typename id } }",
+            )
+            .await;
+        let logs = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 1, fileType: LOG) { __typename id } }",
+            )
+            .await;
+
+        assert_eq!(attachment_keys(&all).len(), 6);
+        assert_eq!(
+            attachment_keys(&logs),
+            vec![
+                ("AutoProcFileAttachment".to_string(), 81),
+                ("DataCollectionFileAttachment".to_string(), 92),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn attachments_of_either_type_require_the_internal_scope_unless_downloadable() {
+        let database = TestDatabase::start().await;
+        let schema = presigning_schema(&database, [AttachmentFileType::Log]);
+        let query = "{ attachments(dataCollectionId: 1) { __typename id fileUrl } }";
+
+        let response = database.try_execute(&schema, query).await;
+        let data = response.data.into_json().unwrap();
+        let presigned = data["attachments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|attachment| {
+                (
+                    attachment["__typename"].as_str().unwrap().to_string(),
+                    attachment["id"].as_u64().unwrap(),
+                    attachment["fileUrl"].is_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            presigned,
+            vec![
+                ("AutoProcFileAttachment".to_string(), 81, true),
+                ("AutoProcFileAttachment".to_string(), 82, false),
+                ("AutoProcFileAttachment".to_string(), 83, false),
+                ("AutoProcFileAttachment".to_string(), 84, false),
+                ("DataCollectionFileAttachment".to_string(), 91, false),
+                ("DataCollectionFileAttachment".to_string(), 92, true),
+            ]
+        );
+        assert_eq!(response.errors.len(), 4);
+        for error in response.errors {
+            assert_eq!(
+                error.extensions.unwrap().get("code"),
+                Some(&async_graphql::Value::from("FORBIDDEN"))
+            );
+        }
+
+        let data = database
+            .execute(
+                &schema,
+                Request::new(query).data(GrantedScopes::from_header(INTERNAL_ATTACHMENT_SCOPE)),
+            )
+            .await;
+        assert!(data["attachments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|attachment| attachment["fileUrl"].is_string()));
+    }
+
+    #[tokio::test]
+    async fn downloadability_is_reported_for_either_type() {
+        let database = TestDatabase::start().await;
+        let schema = presigning_schema(
+            &database,
+            [
+                AttachmentFileType::Log,
+                AttachmentFileType::Result,
+                AttachmentFileType::Graph,
+            ],
+        );
+
+        let data = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 1) { id ... on AutoProcFileAttachment { isDownloadable } ... on DataCollectionFileAttachment { isDownloadable } } }",
+            )
+            .await;
+
+        let downloadable = data["attachments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|attachment| {
+                (
+                    attachment["id"].as_u64().unwrap(),
+                    attachment["isDownloadable"].as_bool().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            downloadable,
+            vec![
+                (81, true),
+                (82, true),
+                (83, false),
+                (84, true),
+                (91, false),
+                (92, true),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn attachments_ordered_by_file_name_are_paged_across_both_sources() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let first_page = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 1, orderBy: FILE_NAME, limit: 3) { __typename id } }",
+            )
+            .await;
+        let second_page = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 1, orderBy: FILE_NAME, limit: 3, offset: 3) { __typename id } }",
+            )
+            .await;
+        let mixed_case = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 2, orderBy: FILE_NAME, limit: 1) { __typename id } }",
+            )
+            .await;
+
+        assert_eq!(
+            attachment_keys(&first_page),
+            vec![
+                ("DataCollectionFileAttachment".to_string(), 92),
+                ("AutoProcFileAttachment".to_string(), 84),
+                ("DataCollectionFileAttachment".to_string(), 91),
+            ]
+        );
+        assert_eq!(
+            attachment_keys(&second_page),
+            vec![
+                ("AutoProcFileAttachment".to_string(), 83),
+                ("AutoProcFileAttachment".to_string(), 82),
+                ("AutoProcFileAttachment".to_string(), 81),
+            ]
+        );
+        assert_eq!(
+            attachment_keys(&mixed_case),
+            vec![("DataCollectionFileAttachment".to_string(), 93)]
+        );
+    }
+
+    #[tokio::test]
+    async fn attachments_ordered_by_id_are_paged_across_both_sources() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                "{ attachments(dataCollectionId: 1, limit: 2, offset: 3) { __typename id } }",
+            )
+            .await;
+
+        assert_eq!(
+            attachment_keys(&data),
+            vec![
+                ("AutoProcFileAttachment".to_string(), 84),
+                ("DataCollectionFileAttachment".to_string(), 91),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn data_collections_are_processing_whilst_any_run_is_in_progress() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id isProcessing } }"#,
+            )
+            .await;
+
+        let processing = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|data_collection| {
+                (
+                    data_collection["id"].as_u64().unwrap(),
+                    data_collection["isProcessing"].as_bool().unwrap(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            processing,
+            HashMap::from([(1, true), (2, false), (3, false)])
+        );
+    }
+
+    #[tokio::test]
+    async fn effective_resolution_is_derived_from_the_overall_statistics_of_the_scaling() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId effectiveResolution } } }"#,
+            )
+            .await;
+
+        let effective_resolutions = auto_processing_field(&data, 1, "effectiveResolution");
+        let successful = effective_resolutions[&31].as_f64().unwrap();
+        assert!((successful - 1.5 * (100.0f64 / 99.5).cbrt()).abs() < 1e-4);
+        let failed = effective_resolutions[&32].as_f64().unwrap();
+        assert!((failed - 2.0 * (100.0f64 / 80.0).cbrt()).abs() < 1e-4);
+        assert_eq!(effective_resolutions[&33], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn standalone_schema_omits_the_federation_fields() {
+        let schema = root_schema_builder(true).finish();
+
+        let sdl = schema_sdl(&schema, true, &FederationEntities::default());
+        assert!(!sdl.contains("_entities"), "{sdl}");
+        assert!(!sdl.contains("_service"), "{sdl}");
+        assert!(!sdl.contains("_Any"), "{sdl}");
+        assert!(sdl.contains("dataCollectionsForVisit"), "{sdl}");
+
+        let response = schema
+            .execute(r#"{ _entities(representations: [{ __typename: "DataCollection", id: 1 }]) { __typename } }"#)
+            .await;
+        assert!(!response.errors.is_empty());
+        assert_eq!(response.data, async_graphql::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn federated_schema_serves_the_federation_fields() {
+        let schema = root_schema_builder(false).finish();
+
+        let response = schema.execute("{ _service { sdl } }").await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert!(data["_service"]["sdl"]
+            .as_str()
+            .unwrap()
+            .contains("@key(fields: \"id\")"));
+    }
+
+    /// The distinct ids of the processing jobs under the alias of the first data collection of the visit, sorted
+    fn aliased_job_ids(data: &serde_json::Value, alias: &str) -> Vec<u64> {
+        let mut ids = data["dataCollectionsForVisit"][0][alias]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|job| job["processingJobId"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    #[tokio::test]
+    async fn processing_jobs_are_filtered_by_whether_they_were_automatic() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") {
+                    all: processingJobs { processingJobId }
+                    automatic: processingJobs(automatic: true) { processingJobId }
+                    manual: processingJobs(automatic: false) { processingJobId }
+                } }"#,
+            )
+            .await;
+
+        assert_eq!(aliased_job_ids(&data, "all"), vec![11, 12, 13]);
+        assert_eq!(aliased_job_ids(&data, "automatic"), vec![11, 12]);
+        assert_eq!(aliased_job_ids(&data, "manual"), vec![13]);
+    }
+
+    #[tokio::test]
+    async fn processing_message_is_resolved_only_with_its_scope() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+        let query = r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId processingMessage } } }"#;
+
+        let response = database.try_execute(&schema, query).await;
+        assert!(!response.errors.is_empty());
+        assert!(response.errors.iter().all(|error| {
+            error
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.get("code"))
+                == Some(&"FORBIDDEN".into())
+        }));
+
+        let data = database
+            .execute(
+                &schema,
+                Request::new(query).data(GrantedScopes::from_header(PROCESSING_MESSAGE_SCOPE)),
+            )
+            .await;
+        let messages = auto_processing_field(&data, 1, "processingMessage");
+        assert_eq!(messages[&31], "processing successful");
+        assert_eq!(messages[&32], "indexing failed");
+        assert_eq!(messages[&33], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn presigns_beyond_the_cap_are_rejected() {
+        let database = TestDatabase::start().await;
+        let schema = presigning_schema(&database, [AttachmentFileType::Log]);
+        let query = "{ attachments(dataCollectionId: 1) { id fileUrl } }";
+
+        let response = database
+            .try_execute(
+                &schema,
+                Request::new(query)
+                    .data(GrantedScopes::from_header(INTERNAL_ATTACHMENT_SCOPE))
+                    .data(MaxPresignsPerRequest(4)),
+            )
+            .await;
+
+        let data = response.data.into_json().unwrap();
+        let attachments = data["attachments"].as_array().unwrap();
+        assert_eq!(attachments.len(), 6);
+        assert_eq!(
+            attachments
+                .iter()
+                .filter(|attachment| attachment["fileUrl"].is_string())
+                .count(),
+            4
+        );
+        assert_eq!(response.errors.len(), 2);
+        for error in response.errors {
+            assert_eq!(
+                error.extensions.unwrap().get("code"),
+                Some(&async_graphql::Value::from("PRESIGN_LIMIT_EXCEEDED"))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_processing_job_of_each_program_is_the_one_with_the_greatest_id() {
+        let database = TestDatabase::start().await;
+        let loader =
+            LatestProcessingJobsDataLoader::new(database.guarded(), Duration::from_secs(1));
+
+        let results = loader.load(&[1, 2, 3]).await.unwrap();
+
+        let latest = |data_collection_id: u32| {
+            results.get(&data_collection_id).map(|jobs| {
+                jobs.iter()
+                    .map(|job| job.processing_job_id.unwrap())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(latest(1), Some(vec![12, 13]));
+        assert_eq!(latest(2), Some(vec![14]));
+        assert_eq!(latest(3), None);
+    }
+
+    #[tokio::test]
+    async fn refined_beam_center_is_null_without_both_coordinates() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId refinedBeamCenter { x y } } } }"#,
+            )
+            .await;
+
+        let centers = auto_processing_field(&data, 1, "refinedBeamCenter");
+        assert_eq!(centers[&31], serde_json::json!({ "x": 210.5, "y": 220.5 }));
+        assert_eq!(centers[&32], serde_json::json!({ "x": 211.0, "y": 221.0 }));
+        assert_eq!(centers[&33], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn total_attachment_count_sums_both_sources() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id totalAttachmentCount } }"#,
+            )
+            .await;
+
+        let counts = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|data_collection| {
+                (
+                    data_collection["id"].as_u64().unwrap(),
+                    data_collection["totalAttachmentCount"].as_u64().unwrap(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(counts, HashMap::from([(1, 6), (2, 3), (3, 0)]));
+    }
+
+    #[test]
+    fn sums_of_observations_do_not_overflow() {
+        let maximum = Some(i64::from(i32::MAX));
+        assert_eq!(
+            sum_present([maximum, None, maximum]),
+            Some(2 * i64::from(i32::MAX))
+        );
+        assert_eq!(sum_present([None, None]), None);
+    }
+
+    #[tokio::test]
+    async fn large_observation_counts_are_not_truncated() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{
+                    scalingStatistics(autoProcScalingId: 62) { nTotalObservations nTotalUniqueObservations }
+                    dataCollectionsForVisit(visit: "cm12345-1") {
+                        id autoProcessing { autoProcProgramId totalObservations uniqueObservations }
+                    }
+                }"#,
+            )
+            .await;
+
+        let overall = &data["scalingStatistics"][0];
+        assert_eq!(overall["nTotalObservations"], 2147483647_i64);
+        assert_eq!(overall["nTotalUniqueObservations"], 1000000);
+        let total_observations = auto_processing_field(&data, 1, "totalObservations");
+        assert_eq!(total_observations[&32], 4294967294_i64);
+        let unique_observations = auto_processing_field(&data, 1, "uniqueObservations");
+        assert_eq!(unique_observations[&32], 1000000);
+    }
+
+    #[tokio::test]
+    async fn scaling_statistics_are_fetched_by_scaling_id() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{
+                    complete: scalingStatistics(autoProcScalingId: 61) { autoProcScalingStatisticsId scalingStatisticsType }
+                    partial: scalingStatistics(autoProcScalingId: 62) { autoProcScalingStatisticsId scalingStatisticsType }
+                    missing: scalingStatistics(autoProcScalingId: 63) { autoProcScalingStatisticsId scalingStatisticsType }
+                }"#,
+            )
+            .await;
+
+        let shells = |alias: &str| {
+            data[alias]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|statistics| {
+                    (
+                        statistics["autoProcScalingStatisticsId"].as_u64().unwrap(),
+                        statistics["scalingStatisticsType"]
+                            .as_str()
+                            .unwrap()
+                            .to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            shells("complete"),
+            [
+                (71, "OVERALL".to_string()),
+                (72, "INNER_SHELL".to_string()),
+                (73, "OUTER_SHELL".to_string())
+            ]
+        );
+        assert_eq!(
+            shells("partial"),
+            [(74, "OVERALL".to_string()), (75, "INNER_SHELL".to_string())]
+        );
+        assert!(shells("missing").is_empty());
+    }
+
+    #[tokio::test]
+    async fn statistics_summary_compares_the_overall_statistics_of_each_program() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id statisticsSummary { program resolution ccHalf completeness } } }"#,
+            )
+            .await;
+
+        let summaries = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|data_collection| {
+                let summary = data_collection["statisticsSummary"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|summary| {
+                        (
+                            summary["program"].as_str().unwrap().to_string(),
+                            summary["resolution"].as_f64().unwrap() as f32,
+                            summary["ccHalf"].as_f64().unwrap() as f32,
+                            summary["completeness"].as_f64().unwrap() as f32,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (data_collection["id"].as_u64().unwrap(), summary)
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            summaries,
+            HashMap::from([
+                (
+                    1,
+                    vec![
+                        ("fast_dp".to_string(), 2.0, 90.0, 80.0),
+                        ("xia2 dials".to_string(), 1.5, 99.8, 99.5),
+                    ]
+                ),
+                (2, vec![("xia2 dials".to_string(), 1.8, 95.0, 90.0)]),
+                (3, vec![]),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn success_is_derived_from_the_processing_status() {
+        let database = TestDatabase::start().await;
+        let query = r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId succeeded } } }"#;
+
+        let data = database
+            .execute(&database.schema_builder().finish(), query)
+            .await;
+        assert_eq!(
+            auto_processing_field(&data, 1, "succeeded"),
+            HashMap::from([
+                (31, true.into()),
+                (32, false.into()),
+                (33, serde_json::Value::Null)
+            ])
+        );
+
+        let schema = database
+            .schema_builder()
+            .data(ProcessingStatusCodes::new([0], [1]))
+            .finish();
+        let data = database.execute(&schema, query).await;
+        assert_eq!(
+            auto_processing_field(&data, 1, "succeeded"),
+            HashMap::from([
+                (31, false.into()),
+                (32, true.into()),
+                (33, serde_json::Value::Null)
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn both_attachment_types_resolve_through_the_interface() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ attachments(dataCollectionId: 2) {
+                    __typename id fileName
+                    ... on AutoProcFileAttachment { autoProcFileType: fileType }
+                    ... on DataCollectionFileAttachment { dataCollectionFileType: fileType }
+                } }"#,
+            )
+            .await;
+
+        let mut attachments = data["attachments"].as_array().unwrap().clone();
+        attachments.sort_by_key(|attachment| attachment["id"].as_u64());
+        assert_eq!(
+            attachments,
+            [
+                serde_json::json!({
+                    "__typename": "AutoProcFileAttachment",
+                    "id": 85,
+                    "fileName": "beta.txt",
+                    "autoProcFileType": "LOG",
+                }),
+                serde_json::json!({
+                    "__typename": "DataCollectionFileAttachment",
+                    "id": 93,
+                    "fileName": "Zinc.log",
+                    "dataCollectionFileType": "LOG",
+                }),
+                serde_json::json!({
+                    "__typename": "DataCollectionFileAttachment",
+                    "id": 94,
+                    "fileName": "alpha.png",
+                    "dataCollectionFileType": null,
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn redundancy_is_resolved_from_the_observation_counts() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{
+                    complete: scalingStatistics(autoProcScalingId: 61) { redundancy }
+                    partial: scalingStatistics(autoProcScalingId: 62) { redundancy }
+                }"#,
+            )
+            .await;
+
+        assert_eq!(data["complete"][0]["redundancy"], 4.0);
+        assert_eq!(data["partial"][1]["redundancy"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn repeated_parameter_keys_are_collected_into_arrays() {
+        let mut parameters = serde_json::Map::new();
+        for (key, value) in [
+            ("beamline", serde_json::json!("i03")),
+            ("d_min", serde_json::json!("1.5")),
+            ("d_min", serde_json::json!("1.8")),
+            ("sweep", serde_json::Value::Null),
+            ("d_min", serde_json::json!("2.0")),
+            ("sweep", serde_json::json!("1-3600")),
+        ] {
+            insert_parameter(&mut parameters, key.to_string(), value);
+        }
+
+        assert_eq!(
+            serde_json::Value::Object(parameters),
+            serde_json::json!({
+                "beamline": "i03",
+                "d_min": ["1.5", "1.8", "2.0"],
+                "sweep": [null, "1-3600"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn processing_job_parameters_are_returned_as_a_json_object() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id processingJobs { processingJobId parametersJson } } }"#,
+            )
+            .await;
+
+        let parameters = data["dataCollectionsForVisit"][0]["processingJobs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|job| {
+                (
+                    job["processingJobId"].as_u64().unwrap(),
+                    job["parametersJson"].clone(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            parameters,
+            HashMap::from([
+                (
+                    11,
+                    serde_json::json!({ "beamline": "i03", "ispyb_dcid": "1" })
+                ),
+                (12, serde_json::json!({})),
+                (13, serde_json::json!({ "d_min": "1.5" })),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn space_groups_of_each_program_are_distinct_and_ordered() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id spaceGroups } }"#,
+            )
+            .await;
+
+        let space_groups = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|data_collection| {
+                (
+                    data_collection["id"].as_u64().unwrap(),
+                    data_collection["spaceGroups"].clone(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            space_groups,
+            HashMap::from([
+                (1, serde_json::json!(["P 1 21 1", "P 21 21 21"])),
+                (2, serde_json::json!(["P 21 21 21"])),
+                (3, serde_json::json!([])),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn batches_larger_than_the_maximum_are_split_and_merged() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+        let query = r#"{ dataCollectionsForVisit(visit: "cm12345-1") {
+            id totalAttachmentCount spaceGroups processingPrograms bestResolution
+        } }"#;
+        let execute = |max_batch_size| {
+            schema.execute(Request::new(query).add_data_loaders(
+                database.connection.clone(),
+                LoaderSettings {
+                    slow_query_threshold: Duration::from_secs(1),
+                    max_batch_size,
+                    circuit_breaker: CircuitBreaker::new(u32::MAX, Duration::ZERO),
+                    status_codes: ProcessingStatusCodes::default(),
+                },
+            ))
+        };
+
+        let unbatched = execute(100).await;
+        let batched = execute(1).await;
+
+        assert!(batched.errors.is_empty(), "{:?}", batched.errors);
+        assert_eq!(
+            batched.data.into_json().unwrap(),
+            unbatched.data.into_json().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn keys_beyond_the_maximum_batch_size_are_loaded() {
+        let database = TestDatabase::start().await;
+        let loader = DataLoader::new(
+            AttachmentCountDataLoader::new(database.guarded(), Duration::from_secs(1)),
+            tokio::spawn,
+        )
+        .max_batch_size(2);
+
+        let counts = loader.load_many([1, 2, 3, 4, 5]).await.unwrap();
+
+        assert_eq!(counts.get(&1), Some(&6));
+        assert_eq!(counts.get(&2), Some(&3));
+        assert_eq!(counts.get(&3).copied().unwrap_or_default(), 0);
+    }
+
+    #[tokio::test]
+    async fn processing_message_is_truncated_to_the_requested_or_configured_length() {
+        let database = TestDatabase::start().await;
+        let query = r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing {
+            autoProcProgramId processingMessage short: processingMessageTruncated(maxLength: 10) processingMessageTruncated
+        } } }"#;
+        let request =
+            || Request::new(query).data(GrantedScopes::from_header(PROCESSING_MESSAGE_SCOPE));
+
+        let data = database
+            .execute(&database.schema_builder().finish(), request())
+            .await;
+        let short = auto_processing_field(&data, 1, "short");
+        assert_eq!(short[&31], "processing…");
+        assert_eq!(short[&32], "indexing f…");
+        assert_eq!(short[&33], serde_json::Value::Null);
+        assert_eq!(
+            auto_processing_field(&data, 1, "processingMessageTruncated"),
+            auto_processing_field(&data, 1, "processingMessage")
+        );
+
+        let schema = database
+            .schema_builder()
+            .data(ProcessingMessageMaxLength(8))
+            .finish();
+        let data = database.execute(&schema, request()).await;
+        let truncated = auto_processing_field(&data, 1, "processingMessageTruncated");
+        assert_eq!(truncated[&31], "processi…");
+        assert_eq!(truncated[&32], "indexing…");
+    }
+
+    #[tokio::test]
+    async fn per_shell_statistics_are_deprecated() {
+        let schema = root_schema_builder(true).finish();
+        let reason = "Use `scaling { statistics }`, or the `scalingStatistics` query, which return all shells together";
+
+        let response = schema
+            .execute(r#"{ __type(name: "AutoProcessing") { fields(includeDeprecated: true) { name isDeprecated deprecationReason } } }"#)
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let fields = data["__type"]["fields"].as_array().unwrap();
+        for shell in ["overall", "innerShell", "outerShell"] {
+            let field = fields.iter().find(|field| field["name"] == shell).unwrap();
+            assert_eq!(field["isDeprecated"], true);
+            assert_eq!(field["deprecationReason"], reason);
+        }
+        let scaling = fields
+            .iter()
+            .find(|field| field["name"] == "scaling")
+            .unwrap();
+        assert_eq!(scaling["isDeprecated"], false);
+
+        let sdl = schema_sdl(&schema, true, &FederationEntities::default());
+        assert_eq!(
+            sdl.matches(&format!(r#"@deprecated(reason: "{reason}")"#))
+                .count(),
+            3,
+            "{sdl}"
+        );
+    }
+
+    #[tokio::test]
+    async fn attachment_links_accept_only_well_formed_byte_ranges() {
+        let database = TestDatabase::start().await;
+        let schema = presigning_schema(&database, [AttachmentFileType::Log]);
+
+        for range in ["bytes=0-1023", "bytes=1024-", "bytes=-1024"] {
+            let data = database
+                .execute(
+                    &schema,
+                    format!(
+                        r#"{{ attachments(dataCollectionId: 1, fileType: LOG) {{ id fileUrl(range: "{range}") ... on AutoProcFileAttachment {{ presignedFileUrl(range: "{range}") {{ url }} }} ... on DataCollectionFileAttachment {{ presignedFileUrl(range: "{range}") {{ url }} }} }} }}"#
+                    ),
+                )
+                .await;
+            for attachment in data["attachments"].as_array().unwrap() {
+                assert!(attachment["fileUrl"].is_string(), "{range}");
+                assert!(attachment["presignedFileUrl"]["url"].is_string(), "{range}");
+            }
+        }
+
+        for range in ["bytes=1024-0", "bytes=0-1k", "0-1023"] {
+            let response = database
+                .try_execute(
+                    &schema,
+                    format!(
+                        r#"{{ attachments(dataCollectionId: 1, fileType: LOG) {{ id fileUrl(range: "{range}") }} }}"#
+                    ),
+                )
+                .await;
+            assert!(!response.errors.is_empty(), "{range}");
+            for error in response.errors {
+                assert_eq!(
+                    error.extensions.unwrap().get("code"),
+                    Some(&async_graphql::Value::from("BAD_USER_INPUT")),
+                    "{range}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn best_resolution_is_the_lowest_overall_limit_of_any_program() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id bestResolution } }"#,
+            )
+            .await;
+
+        let best_resolutions = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|data_collection| {
+                (
+                    data_collection["id"].as_u64().unwrap(),
+                    data_collection["bestResolution"].as_f64(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(best_resolutions.len(), 3);
+        assert!((best_resolutions[&1].unwrap() - 1.5).abs() < 1e-4);
+        assert!((best_resolutions[&2].unwrap() - 1.8).abs() < 1e-4);
+        assert_eq!(best_resolutions[&3], None);
+    }
+
+    #[tokio::test]
+    async fn auto_processing_is_filtered_by_every_combination_of_outcome() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") {
+                    id
+                    all: autoProcessing { autoProcProgramId }
+                    succeeded: autoProcessing(status: SUCCEEDED) { autoProcProgramId }
+                    failed: autoProcessing(status: FAILED) { autoProcProgramId }
+                    notFailed: autoProcessing(excludeFailed: true) { autoProcProgramId }
+                    succeededNotFailed: autoProcessing(status: SUCCEEDED, excludeFailed: true) { autoProcProgramId }
+                    failedNotFailed: autoProcessing(status: FAILED, excludeFailed: true) { autoProcProgramId }
+                } }"#,
+            )
+            .await;
+
+        let data_collection = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|data_collection| data_collection["id"] == 1)
+            .unwrap();
+        let filtered = |alias: &str| {
+            data_collection[alias].as_array().map(|auto_processings| {
+                let mut ids = auto_processings
+                    .iter()
+                    .map(|auto_processing| auto_processing["autoProcProgramId"].as_u64().unwrap())
+                    .collect::<Vec<_>>();
+                ids.sort();
+                ids
+            })
+        };
+        assert_eq!(filtered("all"), Some(vec![31, 32, 33]));
+        assert_eq!(filtered("succeeded"), Some(vec![31]));
+        assert_eq!(filtered("failed"), Some(vec![32]));
+        assert_eq!(filtered("notFailed"), Some(vec![31, 33]));
+        assert_eq!(filtered("succeededNotFailed"), Some(vec![31]));
+        assert_eq!(filtered("failedNotFailed"), None);
+    }
+
+    #[tokio::test]
+    async fn completeness_by_shell_is_null_for_missing_shells() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId completenessByShell { overall inner outer } } } }"#,
+            )
+            .await;
+
+        let completeness = auto_processing_field(&data, 1, "completenessByShell");
+        for (program, expected) in [
+            (31, [Some(99.5), Some(99.9), Some(95.0)]),
+            (32, [Some(80.0), Some(85.0), None]),
+        ] {
+            for (shell, expected) in ["overall", "inner", "outer"].into_iter().zip(expected) {
+                let actual = completeness[&program][shell].as_f64();
+                match (actual, expected) {
+                    (Some(actual), Some(expected)) => {
+                        assert!((actual - expected).abs() < 1e-4, "{program} {shell}")
+                    }
+                    (actual, expected) => assert_eq!(actual, expected, "{program} {shell}"),
+                }
+            }
+        }
+        assert_eq!(completeness[&33], serde_json::Value::Null);
+    }
+
+    /// A presigner which fails every presign, as if S3 were unavailable
+    struct UnavailablePresigner;
+
+    impl Presigner for UnavailablePresigner {
+        fn presign_get_object<'a>(
+            &'a self,
+            _bucket: &'a str,
+            _key: &'a str,
+            _version_id: Option<String>,
+            _content_type: Option<String>,
+            _content_disposition: Option<String>,
+            _range: Option<String>,
+            _expiry: PresignExpiry,
+        ) -> PresignFuture<'a> {
+            Box::pin(async { Err(async_graphql::Error::new("ServiceUnavailable")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn versions_are_not_linked_in_buckets_known_to_be_unversioned() {
+        let database = TestDatabase::start().await;
+        let query =
+            r#"{ attachments(dataCollectionId: 1, fileType: LOG) { fileUrl(versionId: "1") } }"#;
+
+        for bucket_versioned in [Some(true), None, Some(false)] {
+            let mut schema_builder = database
+                .schema_builder()
+                .data(ObjectKeyRewrite::default())
+                .data(DownloadableFileTypes::new([AttachmentFileType::Log]))
+                .data(DataLoader::new(
+                    PresignDataLoader::new(
+                        Arc::new(UnsignedObjectUrls::new(
+                            Url::parse("http://s3.test").unwrap(),
+                            true,
+                        )),
+                        "processed".parse().unwrap(),
+                        PresignExpiry::new(Duration::from_secs(60), Duration::ZERO),
+                        1,
+                        S3ConcurrencyLimit::default(),
+                    ),
+                    tokio::spawn,
+                ));
+            if let Some(bucket_versioned) = bucket_versioned {
+                schema_builder = schema_builder.data(BucketVersioned(bucket_versioned));
+            }
+
+            let response = database.try_execute(&schema_builder.finish(), query).await;
+
+            if bucket_versioned == Some(false) {
+                assert_eq!(response.errors.len(), 2);
+                for error in response.errors {
+                    assert_eq!(
+                        error.message,
+                        "Versions of objects cannot be linked as the bucket does not have versioning enabled"
+                    );
+                }
+            } else {
+                assert!(response.errors.is_empty(), "{:?}", response.errors);
+                let data = response.data.into_json().unwrap();
+                for attachment in data["attachments"].as_array().unwrap() {
+                    assert!(attachment["fileUrl"]
+                        .as_str()
+                        .unwrap()
+                        .ends_with("?versionId=1"));
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn unavailable_s3_nulls_only_the_download_links() {
+        let database = TestDatabase::start().await;
+        let query =
+            r#"{ attachments(dataCollectionId: 1, fileType: LOG) { id fileName fileUrl } }"#;
+
+        for s3_soft_fail in [true, false] {
+            let schema = database
+                .schema_builder()
+                .data(ObjectKeyRewrite::default())
+                .data(DownloadableFileTypes::new([AttachmentFileType::Log]))
+                .data(S3SoftFail(s3_soft_fail))
+                .data(DataLoader::new(
+                    PresignDataLoader::new(
+                        Arc::new(UnavailablePresigner),
+                        "processed".parse().unwrap(),
+                        PresignExpiry::new(Duration::from_secs(60), Duration::ZERO),
+                        1,
+                        S3ConcurrencyLimit::default(),
+                    ),
+                    tokio::spawn,
+                ))
+                .finish();
+
+            let response = database.try_execute(&schema, query).await;
+
+            let data = response.data.into_json().unwrap();
+            let attachments = data["attachments"].as_array().unwrap();
+            assert_eq!(attachments.len(), 2);
+            for attachment in attachments {
+                assert!(attachment["id"].is_u64());
+                assert!(attachment["fileName"].is_string());
+                assert_eq!(attachment["fileUrl"], serde_json::Value::Null);
+            }
+            assert_eq!(response.errors.len(), 2);
+            for error in response.errors {
+                let code = error
+                    .extensions
+                    .and_then(|extensions| extensions.get("code").cloned());
+                assert_eq!(
+                    code == Some(async_graphql::Value::from("S3_UNAVAILABLE")),
+                    s3_soft_fail,
+                    "{}",
+                    error.message
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn attachments_by_ids_preserve_request_order_and_omit_missing_ids() {
+        let database = TestDatabase::start().await;
+        let schema = presigning_schema(&database, [AttachmentFileType::Log]);
+
+        let data = database
+            .execute(
+                &schema,
+                "{ attachmentsByIds(ids: [84, 999, 81, 91, 82, 84]) { id fileName } }",
+            )
+            .await;
+        let ids = data["attachmentsByIds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|attachment| attachment["id"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![84, 81, 82, 84]);
+
+        let data = database
+            .execute(&schema, "{ attachmentsByIds(ids: [81]) { id fileUrl } }")
+            .await;
+        assert!(data["attachmentsByIds"][0]["fileUrl"].is_string());
+
+        let data = database
+            .execute(&schema, "{ attachmentsByIds(ids: []) { id } }")
+            .await;
+        assert_eq!(data["attachmentsByIds"], serde_json::json!([]));
+
+        let too_many = (1..=101).map(|id| id.to_string()).collect::<Vec<_>>();
+        let response = database
+            .try_execute(
+                &schema,
+                format!(
+                    "{{ attachmentsByIds(ids: [{}]) {{ id }} }}",
+                    too_many.join(", ")
+                ),
+            )
+            .await;
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(
+            response.errors[0].extensions.as_ref().unwrap().get("code"),
+            Some(&async_graphql::Value::from("BAD_USER_INPUT"))
+        );
+    }
+
+    #[tokio::test]
+    async fn anomalous_significance_uses_the_configured_threshold() {
+        let database = TestDatabase::start().await;
+        let query = "{ scalingStatistics(autoProcScalingId: 61) { autoProcScalingStatisticsId anomalousSignificant } }";
+
+        for (threshold, expected) in [
+            (None, [(71, true), (72, true), (73, false)]),
+            (Some(35.0), [(71, false), (72, true), (73, false)]),
+        ] {
+            let mut schema = database.schema_builder();
+            if let Some(threshold) = threshold {
+                schema = schema.data(AnomalousSignificanceThreshold(threshold));
+            }
+            let data = database.execute(&schema.finish(), query).await;
+
+            let significant = data["scalingStatistics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|statistics| {
+                    (
+                        statistics["autoProcScalingStatisticsId"].as_u64().unwrap(),
+                        statistics["anomalousSignificant"].as_bool().unwrap(),
+                    )
+                })
+                .collect::<HashMap<_, _>>();
+            assert_eq!(significant, HashMap::from(expected), "{threshold:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn database_info_reports_the_backend_and_version() {
+        let database = sea_orm::MockDatabase::new(DbBackend::MySql)
+            .append_query_results([[std::collections::BTreeMap::from([(
+                "version".to_string(),
+                sea_orm::Value::from("8.0.36"),
+            )])]])
+            .into_connection();
+        let schema = root_schema_builder(true).data(database).finish();
+
+        let response = schema
+            .execute(
+                Request::new("{ databaseInfo { backend version } }")
+                    .data(GrantedScopes::from_header(ADMIN_SCOPE)),
+            )
+            .await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "databaseInfo": { "backend": "MY_SQL", "version": "8.0.36" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn processing_status_counts_group_runs_by_outcome() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id processingStatusCounts { status count } } }"#,
+            )
+            .await;
+
+        let counts = data["dataCollectionsForVisit"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|data_collection| {
+                (
+                    data_collection["id"].as_u64().unwrap(),
+                    data_collection["processingStatusCounts"].clone(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            counts[&1],
+            serde_json::json!([
+                { "status": "SUCCEEDED", "count": 1 },
+                { "status": "FAILED", "count": 1 },
+                { "status": null, "count": 1 },
+            ])
+        );
+        assert_eq!(
+            counts[&2],
+            serde_json::json!([{ "status": "SUCCEEDED", "count": 1 }])
+        );
+        assert_eq!(counts[&3], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn downloads_are_served_with_the_disposition_of_their_type_unless_overridden() {
+        let database = TestDatabase::start().await;
+        let s3_client = Client::from_conf(
+            aws_sdk_s3::config::Builder::new()
+                .credentials_provider(Credentials::new("key", "secret", None, None, "test"))
+                .endpoint_url("http://s3.test")
+                .force_path_style(true)
+                .region(Region::new("us-east-1"))
+                .build(),
+        );
+        let schema = presigning_schema_with(
+            &database,
+            [AttachmentFileType::Log, AttachmentFileType::Graph],
+            Arc::new(s3_client),
+        );
+        let data = database
+            .execute(
+                &schema,
+                r#"{
+                    default: attachmentsByIds(ids: [81, 84]) { id fileUrl }
+                    inline: attachmentsByIds(ids: [81, 84]) { id fileUrl(disposition: INLINE) }
+                    attachment: attachmentsByIds(ids: [81, 84]) { id fileUrl(disposition: ATTACHMENT) }
+                }"#,
+            )
+            .await;
+
+        let dispositions = |alias: &str| {
+            data[alias]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|attachment| {
+                    let url = Url::parse(attachment["fileUrl"].as_str().unwrap()).unwrap();
+                    url.query_pairs()
+                        .find(|(name, _)| name == "response-content-disposition")
+                        .map(|(_, disposition)| disposition.into_owned())
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            dispositions("default"),
+            vec![
+                r#"attachment; filename="xia2.txt""#,
+                r#"inline; filename="fast_dp.png""#
+            ]
+        );
+        assert_eq!(
+            dispositions("inline"),
+            vec![
+                r#"inline; filename="xia2.txt""#,
+                r#"inline; filename="fast_dp.png""#
+            ]
+        );
+        assert_eq!(
+            dispositions("attachment"),
+            vec![
+                r#"attachment; filename="xia2.txt""#,
+                r#"attachment; filename="fast_dp.png""#
+            ]
+        );
+    }
+
+    #[test]
+    fn expiry_is_accepted_only_within_the_bounds() {
+        let bounds = PresignExpiryBounds {
+            min: Duration::from_secs(60),
+            max: Duration::from_secs(7 * 24 * 60 * 60),
+        };
+
+        for expiry_seconds in [60, 3600, 7 * 24 * 60 * 60] {
+            assert_eq!(
+                bounds.validate(expiry_seconds).unwrap(),
+                Duration::from_secs(expiry_seconds)
+            );
+        }
+        for expiry_seconds in [0, 59, 7 * 24 * 60 * 60 + 1, u64::MAX] {
+            assert_eq!(
+                bounds
+                    .validate(expiry_seconds)
+                    .unwrap_err()
+                    .extensions
+                    .unwrap()
+                    .get("code"),
+                Some(&async_graphql::Value::from("INVALID_EXPIRY")),
+                "{expiry_seconds}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn presigned_links_expire_after_the_requested_period() {
+        let database = TestDatabase::start().await;
+        let schema = presigning_schema(&database, [AttachmentFileType::Log]);
+        let query = |expiry_seconds: u64| {
+            format!(
+                "{{ attachmentsByIds(ids: [81]) {{ presignedFileUrl(expirySeconds: {expiry_seconds}) {{ expiresAt }} }} }}"
+            )
+        };
+
+        for expiry_seconds in [60, 3600, 7 * 24 * 60 * 60] {
+            let before = chrono::Utc::now();
+            let data = database.execute(&schema, query(expiry_seconds)).await;
+            let expires_at = chrono::DateTime::parse_from_rfc3339(
+                data["attachmentsByIds"][0]["presignedFileUrl"]["expiresAt"]
+                    .as_str()
+                    .unwrap(),
+            )
+            .unwrap();
+            let expiry = chrono::Duration::seconds(expiry_seconds as i64);
+            assert!(expires_at >= before + expiry - chrono::Duration::seconds(1));
+            assert!(expires_at <= chrono::Utc::now() + expiry);
+        }
+
+        for expiry_seconds in [59, 7 * 24 * 60 * 60 + 1] {
+            let response = database.try_execute(&schema, query(expiry_seconds)).await;
+            assert_eq!(response.errors.len(), 1, "{expiry_seconds}");
+            assert_eq!(
+                response.errors[0].extensions.as_ref().unwrap().get("code"),
+                Some(&async_graphql::Value::from("INVALID_EXPIRY"))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn refined_beam_coordinates_are_read_from_their_own_columns() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId refinedXBeam refinedYBeam } } }"#,
+            )
+            .await;
+
+        let x = auto_processing_field(&data, 1, "refinedXBeam");
+        let y = auto_processing_field(&data, 1, "refinedYBeam");
+        assert_eq!(x[&31], serde_json::json!(210.5));
+        assert_eq!(y[&31], serde_json::json!(220.5));
+    }
+
+    #[tokio::test]
+    async fn deprecated_shells_are_keyed_on_the_scaling() {
+        let database = TestDatabase::start().await;
+        let schema = database.schema_builder().finish();
+
+        let data = database
+            .execute(
+                &schema,
+                r#"{ dataCollectionsForVisit(visit: "cm12345-1") { id autoProcessing { autoProcProgramId overall { autoProcScalingStatisticsId } innerShell { autoProcScalingStatisticsId } outerShell { autoProcScalingStatisticsId } } } }"#,
+            )
+            .await;
+
+        let overall = auto_processing_field(&data, 1, "overall");
+        let inner_shell = auto_processing_field(&data, 1, "innerShell");
+        let outer_shell = auto_processing_field(&data, 1, "outerShell");
+        assert_eq!(
+            overall[&31],
+            serde_json::json!({ "autoProcScalingStatisticsId": 71 })
+        );
+        assert_eq!(
+            inner_shell[&31],
+            serde_json::json!({ "autoProcScalingStatisticsId": 72 })
+        );
+        assert_eq!(
+            outer_shell[&31],
+            serde_json::json!({ "autoProcScalingStatisticsId": 73 })
+        );
+        assert_eq!(
+            overall[&32],
+            serde_json::json!({ "autoProcScalingStatisticsId": 74 })
+        );
+        assert_eq!(outer_shell[&32], serde_json::Value::Null);
+        assert_eq!(overall[&33], serde_json::Value::Null);
     }
 }
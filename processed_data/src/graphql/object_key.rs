@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+/// A rewrite applied to file system paths to produce the corresponding S3 object keys
+#[derive(Debug, Clone, Default)]
+pub struct ObjectKeyRewrite {
+    /// A prefix removed from the start of the path, if present
+    strip_prefix: Option<String>,
+    /// A prefix added to the start of the path, after any prefix has been removed
+    add_prefix: Option<String>,
+}
+
+impl ObjectKeyRewrite {
+    /// Creates a rewrite which strips and then adds the supplied prefixes
+    pub fn new(strip_prefix: Option<String>, add_prefix: Option<String>) -> Self {
+        Self {
+            strip_prefix,
+            add_prefix,
+        }
+    }
+
+    /// Produces the S3 object key of the file at `file_name` within `file_path`
+    pub fn to_object_key(&self, file_path: &str, file_name: &str) -> String {
+        let mut path = PathBuf::from(file_path);
+        path.push(file_name);
+        let path = path.to_string_lossy();
+        let stripped = match &self.strip_prefix {
+            Some(prefix) => path.strip_prefix(prefix.as_str()).unwrap_or(&path),
+            None => &path,
+        };
+        match &self.add_prefix {
+            Some(prefix) => format!("{prefix}{stripped}"),
+            None => stripped.to_string(),
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use super::{s3_concurrency::S3ConcurrencyLimit, scalars::Timestamp};
+use crate::S3Bucket;
+use async_graphql::dataloader::Loader;
+use aws_sdk_s3::{operation::head_object::HeadObjectOutput, Client};
+use chrono::{TimeZone, Utc};
+use futures_util::{stream, StreamExt};
+use std::collections::HashMap;
+use tracing::{instrument, warn};
+
+/// The metadata of an object in the bucket, as reported by `HeadObject`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectMetadata {
+    /// The size of the object, in bytes
+    pub size_bytes: i64,
+    /// The time at which the object was last modified
+    pub last_modified: Option<Timestamp>,
+}
+
+impl From<HeadObjectOutput> for ObjectMetadata {
+    fn from(output: HeadObjectOutput) -> Self {
+        Self {
+            size_bytes: output.content_length(),
+            last_modified: output.last_modified().and_then(|last_modified| {
+                Utc.timestamp_opt(last_modified.secs(), last_modified.subsec_nanos())
+                    .single()
+                    .map(Timestamp::from)
+            }),
+        }
+    }
+}
+
+/// DataLoader which fetches the metadata of many objects concurrently, with at most `concurrency` requests in flight, keyed on the object key
+///
+/// Objects which do not exist resolve to [`None`], whilst objects whose metadata could not be fetched are absent, such
+/// that a failure for one object does not fail the batch
+pub struct ObjectMetadataDataLoader {
+    /// The client with which the metadata is fetched
+    s3_client: Client,
+    /// The bucket in which the objects are stored
+    bucket: S3Bucket,
+    /// The maximum number of requests in flight at once from this loader
+    concurrency: usize,
+    /// The limit on S3 operations in flight across the process
+    limit: S3ConcurrencyLimit,
+}
+
+impl ObjectMetadataDataLoader {
+    /// Creates a loader fetching the metadata of objects in `bucket`, at most `concurrency` at a time and within the process-wide `limit`
+    pub fn new(
+        s3_client: Client,
+        bucket: S3Bucket,
+        concurrency: usize,
+        limit: S3ConcurrencyLimit,
+    ) -> Self {
+        Self {
+            s3_client,
+            bucket,
+            concurrency: concurrency.max(1),
+            limit,
+        }
+    }
+
+    /// Fetches the metadata of the object, returning [`None`] if it does not exist
+    async fn head(&self, object_key: &str) -> Result<Option<ObjectMetadata>, ()> {
+        match self
+            .limit
+            .run(
+                self.s3_client
+                    .head_object()
+                    .bucket(&*self.bucket)
+                    .key(object_key)
+                    .send(),
+            )
+            .await
+        {
+            Ok(output) => Ok(Some(output.into())),
+            Err(err) if err.as_service_error().is_some_and(|err| err.is_not_found()) => Ok(None),
+            Err(err) => {
+                warn!("Failed to fetch metadata of {object_key}: {err}");
+                Err(())
+            }
+        }
+    }
+}
+
+impl Loader<String> for ObjectMetadataDataLoader {
+    type Value = Option<ObjectMetadata>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_object_metadata", skip(self))]
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Self::Value>, Self::Error> {
+        Ok(stream::iter(keys)
+            .map(|key| async move { (key.clone(), self.head(key).await) })
+            .buffer_unordered(self.concurrency)
+            .filter_map(
+                |(key, metadata)| async move { metadata.ok().map(|metadata| (key, metadata)) },
+            )
+            .collect()
+            .await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_credential_types::Credentials;
+    use aws_sdk_s3::config::Region;
+    use axum::{
+        extract::Path,
+        http::{
+            header::{CONTENT_LENGTH, LAST_MODIFIED},
+            StatusCode,
+        },
+        response::IntoResponse,
+        routing::head,
+        Router,
+    };
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+
+    /// A loader of a local mock S3 endpoint, in which `exists.txt` is present, `missing.txt` is absent and requests for
+    /// `forbidden.txt` are refused
+    async fn loader() -> ObjectMetadataDataLoader {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let socket_addr = listener.local_addr().unwrap();
+        let router = Router::new().route(
+            "/:bucket/*key",
+            head(|Path((_, key)): Path<(String, String)>| async move {
+                match key.as_str() {
+                    "cm12345-1/exists.txt" => (
+                        StatusCode::OK,
+                        [
+                            (CONTENT_LENGTH, "1024"),
+                            (LAST_MODIFIED, "Wed, 21 Oct 2015 07:28:00 GMT"),
+                        ],
+                    )
+                        .into_response(),
+                    "cm12345-1/forbidden.txt" => StatusCode::FORBIDDEN.into_response(),
+                    _ => StatusCode::NOT_FOUND.into_response(),
+                }
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        ObjectMetadataDataLoader::new(
+            Client::from_conf(
+                aws_sdk_s3::config::Builder::new()
+                    .credentials_provider(Credentials::new("key", "secret", None, None, "test"))
+                    .endpoint_url(format!("http://{socket_addr}"))
+                    .force_path_style(true)
+                    .region(Region::new("us-east-1"))
+                    .build(),
+            ),
+            "processed".parse().unwrap(),
+            4,
+            S3ConcurrencyLimit::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn metadata_is_fetched_for_existing_and_missing_objects() {
+        let keys = [
+            "cm12345-1/exists.txt",
+            "cm12345-1/missing.txt",
+            "cm12345-1/forbidden.txt",
+        ]
+        .map(str::to_string);
+
+        let results = loader().await.load(&keys).await.unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[&keys[0]],
+            Some(ObjectMetadata {
+                size_bytes: 1024,
+                last_modified: Utc
+                    .timestamp_opt(1_445_412_480, 0)
+                    .single()
+                    .map(Timestamp::from),
+            })
+        );
+        assert_eq!(results[&keys[1]], None);
+        assert!(!results.contains_key(&keys[2]));
+    }
+}
@@ -0,0 +1,145 @@
+use async_graphql::{Context, ErrorExtensions};
+
+/// The largest number of items which may be requested in a single page of a paginated field
+#[derive(Debug, Clone, Copy)]
+pub struct MaxPageSize(pub u64);
+
+impl Default for MaxPageSize {
+    fn default() -> Self {
+        Self(1000)
+    }
+}
+
+/// The validated `limit` and `offset` arguments of a paginated field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// The maximum number of items returned, or all remaining items if omitted
+    pub limit: Option<u64>,
+    /// The number of items skipped before the first returned
+    pub offset: u64,
+}
+
+impl Page {
+    /// Validates the arguments against the [`MaxPageSize`] in the schema data, or the default if absent
+    pub fn from_arguments(
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        offset: i64,
+    ) -> async_graphql::Result<Self> {
+        let max_page_size = ctx.data_opt::<MaxPageSize>().copied().unwrap_or_default();
+        Self::validate(limit, offset, max_page_size)
+    }
+
+    /// Returns the page if the offset is not negative and the limit, if present, is positive and at most the maximum
+    /// page size, or an error with the `INVALID_PAGINATION` code if not
+    fn validate(
+        limit: Option<i64>,
+        offset: i64,
+        max_page_size: MaxPageSize,
+    ) -> async_graphql::Result<Self> {
+        let offset = u64::try_from(offset).map_err(|_| {
+            invalid_pagination(format!("Offset must not be negative, got {offset}"))
+        })?;
+        let limit = limit
+            .map(|limit| match u64::try_from(limit) {
+                Ok(limit) if limit > 0 && limit <= max_page_size.0 => Ok(limit),
+                _ => Err(invalid_pagination(format!(
+                    "Limit must be between 1 and {}, got {limit}",
+                    max_page_size.0
+                ))),
+            })
+            .transpose()?;
+        Ok(Self { limit, offset })
+    }
+
+    /// The number of items to skip, saturating on platforms where it does not fit
+    pub fn skip(&self) -> usize {
+        usize::try_from(self.offset).unwrap_or(usize::MAX)
+    }
+
+    /// The number of items to take after skipping, saturating on platforms where it does not fit
+    pub fn take(&self) -> usize {
+        self.limit.map_or(usize::MAX, |limit| {
+            usize::try_from(limit).unwrap_or(usize::MAX)
+        })
+    }
+
+    /// The number of leading items from which the page is drawn, or [`None`] if unlimited
+    pub fn end(&self) -> Option<u64> {
+        self.limit.map(|limit| limit.saturating_add(self.offset))
+    }
+}
+
+/// An error with the `INVALID_PAGINATION` code
+fn invalid_pagination(message: String) -> async_graphql::Error {
+    async_graphql::Error::new(message)
+        .extend_with(|_, extensions| extensions.set("code", "INVALID_PAGINATION"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The code in the extensions of the error
+    fn code(error: async_graphql::Error) -> Option<async_graphql::Value> {
+        error
+            .extensions
+            .and_then(|extensions| extensions.get("code").cloned())
+    }
+
+    #[test]
+    fn negative_offset_is_invalid() {
+        let error = Page::validate(Some(10), -1, MaxPageSize(100)).unwrap_err();
+        assert_eq!(code(error), Some("INVALID_PAGINATION".into()));
+    }
+
+    #[test]
+    fn zero_limit_is_invalid() {
+        let error = Page::validate(Some(0), 0, MaxPageSize(100)).unwrap_err();
+        assert_eq!(code(error), Some("INVALID_PAGINATION".into()));
+    }
+
+    #[test]
+    fn negative_limit_is_invalid() {
+        let error = Page::validate(Some(-5), 0, MaxPageSize(100)).unwrap_err();
+        assert_eq!(code(error), Some("INVALID_PAGINATION".into()));
+    }
+
+    #[test]
+    fn limit_above_the_maximum_is_invalid() {
+        let error = Page::validate(Some(101), 0, MaxPageSize(100)).unwrap_err();
+        assert_eq!(code(error), Some("INVALID_PAGINATION".into()));
+    }
+
+    #[test]
+    fn limits_at_the_bounds_are_valid() {
+        assert_eq!(
+            Page::validate(Some(1), 0, MaxPageSize(100)).unwrap(),
+            Page {
+                limit: Some(1),
+                offset: 0
+            }
+        );
+        assert_eq!(
+            Page::validate(Some(100), 0, MaxPageSize(100)).unwrap(),
+            Page {
+                limit: Some(100),
+                offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn omitted_limit_takes_all_items_after_the_offset() {
+        let page = Page::validate(None, 5, MaxPageSize(100)).unwrap();
+        assert_eq!(page.skip(), 5);
+        assert_eq!(page.take(), usize::MAX);
+        assert_eq!(page.end(), None);
+    }
+
+    #[test]
+    fn end_covers_the_offset_and_limit() {
+        let page = Page::validate(Some(10), 20, MaxPageSize(100)).unwrap();
+        assert_eq!(page.end(), Some(30));
+    }
+}
@@ -0,0 +1,63 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ErrorExtensions, Pos, ServerResult, Value,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The fields which presign a URL, as `(type, field)`
+const PRESIGNING_FIELDS: &[(&str, &str)] = &[
+    ("AutoProcFileAttachment", "fileUrl"),
+    ("AutoProcFileAttachment", "presignedFileUrl"),
+    ("DataCollectionFileAttachment", "fileUrl"),
+    ("DataCollectionFileAttachment", "presignedFileUrl"),
+];
+
+/// The maximum number of URLs which may be presigned in a single request
+#[derive(Debug, Clone, Copy)]
+pub struct MaxPresignsPerRequest(pub usize);
+
+/// An [`ExtensionFactory`] which rejects the resolution of presigning fields once the [`MaxPresignsPerRequest`] in the schema data has been reached
+#[derive(Debug, Default)]
+pub struct PresignLimit;
+
+impl ExtensionFactory for PresignLimit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PresignLimitExtension::default())
+    }
+}
+
+/// The per-request [`Extension`] created by [`PresignLimit`]
+#[derive(Debug, Default)]
+struct PresignLimitExtension {
+    /// The number of presigning fields resolved so far
+    presigns: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl Extension for PresignLimitExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let presigning = PRESIGNING_FIELDS
+            .iter()
+            .any(|(parent_type, name)| *parent_type == info.parent_type && *name == info.name);
+        if let (true, Some(MaxPresignsPerRequest(max_presigns))) =
+            (presigning, ctx.data_opt::<MaxPresignsPerRequest>())
+        {
+            if self.presigns.fetch_add(1, Ordering::Relaxed) >= *max_presigns {
+                return Err(async_graphql::Error::new(format!(
+                    "No more than {max_presigns} URLs may be presigned in a single request"
+                ))
+                .extend_with(|_, extensions| extensions.set("code", "PRESIGN_LIMIT_EXCEEDED"))
+                .into_server_error(Pos::default()));
+            }
+        }
+        next.run(ctx, info).await
+    }
+}
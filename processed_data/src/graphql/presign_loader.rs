@@ -0,0 +1,296 @@
+use super::{byte_range::ByteRange, entities::PresignedUrl, s3_concurrency::S3ConcurrencyLimit};
+use crate::{
+    presigner::{PresignExpiry, SharedPresigner},
+    S3Bucket,
+};
+use async_graphql::dataloader::Loader;
+use chrono::Utc;
+use futures_util::{stream, StreamExt};
+use std::{collections::HashMap, time::Duration};
+use tracing::instrument;
+
+/// An object to be presigned, along with the parameters of the link
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PresignKey {
+    /// The key of the object in the bucket
+    pub object_key: String,
+    /// The version of the object, or the latest version if omitted
+    pub version_id: Option<String>,
+    /// The content type with which the object is served
+    pub content_type: Option<String>,
+    /// The content disposition with which the object is served
+    pub content_disposition: Option<String>,
+    /// The range of bytes to which the link is restricted, or the whole object if omitted
+    pub range: Option<ByteRange>,
+    /// The period for which the link remains valid, or the configured expiry if omitted
+    pub expiry: Option<Duration>,
+}
+
+/// DataLoader which presigns the links of many objects concurrently, with at most `concurrency` presigns in flight
+///
+/// Each key resolves to its own result, such that a failure to presign one object does not fail the batch
+pub struct PresignDataLoader {
+    /// The producer of links to objects in the bucket
+    presigner: SharedPresigner,
+    /// The bucket in which the objects are stored
+    bucket: S3Bucket,
+    /// The period for which links remain valid
+    expiry: PresignExpiry,
+    /// The maximum number of objects presigned at once by this loader
+    concurrency: usize,
+    /// The limit on S3 operations in flight across the process
+    limit: S3ConcurrencyLimit,
+}
+
+impl PresignDataLoader {
+    /// Creates a loader presigning links to objects in `bucket`, at most `concurrency` at a time and within the process-wide `limit`
+    pub fn new(
+        presigner: SharedPresigner,
+        bucket: S3Bucket,
+        expiry: PresignExpiry,
+        concurrency: usize,
+        limit: S3ConcurrencyLimit,
+    ) -> Self {
+        Self {
+            presigner,
+            bucket,
+            expiry,
+            concurrency: concurrency.max(1),
+            limit,
+        }
+    }
+
+    /// Presigns a link to the object, returning it alongside the time at which it expires
+    async fn presign(&self, key: &PresignKey) -> async_graphql::Result<PresignedUrl> {
+        let expiry = key
+            .expiry
+            .map_or(self.expiry, |expiry| self.expiry.with_expiry(expiry));
+        let expires_at = Utc::now() + chrono::Duration::from_std(expiry.expiry())?;
+        let object_url = self
+            .limit
+            .run(self.presigner.presign_get_object(
+                &self.bucket,
+                &key.object_key,
+                key.version_id.clone(),
+                key.content_type.clone(),
+                key.content_disposition.clone(),
+                key.range.map(|range| range.to_string()),
+                expiry,
+            ))
+            .await?;
+        Ok(PresignedUrl {
+            url: object_url.to_string(),
+            expires_at: expires_at.into(),
+        })
+    }
+}
+
+impl Loader<PresignKey> for PresignDataLoader {
+    type Value = Result<PresignedUrl, async_graphql::Error>;
+    type Error = async_graphql::Error;
+
+    #[instrument(name = "load_presigned_urls", skip(self))]
+    async fn load(
+        &self,
+        keys: &[PresignKey],
+    ) -> Result<HashMap<PresignKey, Self::Value>, Self::Error> {
+        Ok(stream::iter(keys)
+            .map(|key| async move { (key.clone(), self.presign(key).await) })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presigner::{PresignFuture, Presigner};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+    use url::Url;
+
+    /// A presigner of a bucket holding only the listed objects, which records the expiry of each link and the number of
+    /// presigns in flight at once
+    struct MockPresigner {
+        /// The keys of the objects in the bucket
+        objects: Vec<&'static str>,
+        /// The expiry with which each link was requested
+        expiries: Mutex<Vec<Duration>>,
+        /// The number of presigns in flight
+        in_flight: AtomicUsize,
+        /// The greatest number of presigns which were in flight at once
+        max_in_flight: AtomicUsize,
+    }
+
+    impl MockPresigner {
+        /// Creates a presigner of a bucket holding the objects
+        fn new(objects: Vec<&'static str>) -> Arc<Self> {
+            Arc::new(Self {
+                objects,
+                expiries: Mutex::default(),
+                in_flight: AtomicUsize::new(0),
+                max_in_flight: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl Presigner for MockPresigner {
+        fn presign_get_object<'a>(
+            &'a self,
+            bucket: &'a str,
+            key: &'a str,
+            _version_id: Option<String>,
+            _content_type: Option<String>,
+            _content_disposition: Option<String>,
+            _range: Option<String>,
+            expiry: PresignExpiry,
+        ) -> PresignFuture<'a> {
+            Box::pin(async move {
+                self.expiries.lock().unwrap().push(expiry.expiry());
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                if !self.objects.contains(&key) {
+                    return Err(async_graphql::Error::new(format!(
+                        "NoSuchKey: {bucket}/{key}"
+                    )));
+                }
+                Ok(Url::parse(&format!(
+                    "https://s3.test/{bucket}/{key}?X-Amz-Expires={}",
+                    expiry.expiry().as_secs()
+                ))?)
+            })
+        }
+    }
+
+    /// A loader over the presigner, with links valid for a minute by default
+    fn loader(presigner: Arc<MockPresigner>) -> PresignDataLoader {
+        PresignDataLoader::new(
+            presigner,
+            "processed".parse().unwrap(),
+            PresignExpiry::new(Duration::from_secs(60), Duration::ZERO),
+            4,
+            S3ConcurrencyLimit::default(),
+        )
+    }
+
+    /// A key presigning the whole of the latest version of the object
+    fn key(object_key: &str, expiry: Option<Duration>) -> PresignKey {
+        PresignKey {
+            object_key: object_key.to_string(),
+            version_id: None,
+            content_type: None,
+            content_disposition: None,
+            range: None,
+            expiry,
+        }
+    }
+
+    #[tokio::test]
+    async fn presigned_url_addresses_the_object_in_the_bucket() {
+        let key = key("cm12345-1/xia2.txt", None);
+        let before = Utc::now();
+
+        let results = loader(MockPresigner::new(vec!["cm12345-1/xia2.txt"]))
+            .load(&[key.clone()])
+            .await
+            .unwrap();
+
+        let presigned = results[&key].as_ref().unwrap();
+        assert_eq!(
+            presigned.url,
+            "https://s3.test/processed/cm12345-1/xia2.txt?X-Amz-Expires=60"
+        );
+        assert!(*presigned.expires_at >= before + chrono::Duration::seconds(60));
+        assert!(*presigned.expires_at <= Utc::now() + chrono::Duration::seconds(60));
+    }
+
+    #[tokio::test]
+    async fn requested_expiry_is_passed_to_the_presigner() {
+        let presigner = MockPresigner::new(vec!["xia2.txt"]);
+        let key = key("xia2.txt", Some(Duration::from_secs(3600)));
+
+        let results = loader(presigner.clone())
+            .load(&[key.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *presigner.expiries.lock().unwrap(),
+            vec![Duration::from_secs(3600)]
+        );
+        assert!(results[&key]
+            .as_ref()
+            .unwrap()
+            .url
+            .ends_with("X-Amz-Expires=3600"));
+    }
+
+    #[tokio::test]
+    async fn missing_object_resolves_to_an_error() {
+        let key = key("missing.txt", None);
+
+        let results = loader(MockPresigner::new(Vec::new()))
+            .load(&[key.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            results[&key].as_ref().unwrap_err().message,
+            "NoSuchKey: processed/missing.txt"
+        );
+    }
+
+    #[tokio::test]
+    async fn objects_are_presigned_concurrently_up_to_the_limit() {
+        let presigner = MockPresigner::new(Vec::new());
+        let keys = (0..10)
+            .map(|index| key(&format!("{index}.txt"), None))
+            .collect::<Vec<_>>();
+
+        let results = loader(presigner.clone()).load(&keys).await.unwrap();
+
+        assert_eq!(results.len(), 10);
+        assert_eq!(presigner.max_in_flight.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn failure_to_presign_one_object_does_not_fail_the_others() {
+        let keys = [
+            "xia2.txt",
+            "missing.txt",
+            "xia2.mtz",
+            "absent.png",
+            "fast_dp.png",
+        ]
+        .map(|object_key| key(object_key, None));
+
+        let results = loader(MockPresigner::new(vec![
+            "xia2.txt",
+            "xia2.mtz",
+            "fast_dp.png",
+        ]))
+        .load(&keys)
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 5);
+        for key in &keys {
+            match key.object_key.as_str() {
+                "missing.txt" | "absent.png" => assert_eq!(
+                    results[key].as_ref().unwrap_err().message,
+                    format!("NoSuchKey: processed/{}", key.object_key)
+                ),
+                object_key => assert!(results[key]
+                    .as_ref()
+                    .unwrap()
+                    .url
+                    .starts_with(&format!("https://s3.test/processed/{object_key}?"))),
+            }
+        }
+    }
+}
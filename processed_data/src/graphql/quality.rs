@@ -0,0 +1,358 @@
+use super::entities::AutoProcScalingStatics;
+use async_graphql::Enum;
+
+/// A rough grade of the quality of processed data, as assigned by beamline staff
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QualityGrade {
+    /// Meets the thresholds of grade A
+    A,
+    /// Meets the thresholds of grade B, but not those of grade A
+    B,
+    /// Meets neither the thresholds of grade A nor grade B
+    C,
+}
+
+/// The statistics which must be met, in the overall shell, to be awarded a grade
+#[derive(Debug, Clone, Copy)]
+pub struct GradeThresholds {
+    /// The minimum completeness, as a percentage
+    pub min_completeness: f32,
+    /// The maximum high resolution limit, in Angstroms
+    pub max_resolution: f32,
+    /// The minimum CC(1/2), as a percentage
+    pub min_cc_half: f32,
+}
+
+impl GradeThresholds {
+    /// Returns true if the completeness, resolution and CC(1/2) all meet the thresholds
+    fn are_met(&self, completeness: f32, resolution: f32, cc_half: f32) -> bool {
+        completeness >= self.min_completeness
+            && resolution <= self.max_resolution
+            && cc_half >= self.min_cc_half
+    }
+}
+
+/// The thresholds by which a [`QualityGrade`] is assigned
+#[derive(Debug, Clone, Copy)]
+pub struct QualityGradeThresholds {
+    /// The thresholds of grade A
+    pub grade_a: GradeThresholds,
+    /// The thresholds of grade B
+    pub grade_b: GradeThresholds,
+}
+
+impl Default for QualityGradeThresholds {
+    fn default() -> Self {
+        Self {
+            grade_a: GradeThresholds {
+                min_completeness: 95.0,
+                max_resolution: 2.0,
+                min_cc_half: 90.0,
+            },
+            grade_b: GradeThresholds {
+                min_completeness: 85.0,
+                max_resolution: 3.0,
+                min_cc_half: 50.0,
+            },
+        }
+    }
+}
+
+impl QualityGradeThresholds {
+    /// Grades the overall statistics, or returns [`None`] if the completeness, resolution or CC(1/2) is absent
+    pub fn grade(&self, overall: &AutoProcScalingStatics) -> Option<QualityGrade> {
+        let completeness = overall.completeness?;
+        let resolution = overall.resolution_limit_high?;
+        let cc_half = overall.cc_half?;
+        if self.grade_a.are_met(completeness, resolution, cc_half) {
+            Some(QualityGrade::A)
+        } else if self.grade_b.are_met(completeness, resolution, cc_half) {
+            Some(QualityGrade::B)
+        } else {
+            Some(QualityGrade::C)
+        }
+    }
+}
+
+/// The anomalous CC, as a percentage, above which the anomalous signal is deemed significant
+#[derive(Debug, Clone, Copy)]
+pub struct AnomalousSignificanceThreshold(pub f32);
+
+impl Default for AnomalousSignificanceThreshold {
+    fn default() -> Self {
+        Self(15.0)
+    }
+}
+
+impl AnomalousSignificanceThreshold {
+    /// Returns true if the anomalous CC exceeds the threshold, or [`None`] if the anomalous CC is absent
+    pub fn is_significant(&self, statistics: &AutoProcScalingStatics) -> Option<bool> {
+        statistics
+            .cc_anomalous
+            .map(|cc_anomalous| cc_anomalous > self.0)
+    }
+}
+
+/// The exponent by which the high resolution limit is penalised for incompleteness in the effective resolution
+///
+/// The number of reflections within a resolution limit `d` grows as `1/d^3`, so data of completeness `C` to `d`
+/// contains as many reflections as complete data to `d * (100 / C)^(1/3)`. The default exponent of 3 applies this
+/// relation exactly, whilst larger exponents penalise incompleteness less and smaller exponents more.
+#[derive(Debug, Clone, Copy)]
+pub struct EffectiveResolutionExponent(pub f32);
+
+impl Default for EffectiveResolutionExponent {
+    fn default() -> Self {
+        Self(3.0)
+    }
+}
+
+impl EffectiveResolutionExponent {
+    /// Computes `resolution * (100 / completeness)^(1 / exponent)`, or returns [`None`] if the resolution or
+    /// completeness is absent, or the completeness or exponent is not positive
+    pub fn effective_resolution(&self, overall: &AutoProcScalingStatics) -> Option<f32> {
+        let resolution = overall.resolution_limit_high?;
+        let completeness = overall.completeness?;
+        if completeness <= 0.0 || self.0 <= 0.0 {
+            return None;
+        }
+        Some(resolution * (100.0 / completeness).powf(1.0 / self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::entities::StatisticsType;
+
+    /// Overall statistics with only the completeness, high resolution limit and CC(1/2) present
+    fn overall(
+        completeness: Option<f32>,
+        resolution: Option<f32>,
+        cc_half: Option<f32>,
+    ) -> AutoProcScalingStatics {
+        AutoProcScalingStatics {
+            auto_proc_scaling_statistics_id: 1,
+            auto_proc_scaling_id: Some(1),
+            scaling_statistics_type: StatisticsType::Overall,
+            resolution_limit_low: None,
+            resolution_limit_high: resolution,
+            r_merge: None,
+            r_meas_all_i_plus_i_minus: None,
+            n_total_observations: None,
+            n_total_unique_observations: None,
+            mean_i_over_sig_i: None,
+            completeness,
+            multiplicity: None,
+            anomalous_completeness: None,
+            anomalous_multiplicity: None,
+            cc_half,
+            cc_anomalous: None,
+        }
+    }
+
+    #[test]
+    fn statistics_exactly_at_the_thresholds_meet_them() {
+        let thresholds = QualityGradeThresholds::default();
+        assert_eq!(
+            thresholds.grade(&overall(Some(95.0), Some(2.0), Some(90.0))),
+            Some(QualityGrade::A)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(85.0), Some(3.0), Some(50.0))),
+            Some(QualityGrade::B)
+        );
+    }
+
+    #[test]
+    fn statistics_just_beyond_a_threshold_fall_to_the_next_grade() {
+        let thresholds = QualityGradeThresholds::default();
+        assert_eq!(
+            thresholds.grade(&overall(Some(94.9), Some(2.0), Some(90.0))),
+            Some(QualityGrade::B)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(95.0), Some(2.01), Some(90.0))),
+            Some(QualityGrade::B)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(95.0), Some(2.0), Some(89.9))),
+            Some(QualityGrade::B)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(84.9), Some(1.0), Some(99.0))),
+            Some(QualityGrade::C)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(100.0), Some(3.01), Some(99.0))),
+            Some(QualityGrade::C)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(100.0), Some(1.0), Some(49.9))),
+            Some(QualityGrade::C)
+        );
+    }
+
+    #[test]
+    fn statistics_missing_a_graded_value_are_not_graded() {
+        let thresholds = QualityGradeThresholds::default();
+        assert_eq!(
+            thresholds.grade(&overall(None, Some(1.0), Some(99.0))),
+            None
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(99.0), None, Some(99.0))),
+            None
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(99.0), Some(1.0), None)),
+            None
+        );
+    }
+
+    #[test]
+    fn configured_thresholds_replace_the_defaults() {
+        let thresholds = QualityGradeThresholds {
+            grade_a: GradeThresholds {
+                min_completeness: 99.0,
+                max_resolution: 1.5,
+                min_cc_half: 99.0,
+            },
+            ..QualityGradeThresholds::default()
+        };
+        assert_eq!(
+            thresholds.grade(&overall(Some(98.0), Some(1.5), Some(99.0))),
+            Some(QualityGrade::B)
+        );
+        assert_eq!(
+            thresholds.grade(&overall(Some(99.0), Some(1.5), Some(99.0))),
+            Some(QualityGrade::A)
+        );
+    }
+
+    /// Asserts that the effective resolution is present and within rounding of the expected value
+    fn assert_effective_resolution(effective_resolution: Option<f32>, expected: f32) {
+        let effective_resolution = effective_resolution.unwrap();
+        assert!(
+            (effective_resolution - expected).abs() < 1e-4,
+            "{effective_resolution} != {expected}"
+        );
+    }
+
+    #[test]
+    fn complete_data_is_not_penalised() {
+        assert_effective_resolution(
+            EffectiveResolutionExponent::default().effective_resolution(&overall(
+                Some(100.0),
+                Some(1.8),
+                None,
+            )),
+            1.8,
+        );
+    }
+
+    #[test]
+    fn incomplete_data_is_penalised_by_the_root_of_the_exponent() {
+        assert_effective_resolution(
+            EffectiveResolutionExponent::default().effective_resolution(&overall(
+                Some(12.5),
+                Some(1.5),
+                None,
+            )),
+            3.0,
+        );
+        assert_effective_resolution(
+            EffectiveResolutionExponent(1.0).effective_resolution(&overall(
+                Some(50.0),
+                Some(1.5),
+                None,
+            )),
+            3.0,
+        );
+        assert_effective_resolution(
+            EffectiveResolutionExponent(2.0).effective_resolution(&overall(
+                Some(25.0),
+                Some(2.0),
+                None,
+            )),
+            4.0,
+        );
+    }
+
+    #[test]
+    fn effective_resolution_requires_resolution_and_completeness() {
+        let exponent = EffectiveResolutionExponent::default();
+        assert_eq!(
+            exponent.effective_resolution(&overall(None, Some(1.5), None)),
+            None
+        );
+        assert_eq!(
+            exponent.effective_resolution(&overall(Some(90.0), None, None)),
+            None
+        );
+    }
+
+    #[test]
+    fn effective_resolution_requires_positive_completeness_and_exponent() {
+        assert_eq!(
+            EffectiveResolutionExponent::default().effective_resolution(&overall(
+                Some(0.0),
+                Some(1.5),
+                None
+            )),
+            None
+        );
+        assert_eq!(
+            EffectiveResolutionExponent(0.0).effective_resolution(&overall(
+                Some(90.0),
+                Some(1.5),
+                None
+            )),
+            None
+        );
+        assert_eq!(
+            EffectiveResolutionExponent(-3.0).effective_resolution(&overall(
+                Some(90.0),
+                Some(1.5),
+                None
+            )),
+            None
+        );
+    }
+
+    /// Statistics of a shell with only the anomalous CC present
+    fn anomalous(cc_anomalous: Option<f32>) -> AutoProcScalingStatics {
+        AutoProcScalingStatics {
+            cc_anomalous,
+            ..overall(None, None, None)
+        }
+    }
+
+    #[test]
+    fn anomalous_signal_is_significant_only_above_the_threshold() {
+        let threshold = AnomalousSignificanceThreshold::default();
+        assert_eq!(threshold.is_significant(&anomalous(Some(35.0))), Some(true));
+        assert_eq!(threshold.is_significant(&anomalous(Some(15.1))), Some(true));
+        assert_eq!(
+            threshold.is_significant(&anomalous(Some(15.0))),
+            Some(false)
+        );
+        assert_eq!(threshold.is_significant(&anomalous(Some(5.0))), Some(false));
+        assert_eq!(threshold.is_significant(&anomalous(None)), None);
+    }
+
+    #[test]
+    fn anomalous_significance_threshold_is_configurable() {
+        let threshold = AnomalousSignificanceThreshold(40.0);
+        assert_eq!(
+            threshold.is_significant(&anomalous(Some(35.0))),
+            Some(false)
+        );
+        assert_eq!(
+            threshold.is_significant(&anomalous(Some(40.0))),
+            Some(false)
+        );
+        assert_eq!(threshold.is_significant(&anomalous(Some(60.0))), Some(true));
+        assert_eq!(threshold.is_significant(&anomalous(None)), None);
+    }
+}
@@ -0,0 +1,210 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    ErrorExtensions, Pos, Response,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The number of buckets above which full buckets are discarded
+const MAX_IDLE_BUCKETS: usize = 10_000;
+
+/// The client whose bucket a request draws from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    /// A client identified by the subject of its bearer token
+    Subject(String),
+    /// Any client which did not supply a bearer token
+    #[default]
+    Anonymous,
+}
+
+impl RateLimitKey {
+    /// Identifies the client by the subject claim of its bearer token if it is a JWT, or by the token itself otherwise
+    pub fn from_bearer(token: Option<&str>) -> Self {
+        let Some(token) = token else {
+            return Self::Anonymous;
+        };
+        let subject = token
+            .split('.')
+            .nth(1)
+            .and_then(|claims| URL_SAFE_NO_PAD.decode(claims).ok())
+            .and_then(|claims| serde_json::from_slice::<serde_json::Value>(&claims).ok())
+            .and_then(|claims| claims.get("sub")?.as_str().map(String::from));
+        Self::Subject(subject.unwrap_or_else(|| token.to_string()))
+    }
+}
+
+/// The tokens remaining to a client
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    /// The number of tokens, which may be fractional, remaining when last updated
+    tokens: f64,
+    /// The time at which the bucket was last updated
+    updated: Instant,
+}
+
+/// A token bucket rate limiter, shared across requests, with a bucket per [`RateLimitKey`]
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    /// The bucket of each client which has made a request
+    buckets: Arc<Mutex<HashMap<RateLimitKey, TokenBucket>>>,
+    /// The number of tokens added to each bucket per second
+    rate: f64,
+    /// The maximum number of tokens held by each bucket
+    burst: f64,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter allowing `rate` requests per second, with bursts of up to `burst` requests
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            buckets: Arc::default(),
+            rate,
+            burst: f64::from(burst),
+        }
+    }
+
+    /// Takes a token from the bucket of the client, or returns the period after which one will be available
+    fn acquire(&self, key: &RateLimitKey) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > MAX_IDLE_BUCKETS {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * self.rate
+                    < self.burst
+            });
+        }
+        let bucket = buckets.entry(key.clone()).or_insert(TokenBucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.updated).as_secs_f64() * self.rate)
+            .min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+/// An [`ExtensionFactory`] which rejects operations once the client has exhausted its bucket of the [`RateLimiter`] in the schema data
+#[derive(Debug, Default)]
+pub struct RateLimitGuard;
+
+impl ExtensionFactory for RateLimitGuard {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RateLimitGuardExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`RateLimitGuard`]
+struct RateLimitGuardExtension;
+
+#[async_trait::async_trait]
+impl Extension for RateLimitGuardExtension {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        if let Some(rate_limiter) = ctx.data_opt::<RateLimiter>() {
+            let key = ctx.data_opt::<RateLimitKey>().cloned().unwrap_or_default();
+            if let Err(retry_after) = rate_limiter.acquire(&key) {
+                let retry_after = retry_after.as_secs_f64().ceil() as u64;
+                return Response::from_errors(vec![async_graphql::Error::new(
+                    "Rate limit exceeded",
+                )
+                .extend_with(|_, extensions| {
+                    extensions.set("code", "RATE_LIMITED");
+                    extensions.set("retryAfter", retry_after);
+                })
+                .into_server_error(Pos::default())]);
+            }
+        }
+        next.run(ctx, operation_name).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::root_schema_builder;
+    use async_graphql::Request;
+
+    /// A JWT, with an unverified signature, carrying the subject claim
+    fn jwt(subject: &str) -> String {
+        format!(
+            "header.{}.signature",
+            URL_SAFE_NO_PAD.encode(format!(r#"{{"sub":"{subject}"}}"#))
+        )
+    }
+
+    #[test]
+    fn clients_are_identified_by_the_subject_of_their_token() {
+        assert_eq!(RateLimitKey::from_bearer(None), RateLimitKey::Anonymous);
+        assert_eq!(
+            RateLimitKey::from_bearer(Some(&jwt("alice"))),
+            RateLimitKey::Subject("alice".to_string())
+        );
+        assert_eq!(
+            RateLimitKey::from_bearer(Some("opaque")),
+            RateLimitKey::Subject("opaque".to_string())
+        );
+    }
+
+    #[test]
+    fn burst_over_the_limit_is_rejected_until_a_token_is_added() {
+        let rate_limiter = RateLimiter::new(10.0, 3);
+        let key = RateLimitKey::Subject("alice".to_string());
+
+        for _ in 0..3 {
+            assert_eq!(rate_limiter.acquire(&key), Ok(()));
+        }
+        let retry_after = rate_limiter.acquire(&key).unwrap_err();
+        assert!(retry_after <= Duration::from_millis(100), "{retry_after:?}");
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(rate_limiter.acquire(&key), Ok(()));
+        assert!(rate_limiter.acquire(&key).is_err());
+    }
+
+    #[test]
+    fn clients_draw_from_separate_buckets() {
+        let rate_limiter = RateLimiter::new(1.0, 1);
+        let alice = RateLimitKey::Subject("alice".to_string());
+        let bob = RateLimitKey::Subject("bob".to_string());
+
+        assert_eq!(rate_limiter.acquire(&alice), Ok(()));
+        assert!(rate_limiter.acquire(&alice).is_err());
+        assert_eq!(rate_limiter.acquire(&bob), Ok(()));
+        assert_eq!(rate_limiter.acquire(&RateLimitKey::Anonymous), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn operations_over_the_limit_are_rejected_and_recover() {
+        let schema = root_schema_builder(true)
+            .data(RateLimiter::new(5.0, 2))
+            .finish();
+        let request =
+            || Request::new("{ __typename }").data(RateLimitKey::from_bearer(Some(&jwt("alice"))));
+
+        assert!(schema.execute(request()).await.errors.is_empty());
+        assert!(schema.execute(request()).await.errors.is_empty());
+        let response = schema.execute(request()).await;
+        let extensions = response.errors[0].extensions.as_ref().unwrap();
+        assert_eq!(extensions.get("code"), Some(&"RATE_LIMITED".into()));
+        assert_eq!(extensions.get("retryAfter"), Some(&1.into()));
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(schema.execute(request()).await.errors.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tokio::task::JoinSet;
+
+/// A task spawned by a dataloader
+type LoaderTask = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// The dataloader tasks of a single request, which are aborted once the request, and with it every clone, is dropped, such as when the client disconnects
+#[derive(Debug, Clone, Default)]
+pub struct RequestTasks(Arc<Mutex<JoinSet<()>>>);
+
+impl RequestTasks {
+    /// Creates a spawner, for use with [`async_graphql::dataloader::DataLoader::new`], which adds tasks to this request
+    pub fn spawner(&self) -> impl Fn(LoaderTask) + Send + Sync + 'static {
+        let tasks = self.clone();
+        move |task| {
+            tasks.0.lock().unwrap().spawn(task);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::dataloader::{DataLoader, Loader};
+    use std::{collections::HashMap, time::Duration};
+    use tokio::sync::{mpsc, oneshot};
+
+    /// A loader whose queries never complete, reporting each as it starts with a receiver which is closed once the
+    /// query is cancelled
+    struct PendingLoader {
+        /// Receives a channel for each query started
+        started: mpsc::UnboundedSender<oneshot::Receiver<()>>,
+    }
+
+    impl Loader<u32> for PendingLoader {
+        type Value = ();
+        type Error = async_graphql::Error;
+
+        async fn load(&self, _keys: &[u32]) -> Result<HashMap<u32, Self::Value>, Self::Error> {
+            let (_running, cancelled) = oneshot::channel();
+            self.started.send(cancelled).unwrap();
+            std::future::pending().await
+        }
+    }
+
+    /// Starts a query of the loader, dropping the request for it once it has started, and returns the receiver which
+    /// is closed once the query is cancelled
+    async fn start_and_disconnect(
+        loader: &DataLoader<PendingLoader>,
+        started: &mut mpsc::UnboundedReceiver<oneshot::Receiver<()>>,
+    ) -> oneshot::Receiver<()> {
+        tokio::select! {
+            _ = loader.load_one(1) => unreachable!("Query completed"),
+            cancelled = started.recv() => cancelled.unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn queries_are_cancelled_once_the_request_is_dropped() {
+        let (sender, mut started) = mpsc::unbounded_channel();
+        let tasks = RequestTasks::default();
+        let loader = DataLoader::new(PendingLoader { started: sender }, tasks.spawner());
+
+        let cancelled = start_and_disconnect(&loader, &mut started).await;
+        drop(loader);
+        drop(tasks);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), cancelled)
+            .await
+            .expect("Query was not cancelled");
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn queries_continue_whilst_the_request_is_held() {
+        let (sender, mut started) = mpsc::unbounded_channel();
+        let tasks = RequestTasks::default();
+        let loader = DataLoader::new(PendingLoader { started: sender }, tasks.spawner());
+
+        let cancelled = start_and_disconnect(&loader, &mut started).await;
+
+        assert!(tokio::time::timeout(Duration::from_millis(100), cancelled)
+            .await
+            .is_err());
+    }
+}
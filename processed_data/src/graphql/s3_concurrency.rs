@@ -0,0 +1,122 @@
+use super::METRICS_TARGET;
+use std::{future::Future, sync::Arc, time::Instant};
+use tokio::sync::Semaphore;
+
+/// A limit on the number of S3 operations in flight at once across the process, shared by all loaders which call S3
+///
+/// Permits are granted in the order in which they are requested, such that no kind of operation is starved by another.
+#[derive(Debug, Clone, Default)]
+pub struct S3ConcurrencyLimit(Option<Arc<Semaphore>>);
+
+impl S3ConcurrencyLimit {
+    /// Creates a limit of `max_concurrency` operations, or no limit if unset
+    pub fn new(max_concurrency: Option<usize>) -> Self {
+        Self(
+            max_concurrency.map(|max_concurrency| Arc::new(Semaphore::new(max_concurrency.max(1)))),
+        )
+    }
+
+    /// Runs the operation once a permit is available, holding the permit until it completes
+    pub async fn run<T>(&self, operation: impl Future<Output = T>) -> T {
+        let _permit = match &self.0 {
+            Some(semaphore) => {
+                let start = Instant::now();
+                let permit = semaphore.acquire().await.ok();
+                tracing::info!(
+                    target: METRICS_TARGET,
+                    histogram.s3_permit_wait_ms = start.elapsed().as_millis() as u64,
+                );
+                permit
+            }
+            None => None,
+        };
+        operation.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::join_all;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    /// A count of the operations in flight, recording the greatest number which were in flight at once
+    #[derive(Debug, Default)]
+    struct InFlight {
+        /// The number of operations in flight
+        current: AtomicUsize,
+        /// The greatest number of operations which were in flight at once
+        max: AtomicUsize,
+    }
+
+    impl InFlight {
+        /// An operation which counts itself as in flight whilst it sleeps
+        async fn operation(&self) {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            self.current.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrency_never_exceeds_the_limit_under_load() {
+        let limit = S3ConcurrencyLimit::new(Some(3));
+        let in_flight = Arc::new(InFlight::default());
+
+        let tasks = (0..50).map(|_| {
+            let limit = limit.clone();
+            let in_flight = in_flight.clone();
+            tokio::spawn(async move { limit.run(in_flight.operation()).await })
+        });
+        for task in join_all(tasks).await {
+            task.unwrap();
+        }
+
+        assert_eq!(in_flight.max.load(Ordering::SeqCst), 3);
+        assert_eq!(in_flight.current.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn a_limit_of_zero_still_permits_one_operation() {
+        let limit = S3ConcurrencyLimit::new(Some(0));
+        let in_flight = InFlight::default();
+
+        join_all((0..5).map(|_| limit.run(in_flight.operation()))).await;
+
+        assert_eq!(in_flight.max.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn operations_are_unlimited_without_a_limit() {
+        let limit = S3ConcurrencyLimit::default();
+        let in_flight = InFlight::default();
+
+        join_all((0..20).map(|_| limit.run(in_flight.operation()))).await;
+
+        assert_eq!(in_flight.max.load(Ordering::SeqCst), 20);
+    }
+
+    #[tokio::test]
+    async fn permits_are_granted_in_the_order_requested() {
+        let limit = S3ConcurrencyLimit::new(Some(1));
+        let order = Mutex::new(Vec::new());
+
+        join_all((0..10).map(|index| {
+            let order = &order;
+            limit.run(async move {
+                order.lock().unwrap().push(index);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            })
+        }))
+        .await;
+
+        assert_eq!(*order.lock().unwrap(), (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,66 @@
+use async_graphql::{InputValueError, InputValueResult, Json, Scalar, ScalarType, Value};
+use chrono::{DateTime, Utc};
+use derive_more::{Deref, From};
+
+/// A point in time, represented as an RFC 3339 timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deref, From)]
+pub struct Timestamp(DateTime<Utc>);
+
+#[Scalar(
+    name = "DateTime",
+    specified_by_url = "https://datatracker.ietf.org/doc/html/rfc3339"
+)]
+impl ScalarType for Timestamp {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        DateTime::<Utc>::parse(value)
+            .map(Self)
+            .map_err(InputValueError::propagate)
+    }
+
+    fn to_value(&self) -> Value {
+        self.0.to_value()
+    }
+}
+
+/// A JSON object, with arbitrary keys and values
+#[derive(Debug, Clone, Default, PartialEq, Deref, From)]
+pub struct JsonObject(serde_json::Map<String, serde_json::Value>);
+
+#[Scalar(
+    name = "JSON",
+    specified_by_url = "https://datatracker.ietf.org/doc/html/rfc8259"
+)]
+impl ScalarType for JsonObject {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        Json::<serde_json::Map<String, serde_json::Value>>::parse(value)
+            .map(|json| Self(json.0))
+            .map_err(InputValueError::propagate)
+    }
+
+    fn to_value(&self) -> Value {
+        async_graphql::to_value(&self.0).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphql::{root_schema_builder, schema_sdl, FederationEntities};
+
+    #[test]
+    fn custom_scalars_are_exported_with_their_specifications() {
+        for standalone in [true, false] {
+            let sdl = schema_sdl(
+                &root_schema_builder(standalone).finish(),
+                standalone,
+                &FederationEntities::default(),
+            );
+            for (scalar, url) in [
+                ("DateTime", "https://datatracker.ietf.org/doc/html/rfc3339"),
+                ("JSON", "https://datatracker.ietf.org/doc/html/rfc8259"),
+            ] {
+                let definition = format!(r#"scalar {scalar} @specifiedBy(url: "{url}")"#);
+                assert!(sdl.contains(&definition), "{definition} missing from {sdl}");
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ErrorExtensions, Pos, ServerResult, TypeDirective, Value,
+};
+use std::{collections::HashSet, sync::Arc};
+
+/// The request header, set by the federation router, listing the scopes granted to the client
+pub const GRANTED_SCOPES_HEADER: &str = "x-granted-scopes";
+
+/// The scope required to read processing messages
+pub const PROCESSING_MESSAGE_SCOPE: &str = "processing_message:read";
+
+/// The scope required to download attachments of types which are not downloadable by default
+pub const INTERNAL_ATTACHMENT_SCOPE: &str = "attachment_internal:read";
+
+/// The scope required to read diagnostics of the service and its dependencies
+pub const ADMIN_SCOPE: &str = "admin:read";
+
+/// The fields which require a scope, as `(type, field, scope)`
+const SCOPED_FIELDS: &[(&str, &str, &str)] = &[
+    (
+        "AutoProcessing",
+        "processingMessage",
+        PROCESSING_MESSAGE_SCOPE,
+    ),
+    (
+        "AutoProcessing",
+        "processingMessageTruncated",
+        PROCESSING_MESSAGE_SCOPE,
+    ),
+    ("Query", "databaseInfo", ADMIN_SCOPE),
+];
+
+/// Marks a field as requiring the client to have been granted a scope
+#[TypeDirective(location = "FieldDefinition")]
+pub fn requires_scope(scope: String) {}
+
+/// The scopes granted to the client making the request
+#[derive(Debug, Clone, Default)]
+pub struct GrantedScopes(HashSet<String>);
+
+impl GrantedScopes {
+    /// Parses a comma separated list of scopes
+    pub fn from_header(header: &str) -> Self {
+        Self(
+            header
+                .split(',')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(String::from)
+                .collect(),
+        )
+    }
+
+    /// Returns true if the scope has been granted
+    pub fn contains(&self, scope: &str) -> bool {
+        self.0.contains(scope)
+    }
+}
+
+/// An [`ExtensionFactory`] which rejects the resolution of fields requiring scopes the client has not been granted
+#[derive(Debug, Default)]
+pub struct RequiredScopes;
+
+impl ExtensionFactory for RequiredScopes {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(RequiredScopesExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`RequiredScopes`]
+struct RequiredScopesExtension;
+
+#[async_trait::async_trait]
+impl Extension for RequiredScopesExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let required_scope = SCOPED_FIELDS
+            .iter()
+            .find(|(parent_type, name, _)| *parent_type == info.parent_type && *name == info.name)
+            .map(|(_, _, scope)| *scope);
+        if let Some(scope) = required_scope {
+            if !ctx
+                .data_opt::<GrantedScopes>()
+                .is_some_and(|granted_scopes| granted_scopes.contains(scope))
+            {
+                return Err(async_graphql::Error::new(format!(
+                    "The {scope} scope is required to access this field"
+                ))
+                .extend_with(|_, extensions| extensions.set("code", "FORBIDDEN"))
+                .into_server_error(Pos::default()));
+            }
+        }
+        next.run(ctx, info).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::root_schema_builder;
+    use async_graphql::Request;
+
+    /// The `(type, field, scope)` of each field carrying the `@requiresScope` directive in the SDL
+    fn directive_fields(sdl: &str) -> HashSet<(String, String, String)> {
+        let mut parent_type = "";
+        let mut fields = HashSet::new();
+        for line in sdl.lines() {
+            if let Some(definition) = line.strip_prefix("type ") {
+                parent_type = definition.split([' ', '{']).next().unwrap_or_default();
+            } else if let Some((field, directive)) = line.split_once(" @requiresScope(scope: \"") {
+                let name = field.trim().split(['(', ':']).next().unwrap_or_default();
+                let scope = directive.split('"').next().unwrap_or_default();
+                fields.insert((parent_type.to_string(), name.to_string(), scope.to_string()));
+            }
+        }
+        fields
+    }
+
+    /// The code in the extensions of the first error of the response
+    fn error_code(response: &async_graphql::Response) -> Option<Value> {
+        response
+            .errors
+            .first()
+            .and_then(|error| error.extensions.as_ref())
+            .and_then(|extensions| extensions.get("code").cloned())
+    }
+
+    #[test]
+    fn granted_scopes_are_parsed_from_a_comma_separated_list() {
+        let granted_scopes = GrantedScopes::from_header(" admin:read,, processing_message:read ");
+        assert!(granted_scopes.contains(ADMIN_SCOPE));
+        assert!(granted_scopes.contains(PROCESSING_MESSAGE_SCOPE));
+        assert!(!granted_scopes.contains(INTERNAL_ATTACHMENT_SCOPE));
+        assert!(!granted_scopes.contains(""));
+    }
+
+    #[test]
+    fn sdl_advertises_the_scope_of_each_enforced_field() {
+        let sdl = root_schema_builder(true).finish().sdl();
+
+        assert!(sdl.contains("directive @requiresScope(scope: String!) on FIELD_DEFINITION"));
+        assert_eq!(
+            directive_fields(&sdl),
+            SCOPED_FIELDS
+                .iter()
+                .map(|(parent_type, name, scope)| {
+                    (parent_type.to_string(), name.to_string(), scope.to_string())
+                })
+                .collect()
+        );
+    }
+
+    #[tokio::test]
+    async fn field_is_rejected_without_its_scope() {
+        let schema = root_schema_builder(true).finish();
+
+        let response = schema
+            .execute(Request::new("{ databaseInfo { version } }"))
+            .await;
+        assert_eq!(error_code(&response), Some("FORBIDDEN".into()));
+
+        let response = schema
+            .execute(
+                Request::new("{ databaseInfo { version } }")
+                    .data(GrantedScopes::from_header(PROCESSING_MESSAGE_SCOPE)),
+            )
+            .await;
+        assert_eq!(error_code(&response), Some("FORBIDDEN".into()));
+    }
+}
@@ -0,0 +1,209 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+
+/// The MAC with which share tokens are signed
+type ShareTokenMac = Hmac<Sha256>;
+
+/// The auto processing referenced by a share token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShareReference {
+    /// An opaque unique identifier for the data collection
+    pub data_collection_id: u32,
+    /// An opaque unique identifier for the auto processing integration
+    pub auto_proc_integration_id: u32,
+}
+
+/// Signs and verifies tokens which reference an auto processing run
+#[derive(Clone)]
+pub struct ShareTokenSigner {
+    /// The secret with which tokens are signed
+    secret: Vec<u8>,
+    /// The period for which tokens remain valid
+    ttl: Duration,
+}
+
+impl ShareTokenSigner {
+    /// Creates a signer of tokens which remain valid for `ttl`
+    pub fn new(secret: impl Into<Vec<u8>>, ttl: Duration) -> Self {
+        Self {
+            secret: secret.into(),
+            ttl,
+        }
+    }
+
+    /// Computes the MAC of the payload
+    fn mac(&self, payload: &[u8]) -> ShareTokenMac {
+        let mut mac =
+            ShareTokenMac::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+
+    /// Produces a token referencing the auto processing, valid until the configured period has elapsed
+    pub fn sign(&self, reference: ShareReference) -> Result<String, async_graphql::Error> {
+        let expires_at = Utc::now() + chrono::Duration::from_std(self.ttl)?;
+        let payload = format!(
+            "{}:{}:{}",
+            reference.data_collection_id,
+            reference.auto_proc_integration_id,
+            expires_at.timestamp()
+        );
+        let signature = self.mac(payload.as_bytes()).finalize().into_bytes();
+        Ok(format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+
+    /// Returns the auto processing referenced by the token, if it was signed with the secret and has not expired
+    pub fn verify(&self, token: &str) -> Result<ShareReference, async_graphql::Error> {
+        let invalid = || async_graphql::Error::new("Invalid share token");
+        let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        self.mac(&payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+        let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+        let mut fields = payload.split(':').map(str::parse::<i64>);
+        let (
+            Some(Ok(data_collection_id)),
+            Some(Ok(auto_proc_integration_id)),
+            Some(Ok(expires_at)),
+            None,
+        ) = (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid());
+        };
+        let expires_at = DateTime::from_timestamp(expires_at, 0).ok_or_else(invalid)?;
+        if expires_at < Utc::now() {
+            return Err(async_graphql::Error::new("Share token has expired"));
+        }
+        Ok(ShareReference {
+            data_collection_id: u32::try_from(data_collection_id).map_err(|_| invalid())?,
+            auto_proc_integration_id: u32::try_from(auto_proc_integration_id)
+                .map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The reference signed by the tests
+    const REFERENCE: ShareReference = ShareReference {
+        data_collection_id: 1,
+        auto_proc_integration_id: 41,
+    };
+
+    /// A signer of tokens valid for an hour
+    fn signer(secret: &str) -> ShareTokenSigner {
+        ShareTokenSigner::new(secret, Duration::from_secs(3600))
+    }
+
+    /// A token carrying the payload, signed by the signer
+    fn signed_token(signer: &ShareTokenSigner, payload: &str) -> String {
+        let signature = signer.mac(payload.as_bytes()).finalize().into_bytes();
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    #[test]
+    fn signed_reference_is_verified() {
+        let signer = signer("secret");
+        let token = signer.sign(REFERENCE).unwrap();
+        assert_eq!(signer.verify(&token).unwrap(), REFERENCE);
+    }
+
+    #[test]
+    fn token_signed_with_another_secret_is_rejected() {
+        let token = signer("other secret").sign(REFERENCE).unwrap();
+        assert_eq!(
+            signer("secret").verify(&token).unwrap_err().message,
+            "Invalid share token"
+        );
+    }
+
+    #[test]
+    fn tampered_payload_is_rejected() {
+        let signer = signer("secret");
+        let token = signer.sign(REFERENCE).unwrap();
+        let (payload, signature) = token.split_once('.').unwrap();
+        let payload = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        let tampered = format!(
+            "{}.{signature}",
+            URL_SAFE_NO_PAD.encode(payload.replacen("1:", "2:", 1))
+        );
+        assert_eq!(
+            signer.verify(&tampered).unwrap_err().message,
+            "Invalid share token"
+        );
+    }
+
+    #[test]
+    fn tampered_signature_is_rejected() {
+        let signer = signer("secret");
+        let token = signer.sign(REFERENCE).unwrap();
+        let (payload, signature) = token.split_once('.').unwrap();
+        let mut signature = URL_SAFE_NO_PAD.decode(signature).unwrap();
+        signature[0] ^= 1;
+        let tampered = format!("{payload}.{}", URL_SAFE_NO_PAD.encode(signature));
+        assert_eq!(
+            signer.verify(&tampered).unwrap_err().message,
+            "Invalid share token"
+        );
+    }
+
+    #[test]
+    fn malformed_tokens_are_rejected() {
+        let signer = signer("secret");
+        for token in [
+            "",
+            "no-separator",
+            "!!!.!!!",
+            signed_token(&signer, "1:41").as_str(),
+            signed_token(&signer, "1:41:4102444800:0").as_str(),
+            signed_token(&signer, "-1:41:4102444800").as_str(),
+            signed_token(&signer, "one:41:4102444800").as_str(),
+        ] {
+            assert_eq!(
+                signer.verify(token).unwrap_err().message,
+                "Invalid share token",
+                "{token}"
+            );
+        }
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let signer = signer("secret");
+        let expired_at = (Utc::now() - chrono::Duration::seconds(1)).timestamp();
+        let token = signed_token(&signer, &format!("1:41:{expired_at}"));
+        assert_eq!(
+            signer.verify(&token).unwrap_err().message,
+            "Share token has expired"
+        );
+    }
+
+    #[test]
+    fn token_expires_after_the_configured_period() {
+        let signer = signer("secret");
+        let token = signer.sign(REFERENCE).unwrap();
+        let payload = String::from_utf8(
+            URL_SAFE_NO_PAD
+                .decode(token.split_once('.').unwrap().0)
+                .unwrap(),
+        )
+        .unwrap();
+        let expires_at = payload.rsplit(':').next().unwrap().parse::<i64>().unwrap();
+        assert!((expires_at - (Utc::now().timestamp() + 3600)).abs() <= 1);
+    }
+}
@@ -0,0 +1,199 @@
+use futures_util::{stream, Stream};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, Statement};
+use sea_query::{Alias, Expr, Order, Query};
+use std::{ops::Deref, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+/// A change to the processing status of an auto processing program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusEvent {
+    /// An opaque unique identifier for the data collection processed by the program
+    pub data_collection_id: u32,
+    /// An opaque unique identifier for the auto processing program
+    pub auto_proc_program_id: u32,
+    /// The processing status of the program after the change
+    pub processing_status: Option<i8>,
+}
+
+/// A broadcast channel of processing status changes, injected into the schema as data when an event source is running
+///
+/// Subscriptions fall back to polling the database when it is absent.
+#[derive(Debug, Clone)]
+pub struct ProcessingStatusEvents(broadcast::Sender<StatusEvent>);
+
+impl ProcessingStatusEvents {
+    /// Creates a channel buffering up to `capacity` events for each subscriber
+    pub fn new(capacity: usize) -> Self {
+        Self(broadcast::channel(capacity.max(1)).0)
+    }
+
+    /// Sends the event to all subscribers, if there are any
+    pub fn publish(&self, event: StatusEvent) {
+        let _ = self.0.send(event);
+    }
+
+    /// Streams the events published after subscribing which concern the data collection
+    ///
+    /// Events missed by a subscriber which falls behind are skipped with a warning.
+    pub fn subscribe(&self, data_collection_id: u32) -> impl Stream<Item = StatusEvent> {
+        stream::unfold(self.0.subscribe(), move |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.data_collection_id == data_collection_id => {
+                        return Some((event, receiver))
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Subscriber of data collection {data_collection_id} skipped {skipped} processing status events");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// Starts publishing the rows appended to a notification table, populated by triggers on `AutoProcProgram`, to the events
+///
+/// The table must have the columns `notificationId`, `dataCollectionId`, `autoProcProgramId` and `processingStatus`,
+/// with ascending notification ids. A single query is made each `poll_interval` on behalf of all subscribers. An error
+/// is returned, and nothing is started, if the table cannot be read.
+pub async fn spawn_notification_source(
+    database: DatabaseConnection,
+    table: String,
+    poll_interval: Duration,
+    events: ProcessingStatusEvents,
+) -> Result<(), DbErr> {
+    let mut cursor = latest_notification_id(&database, &table).await?;
+    info!("Publishing processing status changes from {table}");
+    tokio::spawn(async move {
+        let mut poll_interval = tokio::time::interval(poll_interval);
+        loop {
+            poll_interval.tick().await;
+            match notifications_after(&database, &table, cursor).await {
+                Ok(notifications) => {
+                    for (notification_id, event) in notifications {
+                        cursor = notification_id;
+                        events.publish(event);
+                    }
+                }
+                Err(err) => warn!("Failed to read processing status notifications: {err}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Fetches the largest notification id currently present, or zero if the table is empty
+async fn latest_notification_id(database: &DatabaseConnection, table: &str) -> Result<u64, DbErr> {
+    let query = Query::select()
+        .expr_as(
+            Expr::col(Alias::new("notificationId")).max(),
+            Alias::new("latest"),
+        )
+        .from(Alias::new(table))
+        .to_owned();
+    let backend = database.get_database_backend();
+    let (sql, values) = query.build_any(backend.get_query_builder().deref());
+    Ok(database
+        .query_one(Statement::from_sql_and_values(backend, sql, values))
+        .await?
+        .map(|row| row.try_get::<Option<u64>>("", "latest"))
+        .transpose()?
+        .flatten()
+        .unwrap_or_default())
+}
+
+/// Fetches the notifications with ids greater than the cursor, in id order
+async fn notifications_after(
+    database: &DatabaseConnection,
+    table: &str,
+    cursor: u64,
+) -> Result<Vec<(u64, StatusEvent)>, DbErr> {
+    let query = Query::select()
+        .columns([
+            Alias::new("notificationId"),
+            Alias::new("dataCollectionId"),
+            Alias::new("autoProcProgramId"),
+            Alias::new("processingStatus"),
+        ])
+        .from(Alias::new(table))
+        .and_where(Expr::col(Alias::new("notificationId")).gt(cursor))
+        .order_by(Alias::new("notificationId"), Order::Asc)
+        .to_owned();
+    let backend = database.get_database_backend();
+    let (sql, values) = query.build_any(backend.get_query_builder().deref());
+    database
+        .query_all(Statement::from_sql_and_values(backend, sql, values))
+        .await?
+        .into_iter()
+        .map(|row| {
+            Ok((
+                row.try_get("", "notificationId")?,
+                StatusEvent {
+                    data_collection_id: row.try_get("", "dataCollectionId")?,
+                    auto_proc_program_id: row.try_get("", "autoProcProgramId")?,
+                    processing_status: row.try_get("", "processingStatus")?,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    /// A change to the status of the program of the data collection
+    fn event(
+        data_collection_id: u32,
+        auto_proc_program_id: u32,
+        status: Option<i8>,
+    ) -> StatusEvent {
+        StatusEvent {
+            data_collection_id,
+            auto_proc_program_id,
+            processing_status: status,
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_the_events_of_their_data_collection() {
+        let events = ProcessingStatusEvents::new(16);
+        events.publish(event(1, 31, Some(0)));
+        let first = events.subscribe(1);
+        let second = events.subscribe(2);
+
+        events.publish(event(2, 34, Some(1)));
+        events.publish(event(1, 33, None));
+        events.publish(event(1, 33, Some(1)));
+        drop(events);
+
+        assert_eq!(
+            first.collect::<Vec<_>>().await,
+            vec![event(1, 33, None), event(1, 33, Some(1))]
+        );
+        assert_eq!(
+            second.collect::<Vec<_>>().await,
+            vec![event(2, 34, Some(1))]
+        );
+    }
+
+    #[tokio::test]
+    async fn lagging_subscribers_skip_missed_events() {
+        let events = ProcessingStatusEvents::new(2);
+        let subscriber = events.subscribe(1);
+
+        for program_id in 31..35 {
+            events.publish(event(1, program_id, Some(1)));
+        }
+        drop(events);
+
+        assert_eq!(
+            subscriber.collect::<Vec<_>>().await,
+            vec![event(1, 33, Some(1)), event(1, 34, Some(1))]
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use super::{
+    entities::{AutoProcFileAttachment, ProcessingStatusChange, ProcessingStatusCodes},
+    features::{require_feature, Feature},
+    status_events::{ProcessingStatusEvents, StatusEvent},
+};
+use async_graphql::{Context, Subscription};
+use derive_more::Deref;
+use futures_util::{future::ready, stream, Stream, StreamExt};
+use models::{auto_proc_integration, auto_proc_program, auto_proc_program_attachment};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use sea_query::Expr;
+use std::{collections::HashMap, time::Duration};
+
+/// The period between successive polls of the database by subscriptions
+#[derive(Debug, Clone, Copy, Deref)]
+pub struct AttachmentPollInterval(Duration);
+
+impl AttachmentPollInterval {
+    /// Creates a poll interval of the supplied duration
+    pub fn new(interval: Duration) -> Self {
+        Self(interval)
+    }
+}
+
+/// The root subscription of the service
+#[derive(Debug, Clone, Default)]
+pub struct Subscription;
+
+#[Subscription]
+impl Subscription {
+    /// Streams batches of file attachments added to the auto processing of a data collection after subscribing, if the subscriptions feature is enabled
+    async fn new_attachments(
+        &self,
+        ctx: &Context<'_>,
+        data_collection_id: u32,
+    ) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<Vec<AutoProcFileAttachment>>>>
+    {
+        require_feature(ctx, Feature::Subscriptions)?;
+        let database = ctx.data::<DatabaseConnection>()?.clone();
+        let poll_interval = tokio::time::interval(**ctx.data::<AttachmentPollInterval>()?);
+        let cursor = latest_attachment_id(&database).await?;
+        Ok(stream::unfold(
+            (database, cursor, poll_interval),
+            move |(database, cursor, mut poll_interval)| async move {
+                poll_interval.tick().await;
+                match attachments_after(&database, data_collection_id, cursor).await {
+                    Ok(attachments) => {
+                        let cursor = attachments
+                            .last()
+                            .map(|attachment| attachment.id)
+                            .unwrap_or(cursor);
+                        Some((Ok(attachments), (database, cursor, poll_interval)))
+                    }
+                    Err(err) => Some((Err(err.into()), (database, cursor, poll_interval))),
+                }
+            },
+        )
+        .filter(|attachments| {
+            ready(!matches!(attachments, Ok(attachments) if attachments.is_empty()))
+        }))
+    }
+
+    /// Streams changes to the processing status of the auto processing of a data collection after subscribing, if the subscriptions feature is enabled
+    ///
+    /// Changes are pushed from the processing status event source if one is running, and otherwise found by polling.
+    async fn processing_status_changes(
+        &self,
+        ctx: &Context<'_>,
+        data_collection_id: u32,
+    ) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<ProcessingStatusChange>>>
+    {
+        require_feature(ctx, Feature::Subscriptions)?;
+        let status_codes = ctx
+            .data_opt::<ProcessingStatusCodes>()
+            .cloned()
+            .unwrap_or_default();
+        let events = match ctx.data_opt::<ProcessingStatusEvents>() {
+            Some(events) => events
+                .subscribe(data_collection_id)
+                .map(Ok::<_, DbErr>)
+                .boxed(),
+            None => {
+                let database = ctx.data::<DatabaseConnection>()?.clone();
+                let poll_interval = tokio::time::interval(**ctx.data::<AttachmentPollInterval>()?);
+                let statuses = program_statuses(&database, data_collection_id).await?;
+                poll_status_events(database, data_collection_id, statuses, poll_interval).boxed()
+            }
+        };
+        Ok(events.map(move |event| {
+            let event = event?;
+            Ok(ProcessingStatusChange {
+                auto_proc_program_id: event.auto_proc_program_id,
+                status: status_codes.outcome(event.processing_status),
+            })
+        }))
+    }
+}
+
+/// Polls the processing statuses of the data collection's auto processing, streaming those which differ from the last poll
+fn poll_status_events(
+    database: DatabaseConnection,
+    data_collection_id: u32,
+    statuses: HashMap<u32, Option<i8>>,
+    poll_interval: tokio::time::Interval,
+) -> impl Stream<Item = Result<StatusEvent, DbErr>> {
+    stream::unfold(
+        (database, statuses, poll_interval),
+        move |(database, mut statuses, mut poll_interval)| async move {
+            poll_interval.tick().await;
+            let events = match program_statuses(&database, data_collection_id).await {
+                Ok(current) => current
+                    .into_iter()
+                    .filter(|(program_id, status)| {
+                        statuses.insert(*program_id, *status) != Some(*status)
+                    })
+                    .map(|(auto_proc_program_id, processing_status)| {
+                        Ok(StatusEvent {
+                            data_collection_id,
+                            auto_proc_program_id,
+                            processing_status,
+                        })
+                    })
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            };
+            Some((stream::iter(events), (database, statuses, poll_interval)))
+        },
+    )
+    .flatten()
+}
+
+/// Fetches the processing status of each auto processing program of the data collection, keyed on program id
+async fn program_statuses(
+    database: &DatabaseConnection,
+    data_collection_id: u32,
+) -> Result<HashMap<u32, Option<i8>>, DbErr> {
+    Ok(auto_proc_program::Entity::find()
+        .select_only()
+        .column(auto_proc_program::Column::AutoProcProgramId)
+        .column(auto_proc_program::Column::ProcessingStatus)
+        .filter(
+            auto_proc_program::Column::AutoProcProgramId.in_subquery(
+                sea_query::Query::select()
+                    .column(auto_proc_integration::Column::AutoProcProgramId)
+                    .from(auto_proc_integration::Entity)
+                    .and_where(
+                        Expr::col(auto_proc_integration::Column::DataCollectionId)
+                            .eq(data_collection_id),
+                    )
+                    .to_owned(),
+            ),
+        )
+        .into_tuple::<(u32, Option<i8>)>()
+        .all(database)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Fetches the largest attachment id currently present, or zero if there are no attachments
+async fn latest_attachment_id(database: &DatabaseConnection) -> Result<u32, DbErr> {
+    let latest = auto_proc_program_attachment::Entity::find()
+        .select_only()
+        .column_as(
+            auto_proc_program_attachment::Column::AutoProcProgramAttachmentId.max(),
+            "latest",
+        )
+        .into_tuple::<Option<u32>>()
+        .one(database)
+        .await?;
+    Ok(latest.flatten().unwrap_or_default())
+}
+
+/// Fetches the attachments of the data collection's auto processing with ids greater than the cursor, in id order
+async fn attachments_after(
+    database: &DatabaseConnection,
+    data_collection_id: u32,
+    cursor: u32,
+) -> Result<Vec<AutoProcFileAttachment>, DbErr> {
+    Ok(auto_proc_program_attachment::Entity::find()
+        .filter(
+            auto_proc_program_attachment::Column::AutoProcProgramId.in_subquery(
+                sea_query::Query::select()
+                    .column(auto_proc_integration::Column::AutoProcProgramId)
+                    .from(auto_proc_integration::Entity)
+                    .and_where(
+                        Expr::col(auto_proc_integration::Column::DataCollectionId)
+                            .eq(data_collection_id),
+                    )
+                    .to_owned(),
+            ),
+        )
+        .filter(auto_proc_program_attachment::Column::AutoProcProgramAttachmentId.gt(cursor))
+        .order_by_asc(auto_proc_program_attachment::Column::AutoProcProgramAttachmentId)
+        .all(database)
+        .await?
+        .into_iter()
+        .map(AutoProcFileAttachment::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::{root_schema_builder, FeatureSet};
+    use async_graphql::Request;
+
+    #[tokio::test]
+    async fn subscribers_receive_status_changes_from_the_event_source() {
+        let events = ProcessingStatusEvents::new(16);
+        let schema = root_schema_builder(true)
+            .data(FeatureSet::new([Feature::Subscriptions]))
+            .data(events.clone())
+            .finish();
+        let mut subscription = schema
+            .execute_stream(Request::new(
+                "subscription { processingStatusChanges(dataCollectionId: 1) { autoProcProgramId status } }",
+            ))
+            .boxed();
+        // The subscription only receives events published once it has started, so they are published until one arrives
+        let publisher = tokio::spawn(async move {
+            loop {
+                events.publish(StatusEvent {
+                    data_collection_id: 2,
+                    auto_proc_program_id: 34,
+                    processing_status: Some(0),
+                });
+                events.publish(StatusEvent {
+                    data_collection_id: 1,
+                    auto_proc_program_id: 33,
+                    processing_status: Some(1),
+                });
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        let response = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+            .await
+            .unwrap()
+            .unwrap();
+        publisher.abort();
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({
+                "processingStatusChanges": { "autoProcProgramId": 33, "status": "SUCCEEDED" }
+            })
+        );
+    }
+}
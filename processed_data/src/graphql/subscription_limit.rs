@@ -0,0 +1,180 @@
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextSubscribe},
+    ErrorExtensions, Pos, Response,
+};
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// The maximum number of subscriptions which may be active on a single websocket connection
+#[derive(Debug, Clone, Copy)]
+pub struct MaxSubscriptionsPerConnection(pub usize);
+
+/// The number of subscriptions active on a websocket connection, injected into the connection data
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionSubscriptions(Arc<AtomicUsize>);
+
+/// Marks a subscription as active until dropped
+struct ActiveSubscription(ConnectionSubscriptions);
+
+impl Drop for ActiveSubscription {
+    fn drop(&mut self) {
+        self.0 .0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// An [`ExtensionFactory`] which rejects subscriptions once the [`MaxSubscriptionsPerConnection`] in the schema data are active on the connection
+#[derive(Debug, Default)]
+pub struct SubscriptionLimit;
+
+impl ExtensionFactory for SubscriptionLimit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(SubscriptionLimitExtension)
+    }
+}
+
+/// The per-request [`Extension`] created by [`SubscriptionLimit`]
+struct SubscriptionLimitExtension;
+
+#[async_trait::async_trait]
+impl Extension for SubscriptionLimitExtension {
+    fn subscribe<'s>(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        stream: BoxStream<'s, Response>,
+        next: NextSubscribe<'_>,
+    ) -> BoxStream<'s, Response> {
+        let (Some(MaxSubscriptionsPerConnection(max_subscriptions)), Some(subscriptions)) = (
+            ctx.data_opt::<MaxSubscriptionsPerConnection>(),
+            ctx.data_opt::<ConnectionSubscriptions>(),
+        ) else {
+            return next.run(ctx, stream);
+        };
+        if subscriptions.0.fetch_add(1, Ordering::Relaxed) >= *max_subscriptions {
+            subscriptions.0.fetch_sub(1, Ordering::Relaxed);
+            let max_subscriptions = *max_subscriptions;
+            return stream::once(async move {
+                Response::from_errors(vec![async_graphql::Error::new(format!(
+                    "No more than {max_subscriptions} subscriptions may be active on a single connection"
+                ))
+                .extend_with(|_, extensions| extensions.set("code", "SUBSCRIPTION_LIMIT_EXCEEDED"))
+                .into_server_error(Pos::default())])
+            })
+            .boxed();
+        }
+        let active = ActiveSubscription(subscriptions.clone());
+        next.run(ctx, stream)
+            .inspect(move |_| {
+                let _active = &active;
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{Data, EmptyMutation, Executor, Object, Request, Schema, Subscription};
+    use futures_util::Stream;
+
+    /// A query with a single field, as required of every schema
+    struct TestQuery;
+
+    #[Object]
+    impl TestQuery {
+        /// A constant value
+        async fn value(&self) -> u32 {
+            0
+        }
+    }
+
+    /// A subscription which never ends
+    struct TestSubscription;
+
+    #[Subscription]
+    impl TestSubscription {
+        /// Counts upwards indefinitely
+        async fn ticks(&self) -> impl Stream<Item = u32> {
+            stream::iter(0..)
+        }
+    }
+
+    /// A schema permitting two subscriptions on each connection
+    fn schema() -> Schema<TestQuery, EmptyMutation, TestSubscription> {
+        Schema::build(TestQuery, EmptyMutation, TestSubscription)
+            .extension(SubscriptionLimit)
+            .data(MaxSubscriptionsPerConnection(2))
+            .finish()
+    }
+
+    /// Starts a subscription on the connection
+    fn subscribe(
+        schema: &Schema<TestQuery, EmptyMutation, TestSubscription>,
+        connection: &ConnectionSubscriptions,
+    ) -> BoxStream<'static, Response> {
+        let mut data = Data::default();
+        data.insert(connection.clone());
+        Executor::execute_stream(
+            schema,
+            Request::new("subscription { ticks }"),
+            Some(Arc::new(data)),
+        )
+    }
+
+    /// The code of the first error of the next response of the subscription, if any
+    async fn next_error_code(subscription: &mut BoxStream<'static, Response>) -> Option<String> {
+        let response = subscription.next().await.unwrap();
+        let error = response.errors.first()?;
+        match error.extensions.as_ref()?.get("code")? {
+            async_graphql::Value::String(code) => Some(code.clone()),
+            code => panic!("Unexpected error code {code}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscriptions_over_the_limit_are_rejected() {
+        let schema = schema();
+        let connection = ConnectionSubscriptions::default();
+
+        let mut first = subscribe(&schema, &connection);
+        let mut second = subscribe(&schema, &connection);
+        let mut third = subscribe(&schema, &connection);
+
+        assert_eq!(next_error_code(&mut first).await, None);
+        assert_eq!(next_error_code(&mut second).await, None);
+        assert_eq!(
+            next_error_code(&mut third).await.as_deref(),
+            Some("SUBSCRIPTION_LIMIT_EXCEEDED")
+        );
+        assert!(third.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn subscriptions_may_be_started_once_others_end() {
+        let schema = schema();
+        let connection = ConnectionSubscriptions::default();
+
+        let first = subscribe(&schema, &connection);
+        let _second = subscribe(&schema, &connection);
+        drop(first);
+
+        let mut third = subscribe(&schema, &connection);
+        assert_eq!(next_error_code(&mut third).await, None);
+    }
+
+    #[tokio::test]
+    async fn subscriptions_are_limited_per_connection() {
+        let schema = schema();
+        let connection = ConnectionSubscriptions::default();
+        let _first = subscribe(&schema, &connection);
+        let _second = subscribe(&schema, &connection);
+
+        let mut other = subscribe(&schema, &ConnectionSubscriptions::default());
+        assert_eq!(next_error_code(&mut other).await, None);
+    }
+}
@@ -0,0 +1,63 @@
+use super::METRICS_TARGET;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextRequest},
+    value, Response,
+};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
+
+/// The request header with which clients opt in to receiving the execution time
+pub const INCLUDE_TIMING_HEADER: &str = "x-include-timing";
+
+/// A marker, added to the request data, indicating the execution time should be included in the response
+#[derive(Debug, Clone, Copy)]
+pub struct IncludeTiming;
+
+/// An [`ExtensionFactory`] which records the time taken to handle each request as a metric and, if requested, in the response extensions
+#[derive(Debug, Default)]
+pub struct ExecutionTimer;
+
+impl ExtensionFactory for ExecutionTimer {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ExecutionTimerExtension::default())
+    }
+}
+
+/// The per-request [`Extension`] created by [`ExecutionTimer`]
+#[derive(Debug, Default)]
+struct ExecutionTimerExtension {
+    /// The time at which the request was received
+    start: OnceLock<Instant>,
+}
+
+#[async_trait::async_trait]
+impl Extension for ExecutionTimerExtension {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let start = *self.start.get_or_init(Instant::now);
+        let response = next.run(ctx).await;
+        tracing::info!(
+            target: METRICS_TARGET,
+            histogram.request_duration_ms = start.elapsed().as_secs_f64() * 1000.0,
+        );
+        response
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let response = next.run(ctx, operation_name).await;
+        if ctx.data_opt::<IncludeTiming>().is_some() {
+            // Parsing and validation have completed by execution, so only the serialization of the response is excluded
+            let start = *self.start.get_or_init(Instant::now);
+            let total_ms = start.elapsed().as_secs_f64() * 1000.0;
+            response.extension("timing", value!({ "totalMs": total_ms }))
+        } else {
+            response
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use async_graphql::ErrorExtensions;
+use std::str::FromStr;
+
+/// A visit to a beamline, identified as `{proposal_code}{proposal_number}-{visit_number}`, e.g. `cm12345-6`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Visit {
+    /// The alphabetic code of the proposal
+    pub proposal_code: String,
+    /// The number of the proposal
+    pub proposal_number: String,
+    /// The number of the visit within the proposal
+    pub visit_number: u32,
+}
+
+impl FromStr for Visit {
+    type Err = async_graphql::Error;
+
+    fn from_str(visit: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            async_graphql::Error::new(format!("Invalid visit {visit}"))
+                .extend_with(|_, extensions| extensions.set("code", "BAD_USER_INPUT"))
+        };
+        let (proposal, visit_number) = visit.rsplit_once('-').ok_or_else(invalid)?;
+        let number_start = proposal
+            .find(|character: char| !character.is_ascii_alphabetic())
+            .ok_or_else(invalid)?;
+        let (proposal_code, proposal_number) = proposal.split_at(number_start);
+        if proposal_code.is_empty()
+            || proposal_number.is_empty()
+            || !proposal_number
+                .chars()
+                .all(|character| character.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            proposal_code: proposal_code.to_string(),
+            proposal_number: proposal_number.to_string(),
+            visit_number: visit_number.parse().map_err(|_| invalid())?,
+        })
+    }
+}
@@ -3,36 +3,101 @@
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
+/// Logging of each HTTP request
+mod access_log;
+/// Administrative HTTP endpoints
+mod admin;
 /// Metadata about the crate, courtesy of [`built`]
 mod built_info;
+/// S3 credentials read from files
+mod credentials;
+/// Reporting of the configuration resolved from arguments and environment variables
+mod effective_config;
+/// Example GraphQL documents
+mod examples;
 /// GraphQL resolvers
 mod graphql;
-/// An [`axum::handler::Handler`] for GraphQL
+/// Presigning of S3 object URLs
+mod presigner;
+/// Readiness of the service to receive traffic
+mod readiness;
+/// Fixed headers added to every response
+mod response_headers;
+/// [`axum::handler::Handler`]s for GraphQL and the schema
 mod route_handlers;
 
-use async_graphql::{http::GraphiQLSource, SDLExportOptions};
+use access_log::with_access_log;
+use admin::{admin_router, LogLevelHandle};
+use async_graphql::{dataloader::DataLoader, http::GraphiQLSource};
+use aws_config::default_provider::credentials::DefaultCredentialsChain;
 use aws_credential_types::{provider::SharedCredentialsProvider, Credentials};
-use aws_sdk_s3::{config::Region, Client};
-use axum::{response::Html, routing::get, Router};
+use aws_sdk_s3::{
+    config::Region,
+    types::{BucketLocationConstraint, BucketVersioningStatus},
+    Client,
+};
+use axum::{
+    response::Html,
+    routing::{get, post},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
 use axum_tracing_opentelemetry::middleware::{OtelAxumLayer, OtelInResponseLayer};
-use clap::{ArgAction::SetTrue, Parser};
+use clap::{
+    error::ErrorKind, ArgAction::SetTrue, ArgMatches, CommandFactory, FromArgMatches, Parser,
+    ValueEnum,
+};
+use credentials::FileCredentialsProvider;
 use derive_more::{Deref, FromStr, Into};
-use graphql::{root_schema_builder, RootSchema};
-use opentelemetry_otlp::WithExportConfig;
-use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr, TransactionError};
+use effective_config::effective_config;
+use examples::{validate_examples, ValidationOnly, EXAMPLES, EXAMPLE_VARIABLES};
+use graphql::{
+    root_schema_builder, schema_sdl, spawn_notification_source, AnomalousSignificanceThreshold,
+    AttachmentFileType, AttachmentPollInterval, BucketVersioned, CircuitBreaker, DependencyChecks,
+    DownloadableFileTypes, EffectiveResolutionExponent, ErrorDetail, Feature, FeatureSet,
+    FederationEntities, FederationEntity, GradeThresholds, LoaderSettings, MaintenanceMode,
+    MaxPageSize, MaxPresignsPerRequest, MaxSubscriptionsPerConnection, ObjectKeyRewrite,
+    ObjectMetadataDataLoader, PresignDataLoader, PresignExpiryBounds, ProcessingMessageMaxLength,
+    ProcessingStatusCodes, ProcessingStatusEvents, QualityGradeThresholds, RateLimiter, RootSchema,
+    S3ConcurrencyLimit, S3SoftFail, ShareTokenSigner, StatsPrecision, METRICS_TARGET,
+};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{MetricsExporterBuilder, SpanExporterBuilder, WithExportConfig};
+use opentelemetry_sdk::metrics::{
+    reader::{DefaultAggregationSelector, DefaultTemporalitySelector},
+    PeriodicReader, SdkMeterProvider,
+};
+use presigner::{PresignExpiry, SharedPresigner, UnsignedObjectUrls};
+use readiness::{probe_readiness, ready, Readiness};
+use response_headers::{with_response_headers, ResponseHeader};
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseBackend, DatabaseConnection, DbErr,
+    TransactionError,
+};
 use std::{
     fs::File,
     io::Write,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     path::PathBuf,
+    sync::Arc,
     time::Duration,
 };
 use tokio::net::TcpListener;
-use tracing::{info, instrument};
-use tracing_subscriber::{filter::FilterFn, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing::{info, instrument, warn};
+use tracing_subscriber::{
+    filter::{FilterFn, Targets},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    Layer,
+};
 use url::Url;
 
-use crate::route_handlers::GraphQLHandler;
+use crate::route_handlers::{subscription_handler, GraphQLHandler, SchemaHandler};
 
 /// A service providing Beamline ISPyB data collected during sessions
 #[derive(Debug, Parser)]
@@ -43,6 +108,10 @@ enum Cli {
     Serve(ServeArgs),
     /// Produces the GraphQL schema
     Schema(SchemaArgs),
+    /// Prints example GraphQL documents, having validated them against the schema
+    Examples,
+    /// Prints the configuration with which the webserver would be started as JSON, with secrets redacted
+    Config(ServeArgs),
 }
 
 /// Arguments for serving the GraphQL API
@@ -51,29 +120,273 @@ struct ServeArgs {
     /// The port to which this application should bind
     #[arg(short, long, env = "PORT", default_value_t = 80)]
     port: u16,
+    /// A path prefix under which all routes are served, for ingresses which do not strip it, e.g. `/processed-data`
+    #[arg(long, env)]
+    base_path: Option<BasePath>,
+    /// Accepts cleartext HTTP/2 with prior knowledge alongside HTTP/1.1, subscriptions over the websocket route remain HTTP/1.1 only
+    #[arg(long, env, action = SetTrue)]
+    http2: bool,
+    /// The number of seconds an HTTP/1.1 connection may remain idle between requests before it is closed, connections are kept open indefinitely if unset. As the idle period is bounded together with the header read, the shorter of this and the header read timeout applies to both. Websocket subscriptions are not subject to this timeout
+    #[arg(long, env)]
+    http_keepalive_timeout: Option<u64>,
+    /// The number of seconds within which a client must send the headers of an HTTP/1.1 request, there is no limit if unset
+    #[arg(long, env)]
+    http_header_read_timeout: Option<u64>,
+    /// The path of a PEM encoded certificate chain, with which HTTPS is served in place of HTTP
+    #[arg(long, env, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+    /// The path of the PEM encoded private key of the TLS certificate
+    #[arg(long, env, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+    /// The number of worker threads used by the async runtime, defaults to the number of available cores
+    #[arg(long, env = "TOKIO_WORKER_THREADS")]
+    worker_threads: Option<usize>,
     /// The URL of the ISPyB instance which should be connected to
     #[arg(long, env = "DATABASE_URL")]
     database_url: Url,
+    /// The number of seconds to wait for a database connection to become available before failing the query
+    #[arg(long, env, default_value_t = 30)]
+    db_acquire_timeout: u64,
+    /// The number of consecutive failed database queries after which queries are suspended
+    #[arg(long, env, default_value_t = 5)]
+    db_circuit_failure_threshold: u32,
+    /// The number of seconds for which database queries are suspended before a single query is attempted
+    #[arg(long, env, default_value_t = 30)]
+    db_circuit_cooldown: u64,
     /// The S3 bucket which images are to be stored in.
     #[arg(long, env)]
     s3_bucket: S3Bucket,
     /// Configuration argument of the S3 client.
     #[command(flatten)]
     s3_client: S3ClientArgs,
+    /// Checks at startup that the S3 bucket is located in the configured region, failing if not. The check is skipped for custom endpoints and anonymous access
+    #[arg(long, env, action = SetTrue)]
+    s3_verify_on_start: bool,
+    /// The number of seconds for which presigned S3 URLs remain valid.
+    #[arg(long, env, default_value_t = 600)]
+    s3_presign_expiry: u64,
+    /// The number of seconds by which the start of presigned S3 URL validity is backdated, to accommodate clients with skewed clocks
+    #[arg(long, env, default_value_t = 5)]
+    presign_clock_skew: u64,
+    /// The minimum number of seconds for which clients may request presigned S3 URLs to remain valid, the maximum being the seven days permitted by S3
+    #[arg(long, env, default_value_t = 60)]
+    presign_min_expiry: u64,
+    /// The maximum number of S3 URLs presigned concurrently
+    #[arg(long, env, default_value_t = 16)]
+    presign_concurrency: usize,
+    /// The maximum number of requests for the metadata of S3 objects in flight at once
+    #[arg(long, env, default_value_t = 16)]
+    s3_metadata_concurrency: usize,
+    /// The maximum number of S3 operations, presigns and metadata requests alike, in flight at once across all requests, there is no limit if unset
+    #[arg(long, env)]
+    s3_max_concurrency: Option<usize>,
+    /// The number of milliseconds after which a check of a dependency by the readiness query is abandoned and the dependency reported down
+    #[arg(long, env, default_value_t = 2000)]
+    dependency_check_timeout: u64,
+    /// The number of milliseconds above which a dependency is reported degraded by the readiness query
+    #[arg(long, env, default_value_t = 500)]
+    dependency_degraded_latency: u64,
+    /// The data collection queried to check the service is ready, the check is skipped if unset
+    #[arg(long, env)]
+    readiness_data_collection_id: Option<u32>,
+    /// The number of significant figures to which scaling statistics are rounded, they are not rounded if unset
+    #[arg(long, env)]
+    stats_precision: Option<u32>,
+    /// The number of requests per second each client may make, clients are not rate limited if unset
+    #[arg(long, env)]
+    rate_limit: Option<f64>,
+    /// The number of requests each client may make in a burst exceeding the rate limit
+    #[arg(long, env, default_value_t = 20)]
+    rate_limit_burst: u32,
+    /// The maximum number of URLs which may be presigned in a single request
+    #[arg(long, env, default_value_t = 1000)]
+    max_presigns_per_request: usize,
+    /// The maximum number of items which may be requested in a single page of a paginated field
+    #[arg(long, env, default_value_t = MaxPageSize::default().0)]
+    max_page_size: u64,
+    /// The types of file attachment which may be downloaded without the internal attachment scope
+    #[arg(long, env, value_delimiter = ',', default_values = ["log", "result", "graph"])]
+    downloadable_file_types: Vec<AttachmentFileType>,
+    /// The processing program statuses which indicate that processing succeeded
+    #[arg(long, env, value_delimiter = ',', default_values = ["1"])]
+    processing_success_statuses: Vec<i8>,
+    /// The processing program statuses which indicate that processing failed
+    #[arg(long, env, value_delimiter = ',', default_values = ["0"])]
+    processing_failure_statuses: Vec<i8>,
+    /// The number of characters to which processing messages are truncated when no length is requested
+    #[arg(long, env, default_value_t = 200)]
+    processing_message_max_length: usize,
+    /// Thresholds by which the quality of processed data is graded.
+    #[command(flatten)]
+    quality_grade: QualityGradeArgs,
+    /// The anomalous CC, as a percentage, above which the anomalous signal of a shell is deemed significant
+    #[arg(long, env, default_value_t = AnomalousSignificanceThreshold::default().0)]
+    anomalous_cc_threshold: f32,
+    /// The exponent by which the effective resolution penalises incompleteness, 3 matching the growth of reflections with resolution
+    #[arg(long, env, default_value_t = EffectiveResolutionExponent::default().0)]
+    effective_resolution_exponent: f32,
+    /// The optional capabilities to enable, e.g. `explain,subscriptions`
+    #[arg(long, env, value_enum, value_delimiter = ',', default_values = ["subscriptions"])]
+    features: Vec<Feature>,
+    /// The entities resolved for the federation router, others being omitted from the schema served to it
+    #[arg(long, env, value_enum, value_delimiter = ',', default_values = ["data-collection"])]
+    federation_entities: Vec<FederationEntity>,
+    /// Reports failures to presign download links as S3 being unavailable, nulling only the link rather than surfacing the underlying error
+    #[arg(long, env, action = SetTrue)]
+    s3_soft_fail: bool,
+    /// The number of seconds between polls of the database by subscriptions, or by the processing status event source
+    #[arg(long, env, default_value_t = 5)]
+    attachment_poll_interval: u64,
+    /// A table, populated by triggers on `AutoProcProgram`, from which processing status changes are pushed to subscriptions, which poll if unset or unreadable
+    #[arg(long, env)]
+    status_notification_table: Option<String>,
+    /// The number of processing status changes buffered for each subscription before the oldest are skipped
+    #[arg(long, env, default_value_t = 256)]
+    status_event_capacity: usize,
+    /// The number of subscriptions which may be active on a single websocket connection, there is no limit if unset
+    #[arg(long, env)]
+    max_subscriptions_per_connection: Option<usize>,
+    /// A prefix removed from file system paths when mapping them to S3 object keys.
+    #[arg(long, env)]
+    s3_key_prefix_strip: Option<String>,
+    /// A prefix added to file system paths when mapping them to S3 object keys.
+    #[arg(long, env)]
+    s3_key_prefix_add: Option<String>,
     /// The [`tracing::Level`] to log at
     #[arg(long, env = "LOG_LEVEL", default_value_t = tracing::Level::INFO)]
     log_level: tracing::Level,
-    /// The URL of the OpenTelemetry collector to send traces to
-    #[arg(long, env = "OTEL_COLLECTOR_URL")]
-    otel_collector_url: Option<Url>,
+    /// The URLs of the OpenTelemetry collectors to send traces and metrics to, may be repeated
+    #[arg(long, env = "OTEL_COLLECTOR_URL", value_delimiter = ',')]
+    otel_collector_url: Vec<Url>,
+    /// The format in which trace context is propagated to and from other services
+    #[arg(long, env = "OTEL_PROPAGATOR", value_enum, default_value_t = OtelPropagator::TraceContext)]
+    otel_propagator: OtelPropagator,
+    /// Serves the schema without Apollo Federation support
+    #[arg(long, env, action = SetTrue)]
+    standalone: bool,
+    /// A header, of the form `name=value`, added to every response which does not already set it, may be repeated
+    #[arg(long, env)]
+    response_header: Vec<ResponseHeader>,
+    /// Serves GraphiQL from `/graphiql` rather than on GET from the GraphQL endpoint, which then accepts only POST
+    #[arg(long, env, action = SetTrue)]
+    separate_graphiql_path: bool,
+    /// Emits an access log line, with the method, path, status, duration and request ID, for each HTTP request
+    #[arg(long, env, action = SetTrue)]
+    access_log: bool,
+    /// The level at which access log lines are emitted
+    #[arg(long, env, default_value_t = tracing::Level::INFO)]
+    access_log_level: tracing::Level,
+    /// The duration, in milliseconds, after which a dataloader query is logged as slow
+    #[arg(long, env, default_value_t = 2000)]
+    slow_query_threshold: u64,
+    /// The maximum number of keys loaded by a single dataloader query, larger batches are split across several queries to keep `IN` clauses within database limits
+    #[arg(long, env, default_value_t = 1000)]
+    max_loader_batch_size: usize,
+    /// Starts the service rejecting all GraphQL operations
+    #[arg(long, env, action = SetTrue)]
+    maintenance_mode: bool,
+    /// The number of seconds clients are advised to wait before retrying during maintenance
+    #[arg(long, env, default_value_t = 60)]
+    maintenance_retry_after: u64,
+    /// The secret with which share tokens are signed, share tokens are disabled if unset
+    #[arg(long, env)]
+    share_secret: Option<ShareSecret>,
+    /// The number of seconds for which share tokens remain valid
+    #[arg(long, env, default_value_t = 30 * 24 * 60 * 60)]
+    share_token_ttl: u64,
+    /// The detail included in errors returned to clients, `minimal` replaces the messages of internal errors with a generic message
+    #[arg(long, env, value_enum, default_value_t = ErrorDetail::Full)]
+    error_detail: ErrorDetail,
+    /// The bearer token required to access the administrative endpoints, which are disabled if unset
+    #[arg(long, env)]
+    admin_token: Option<AdminToken>,
+    /// Prints the configuration as JSON, with secrets redacted, before starting the webserver
+    #[arg(long, env, action = SetTrue)]
+    print_config: bool,
+}
+
+/// Arguments for configuring the thresholds of the data quality grades, applied to the overall shell
+#[derive(Debug, Parser)]
+struct QualityGradeArgs {
+    /// The minimum completeness, as a percentage, for grade A
+    #[arg(long, env, default_value_t = QualityGradeThresholds::default().grade_a.min_completeness)]
+    grade_a_min_completeness: f32,
+    /// The maximum high resolution limit, in Angstroms, for grade A
+    #[arg(long, env, default_value_t = QualityGradeThresholds::default().grade_a.max_resolution)]
+    grade_a_max_resolution: f32,
+    /// The minimum CC(1/2), as a percentage, for grade A
+    #[arg(long, env, default_value_t = QualityGradeThresholds::default().grade_a.min_cc_half)]
+    grade_a_min_cc_half: f32,
+    /// The minimum completeness, as a percentage, for grade B
+    #[arg(long, env, default_value_t = QualityGradeThresholds::default().grade_b.min_completeness)]
+    grade_b_min_completeness: f32,
+    /// The maximum high resolution limit, in Angstroms, for grade B
+    #[arg(long, env, default_value_t = QualityGradeThresholds::default().grade_b.max_resolution)]
+    grade_b_max_resolution: f32,
+    /// The minimum CC(1/2), as a percentage, for grade B
+    #[arg(long, env, default_value_t = QualityGradeThresholds::default().grade_b.min_cc_half)]
+    grade_b_min_cc_half: f32,
+}
+
+impl From<QualityGradeArgs> for QualityGradeThresholds {
+    fn from(args: QualityGradeArgs) -> Self {
+        Self {
+            grade_a: GradeThresholds {
+                min_completeness: args.grade_a_min_completeness,
+                max_resolution: args.grade_a_max_resolution,
+                min_cc_half: args.grade_a_min_cc_half,
+            },
+            grade_b: GradeThresholds {
+                min_completeness: args.grade_b_min_completeness,
+                max_resolution: args.grade_b_max_resolution,
+                min_cc_half: args.grade_b_min_cc_half,
+            },
+        }
+    }
+}
+
+/// Trace context propagation formats
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OtelPropagator {
+    /// W3C Trace Context
+    #[value(name = "tracecontext")]
+    TraceContext,
+    /// Zipkin B3
+    B3,
+    /// Jaeger
+    Jaeger,
 }
 
 /// S3 bucket where the processed data is stored
 #[derive(Debug, Clone, Deref, FromStr, Into)]
 pub struct S3Bucket(String);
 
+/// Secret with which share tokens are signed
+#[derive(Debug, Clone, Deref, FromStr, Into)]
+pub struct ShareSecret(String);
+
+/// Bearer token granting access to the administrative endpoints
+#[derive(Debug, Clone, Deref, FromStr, Into)]
+pub struct AdminToken(String);
+
+/// A path prefix under which all routes are served, with a leading and without a trailing slash
+#[derive(Debug, Clone, Deref)]
+pub struct BasePath(String);
+
+impl std::str::FromStr for BasePath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return Err("Base path must not be empty".to_string());
+        }
+        Ok(Self(format!("/{path}")))
+    }
+}
+
 /// Arguments for configuring the S3 Client.
-#[derive(Debug, Parser)]
+#[derive(Debug, Clone, Parser)]
 pub struct S3ClientArgs {
     /// The URL of the S3 endpoint to retrieve images from.
     #[arg(long, env)]
@@ -84,42 +397,165 @@ pub struct S3ClientArgs {
     /// The secret access key used for S3 authorization.
     #[arg(long, env)]
     s3_secret_access_key: Option<String>,
+    /// A file containing the ID of the access key used for S3 authorization, re-read periodically so that rotated keys are picked up.
+    #[arg(long, env, requires = "s3_secret_access_key_file", conflicts_with_all = ["s3_access_key_id", "s3_secret_access_key"])]
+    s3_access_key_id_file: Option<PathBuf>,
+    /// A file containing the secret access key used for S3 authorization, re-read periodically so that rotated keys are picked up.
+    #[arg(long, env, requires = "s3_access_key_id_file", conflicts_with_all = ["s3_access_key_id", "s3_secret_access_key"])]
+    s3_secret_access_key_file: Option<PathBuf>,
+    /// The number of seconds after which the S3 credential files are re-read.
+    #[arg(long, env, default_value_t = 300)]
+    s3_credentials_refresh_interval: u64,
     /// Forces path style endpoint URIs for S3 queries.
     #[arg(long, env, action = SetTrue)]
     s3_force_path_style: bool,
     /// The AWS region of the S3 bucket.
     #[arg(long, env)]
     s3_region: Option<String>,
+    /// Uses the default AWS credentials provider chain (environment, web identity, instance metadata), as is the case when no access key is supplied. In-cluster deployments should use this with an IAM role bound to the service account.
+    #[arg(long, env, action = SetTrue, conflicts_with_all = ["s3_access_key_id", "s3_secret_access_key", "s3_access_key_id_file", "s3_secret_access_key_file", "s3_anonymous"])]
+    s3_use_default_credentials: bool,
+    /// Accesses a publicly readable bucket without credentials, serving unsigned object URLs.
+    #[arg(long, env, action = SetTrue, conflicts_with_all = ["s3_access_key_id", "s3_secret_access_key", "s3_access_key_id_file", "s3_secret_access_key_file"])]
+    s3_anonymous: bool,
+}
+
+/// The region assumed for custom S3 endpoints, which typically ignore it, when none is supplied
+const CUSTOM_ENDPOINT_DEFAULT_REGION: &str = "us-east-1";
+
+impl S3ClientArgs {
+    /// Returns true if the S3 endpoint is served by AWS, as is the case when no endpoint is supplied
+    fn is_aws_endpoint(&self) -> bool {
+        self.s3_endpoint_url.as_ref().map_or(true, |endpoint| {
+            endpoint.host_str().is_some_and(|host| {
+                host == "amazonaws.com"
+                    || host.ends_with(".amazonaws.com")
+                    || host.ends_with(".amazonaws.com.cn")
+            })
+        })
+    }
+
+    /// The region of the S3 bucket, defaulted for custom endpoints, or [`None`] if a region is required but absent
+    fn region(&self) -> Option<&str> {
+        self.s3_region
+            .as_deref()
+            .or((!self.is_aws_endpoint()).then_some(CUSTOM_ENDPOINT_DEFAULT_REGION))
+    }
 }
 
 /// S3 client argument trait
+#[async_trait::async_trait]
 pub trait FromS3ClientArgs {
     /// Creates a S3 [`Client`] with the supplied credentials using the supplied endpoint configuration.
-    fn from_s3_client_args(args: S3ClientArgs) -> Self;
+    async fn from_s3_client_args(args: S3ClientArgs) -> Self;
 }
 
+#[async_trait::async_trait]
 impl FromS3ClientArgs for Client {
-    fn from_s3_client_args(args: S3ClientArgs) -> Self {
-        let credentials = Credentials::new(
-            args.s3_access_key_id.unwrap_or_default(),
-            args.s3_secret_access_key.unwrap_or_default(),
-            None,
-            None,
-            "Other",
-        );
-        let credentials_provider = SharedCredentialsProvider::new(credentials);
+    async fn from_s3_client_args(args: S3ClientArgs) -> Self {
         let mut config_builder = aws_sdk_s3::config::Builder::new();
-        config_builder.set_credentials_provider(Some(credentials_provider));
+        let use_default_credentials = args.s3_use_default_credentials
+            || (args.s3_access_key_id.is_none()
+                && args.s3_secret_access_key.is_none()
+                && args.s3_access_key_id_file.is_none());
+        if args.s3_anonymous {
+            // Requests are unsigned, so no credentials are required
+        } else if let (Some(access_key_id_file), Some(secret_access_key_file)) =
+            (args.s3_access_key_id_file, args.s3_secret_access_key_file)
+        {
+            let credentials_provider = FileCredentialsProvider::new(
+                access_key_id_file,
+                secret_access_key_file,
+                Duration::from_secs(args.s3_credentials_refresh_interval),
+            );
+            config_builder.set_credentials_provider(Some(SharedCredentialsProvider::new(
+                credentials_provider,
+            )));
+        } else if use_default_credentials {
+            let credentials_provider = DefaultCredentialsChain::builder().build().await;
+            config_builder.set_credentials_provider(Some(SharedCredentialsProvider::new(
+                credentials_provider,
+            )));
+        } else {
+            let credentials = Credentials::new(
+                args.s3_access_key_id.unwrap_or_default(),
+                args.s3_secret_access_key.unwrap_or_default(),
+                None,
+                None,
+                "Other",
+            );
+            let credentials_provider = SharedCredentialsProvider::new(credentials);
+            config_builder.set_credentials_provider(Some(credentials_provider));
+        }
         config_builder.set_endpoint_url(args.s3_endpoint_url.map(String::from));
         config_builder.set_force_path_style(Some(args.s3_force_path_style));
-        config_builder.set_region(Some(Region::new(
-            args.s3_region.unwrap_or(String::from("undefined")),
-        )));
+        config_builder.set_region(args.region().map(|region| Region::new(region.to_string())));
         let config = config_builder.build();
         Client::from_conf(config)
     }
 }
 
+#[async_trait::async_trait]
+impl FromS3ClientArgs for UnsignedObjectUrls {
+    async fn from_s3_client_args(args: S3ClientArgs) -> Self {
+        let region = args
+            .region()
+            .unwrap_or(CUSTOM_ENDPOINT_DEFAULT_REGION)
+            .to_string();
+        let endpoint = args
+            .s3_endpoint_url
+            .unwrap_or_else(|| Url::parse(&format!("https://s3.{region}.amazonaws.com")).unwrap());
+        UnsignedObjectUrls::new(endpoint, args.s3_force_path_style)
+    }
+}
+
+#[async_trait::async_trait]
+impl FromS3ClientArgs for SharedPresigner {
+    async fn from_s3_client_args(args: S3ClientArgs) -> Self {
+        if args.s3_anonymous {
+            Arc::new(UnsignedObjectUrls::from_s3_client_args(args).await)
+        } else {
+            Arc::new(Client::from_s3_client_args(args).await)
+        }
+    }
+}
+
+/// The region of a bucket with the location constraint, which is absent for buckets in us-east-1 and is `EU` for legacy buckets in eu-west-1
+fn location_region(location: Option<&BucketLocationConstraint>) -> &str {
+    match location.map(BucketLocationConstraint::as_str) {
+        None | Some("") => "us-east-1",
+        Some("EU") => "eu-west-1",
+        Some(region) => region,
+    }
+}
+
+/// Checks that the S3 bucket is located in the configured region, as requests would otherwise be redirected
+#[instrument(skip(client))]
+async fn verify_bucket_region(
+    client: &Client,
+    bucket: &str,
+    region: &str,
+) -> Result<(), anyhow::Error> {
+    let location = client.get_bucket_location().bucket(bucket).send().await?;
+    let bucket_region = location_region(location.location_constraint());
+    if bucket_region != region {
+        return Err(anyhow::anyhow!(
+            "S3 bucket {bucket} is located in {bucket_region}, but the configured region is {region}"
+        ));
+    }
+    Ok(())
+}
+
+/// Returns true if the S3 bucket retains previous versions of its objects, which it continues to do whilst versioning is suspended
+#[instrument(skip(client))]
+async fn bucket_versioned(client: &Client, bucket: &str) -> Result<bool, anyhow::Error> {
+    let versioning = client.get_bucket_versioning().bucket(bucket).send().await?;
+    Ok(matches!(
+        versioning.status(),
+        Some(BucketVersioningStatus::Enabled | BucketVersioningStatus::Suspended)
+    ))
+}
+
 /// Arguments for produces the GraphQL schema
 #[derive(Debug, Parser)]
 struct SchemaArgs {
@@ -129,57 +565,230 @@ struct SchemaArgs {
     /// The URL of the ISPyB instance which should be connected to
     #[arg(long, env = "DATABASE_URL")]
     database_url: Url,
+    /// Produces the schema without Apollo Federation support
+    #[arg(long, env, action = SetTrue)]
+    standalone: bool,
+    /// The entities resolved for the federation router, others being omitted from the schema
+    #[arg(long, env, value_enum, value_delimiter = ',', default_values = ["data-collection"])]
+    federation_entities: Vec<FederationEntity>,
+}
+
+/// The database backends for which the hand-written queries are built
+const SUPPORTED_DATABASE_BACKENDS: &[DatabaseBackend] = &[DatabaseBackend::MySql];
+
+/// Returns an error naming the backend if the hand-written queries are not built for it
+fn check_database_backend(backend: DatabaseBackend) -> Result<(), DbErr> {
+    if SUPPORTED_DATABASE_BACKENDS.contains(&backend) {
+        Ok(())
+    } else {
+        Err(DbErr::Custom(format!(
+            "Unsupported database backend {backend:?}, expected one of {SUPPORTED_DATABASE_BACKENDS:?}"
+        )))
+    }
 }
 
-/// Creates a connection pool to access the database
+/// Creates a connection pool to access the database, refusing backends the queries are not built for
 #[instrument(skip(database_url))]
-async fn setup_database(database_url: Url) -> Result<DatabaseConnection, TransactionError<DbErr>> {
+async fn setup_database(
+    database_url: Url,
+    acquire_timeout: Duration,
+) -> Result<DatabaseConnection, TransactionError<DbErr>> {
     info!("Connecting to database at {database_url}");
     let connection_options = ConnectOptions::new(database_url.to_string())
         .sqlx_logging_level(tracing::log::LevelFilter::Debug)
+        .acquire_timeout(acquire_timeout)
         .to_owned();
     let connection = Database::connect(connection_options).await?;
+    check_database_backend(connection.get_database_backend())
+        .map_err(TransactionError::Connection)?;
     info!("Database connection established: {connection:?}");
     Ok(connection)
 }
 
 /// Creates an [`axum::Router`] serving GraphiQL, synchronous GraphQL and GraphQL subscriptions
-fn setup_router(schema: RootSchema, database: DatabaseConnection) -> Router {
+///
+/// GraphiQL is served on GET from the GraphQL endpoint, or from a path of its own if `separate_graphiql_path` is set,
+/// leaving the GraphQL endpoint to accept only POST.
+#[allow(clippy::too_many_arguments)]
+fn setup_router(
+    schema: RootSchema,
+    database: DatabaseConnection,
+    admin_router: Option<Router>,
+    loader_settings: LoaderSettings,
+    sdl: String,
+    readiness: Readiness,
+    base_path: Option<BasePath>,
+    separate_graphiql_path: bool,
+) -> Router {
     #[allow(clippy::missing_docs_in_private_items)]
     const GRAPHQL_ENDPOINT: &str = "/";
+    #[allow(clippy::missing_docs_in_private_items)]
+    const GRAPHIQL_ENDPOINT: &str = "/graphiql";
+    #[allow(clippy::missing_docs_in_private_items)]
+    const SCHEMA_ENDPOINT: &str = "/schema";
+    #[allow(clippy::missing_docs_in_private_items)]
+    const SUBSCRIPTION_ENDPOINT: &str = "/ws";
+    #[allow(clippy::missing_docs_in_private_items)]
+    const READY_ENDPOINT: &str = "/ready";
 
-    Router::new()
+    let prefix = base_path.as_deref().map(String::as_str).unwrap_or_default();
+    let graphiql_endpoint = match prefix {
+        "" => GRAPHQL_ENDPOINT.to_string(),
+        prefix => prefix.to_string(),
+    };
+    let graphiql = Html(
+        GraphiQLSource::build()
+            .endpoint(&graphiql_endpoint)
+            .subscription_endpoint(&format!("{prefix}{SUBSCRIPTION_ENDPOINT}"))
+            .finish(),
+    );
+    let graphql = GraphQLHandler::new(schema.clone(), database, loader_settings);
+    let router = if separate_graphiql_path {
+        Router::new()
+            .route(GRAPHQL_ENDPOINT, post(graphql))
+            .route(GRAPHIQL_ENDPOINT, get(graphiql))
+    } else {
+        Router::new().route(GRAPHQL_ENDPOINT, get(graphiql).post(graphql))
+    };
+    let mut router = router
+        .route(
+            SUBSCRIPTION_ENDPOINT,
+            get(subscription_handler).with_state(schema.clone()),
+        )
         .route(
-            GRAPHQL_ENDPOINT,
-            get(Html(
-                GraphiQLSource::build().endpoint(GRAPHQL_ENDPOINT).finish(),
-            ))
-            .post(GraphQLHandler::new(schema, database)),
+            SCHEMA_ENDPOINT,
+            get(SchemaHandler::new(schema.clone(), sdl)),
         )
+        .route(READY_ENDPOINT, get(ready).with_state(readiness));
+    if let Some(admin_router) = admin_router {
+        router = router.merge(admin_router);
+    }
+    if let Some(base_path) = base_path {
+        router = Router::new().nest(&base_path, router);
+    }
+    router
         .layer(OtelInResponseLayer)
         .layer(OtelAxumLayer::default())
 }
 
-/// Serves the endpoints on the specified port forever
-async fn serve(router: Router, port: u16) -> Result<(), std::io::Error> {
+/// Serves the endpoints on the specified port forever, over HTTPS if a TLS configuration is supplied and otherwise optionally accepting cleartext HTTP/2
+async fn serve(
+    router: Router,
+    port: u16,
+    http2: bool,
+    timeouts: ConnectionTimeouts,
+    tls_config: Option<RustlsConfig>,
+) -> Result<(), std::io::Error> {
     let socket_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
+    if let Some(tls_config) = tls_config {
+        println!("Serving API & GraphQL UI over TLS at {}", socket_addr);
+        let mut server = axum_server::bind_rustls(socket_addr, tls_config);
+        timeouts.apply(server.http_builder());
+        return server.serve(router.into_make_service()).await;
+    }
     let listener = TcpListener::bind(socket_addr).await?;
     println!("Serving API & GraphQL UI at {}", socket_addr);
-    axum::serve(listener, router.into_make_service()).await?;
+    if http2 || timeouts.header_read.is_some() || timeouts.keepalive.is_some() {
+        serve_connections(listener, router, http2, timeouts).await?;
+    } else {
+        axum::serve(listener, router.into_make_service()).await?;
+    }
     Ok(())
 }
 
-/// Sets up Logging & Tracing using opentelemetry if available
+/// The timeouts applied to each HTTP connection
+#[derive(Debug, Clone, Copy, Default)]
+struct ConnectionTimeouts {
+    /// The period an HTTP/1.1 connection may remain idle between requests before it is closed
+    keepalive: Option<Duration>,
+    /// The period within which a client must send the headers of an HTTP/1.1 request
+    header_read: Option<Duration>,
+}
+
+impl ConnectionTimeouts {
+    /// Applies the timeouts to the HTTP/1.1 connections served by the builder
+    ///
+    /// Hyper times the idle period of a kept-alive connection and the reading of the headers of its next request with a
+    /// single timer, so the shorter of the two timeouts is applied to both. Websocket connections leave hyper once
+    /// upgraded, so are never closed by either.
+    fn apply(&self, builder: &mut auto::Builder<TokioExecutor>) {
+        let header_read = match (self.keepalive, self.header_read) {
+            (Some(keepalive), Some(header_read)) => Some(keepalive.min(header_read)),
+            (keepalive, header_read) => keepalive.or(header_read),
+        };
+        if let Some(header_read) = header_read {
+            builder
+                .http1()
+                .timer(TokioTimer::new())
+                .header_read_timeout(header_read);
+        }
+    }
+}
+
+/// Serves the endpoints forever, speaking cleartext HTTP/2 with prior knowledge alongside HTTP/1.1 if enabled, and applying the timeouts to each connection
+async fn serve_connections(
+    listener: TcpListener,
+    router: Router,
+    http2: bool,
+    timeouts: ConnectionTimeouts,
+) -> Result<(), std::io::Error> {
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    if !http2 {
+        builder = builder.http1_only();
+    }
+    timeouts.apply(&mut builder);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let builder = builder.clone();
+        let service = TowerToHyperService::new(router.clone());
+        tokio::spawn(async move {
+            if let Err(err) = builder
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(error = %err, "Failed to serve connection");
+            }
+        });
+    }
+}
+
+/// Sets the format in which trace context is extracted from incoming requests and injected into outgoing requests
+fn set_text_map_propagator(otel_propagator: OtelPropagator) {
+    match otel_propagator {
+        OtelPropagator::TraceContext => opentelemetry::global::set_text_map_propagator(
+            opentelemetry_sdk::propagation::TraceContextPropagator::default(),
+        ),
+        OtelPropagator::B3 => {
+            opentelemetry::global::set_text_map_propagator(opentelemetry_zipkin::Propagator::new())
+        }
+        OtelPropagator::Jaeger => opentelemetry::global::set_text_map_propagator(
+            opentelemetry_jaeger_propagator::Propagator::new(),
+        ),
+    }
+}
+
+/// Sets up Logging & Tracing using opentelemetry if available, returning a handle with which the log level can be changed
+///
+/// Only the level of the logs written to stdout can be changed, exported spans remain filtered at the initial level. Only
+/// events targeting [`METRICS_TARGET`] reach the metrics layer, such that the callsites of dependencies are not enabled
+/// for its sake.
 fn setup_telemetry(
     log_level: tracing::Level,
-    otel_collector_url: Option<Url>,
-) -> Result<(), anyhow::Error> {
+    otel_collector_urls: Vec<Url>,
+    otel_propagator: OtelPropagator,
+) -> Result<LogLevelHandle, anyhow::Error> {
     let custom_filter = FilterFn::new(|metadata| {
         !metadata.target().contains("aws_smithy_runtime")
             && !metadata.target().contains("aws_credential_types")
     });
-    let level_filter = tracing_subscriber::filter::LevelFilter::from_level(log_level);
-    let log_layer = tracing_subscriber::fmt::layer();
+    let (level_filter, log_level_handle) = tracing_subscriber::reload::Layer::new(
+        tracing_subscriber::filter::LevelFilter::from_level(log_level),
+    );
+    let log_layer = tracing_subscriber::fmt::layer()
+        .with_filter(FilterFn::new(|metadata| {
+            metadata.target() != METRICS_TARGET
+        }))
+        .with_filter(level_filter);
     let service_name_resource = opentelemetry_sdk::Resource::new(vec![
         opentelemetry::KeyValue::new(
             opentelemetry_semantic_conventions::resource::SERVICE_NAME,
@@ -190,37 +799,50 @@ fn setup_telemetry(
             built_info::PKG_VERSION,
         ),
     ]);
-    let (metrics_layer, tracing_layer) = if let Some(otel_collector_url) = otel_collector_url {
-        opentelemetry::global::set_text_map_propagator(
-            opentelemetry_sdk::propagation::TraceContextPropagator::default(),
-        );
+    let (metrics_layer, tracing_layer) = if !otel_collector_urls.is_empty() {
+        set_text_map_propagator(otel_propagator);
+        let mut meter_provider_builder =
+            SdkMeterProvider::builder().with_resource(service_name_resource.clone());
+        let mut tracer_provider_builder = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_config(opentelemetry_sdk::trace::config().with_resource(service_name_resource));
+        for otel_collector_url in otel_collector_urls {
+            let metrics_exporter = MetricsExporterBuilder::from(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(otel_collector_url.clone()),
+            )
+            .build_metrics_exporter(
+                Box::new(DefaultTemporalitySelector::new()),
+                Box::new(DefaultAggregationSelector::new()),
+            )?;
+            meter_provider_builder = meter_provider_builder.with_reader(
+                PeriodicReader::builder(metrics_exporter, opentelemetry_sdk::runtime::Tokio)
+                    .with_interval(Duration::from_secs(10))
+                    .build(),
+            );
+            let span_exporter = SpanExporterBuilder::from(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(otel_collector_url),
+            )
+            .build_span_exporter()?;
+            tracer_provider_builder = tracer_provider_builder
+                .with_batch_exporter(span_exporter, opentelemetry_sdk::runtime::Tokio);
+        }
+        let tracer_provider = tracer_provider_builder.build();
+        let tracer = tracer_provider.tracer(built_info::PKG_NAME);
+        opentelemetry::global::set_tracer_provider(tracer_provider);
         (
-            Some(tracing_opentelemetry::MetricsLayer::new(
-                opentelemetry_otlp::new_pipeline()
-                    .metrics(opentelemetry_sdk::runtime::Tokio)
-                    .with_exporter(
-                        opentelemetry_otlp::new_exporter()
-                            .tonic()
-                            .with_endpoint(otel_collector_url.clone()),
-                    )
-                    .with_resource(service_name_resource.clone())
-                    .with_period(Duration::from_secs(10))
-                    .build()?,
-            )),
             Some(
-                tracing_opentelemetry::layer().with_tracer(
-                    opentelemetry_otlp::new_pipeline()
-                        .tracing()
-                        .with_exporter(
-                            opentelemetry_otlp::new_exporter()
-                                .tonic()
-                                .with_endpoint(otel_collector_url),
-                        )
-                        .with_trace_config(
-                            opentelemetry_sdk::trace::config().with_resource(service_name_resource),
-                        )
-                        .install_batch(opentelemetry_sdk::runtime::Tokio)?,
-                ),
+                tracing_opentelemetry::MetricsLayer::new(meter_provider_builder.build())
+                    .with_filter(Targets::new().with_target(METRICS_TARGET, tracing::Level::INFO)),
+            ),
+            Some(
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(tracing_subscriber::filter::LevelFilter::from_level(
+                        log_level,
+                    )),
             ),
         )
     } else {
@@ -228,36 +850,280 @@ fn setup_telemetry(
     };
 
     tracing_subscriber::Registry::default()
-        .with(custom_filter)
-        .with(level_filter)
         .with(log_layer)
+        .with(custom_filter)
         .with(metrics_layer)
         .with(tracing_layer)
         .init();
 
-    Ok(())
+    Ok(log_level_handle)
+}
+
+/// Prints the value and source of each argument of the selected subcommand as JSON, with secrets redacted
+fn print_config(matches: &ArgMatches) {
+    if let Some((name, subcommand_matches)) = matches.subcommand() {
+        let command = Cli::command();
+        let subcommand = command
+            .find_subcommand(name)
+            .expect("Matched subcommand is defined");
+        println!("{:#}", effective_config(subcommand, subcommand_matches));
+    }
 }
 
-#[tokio::main]
-async fn main() {
+fn main() {
     dotenvy::dotenv().ok();
-    let args = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let args = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    match &args {
+        Cli::Config(_) => {
+            print_config(&matches);
+            return;
+        }
+        Cli::Serve(ServeArgs {
+            print_config: true, ..
+        }) => print_config(&matches),
+        _ => {}
+    }
+    if let Cli::Serve(ServeArgs { s3_client, .. }) = &args {
+        if s3_client.region().is_none() {
+            Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "--s3-region is required when using an AWS S3 endpoint",
+                )
+                .exit()
+        }
+    }
 
+    let mut runtime_builder = tokio::runtime::Builder::new_multi_thread();
+    if let Cli::Serve(ServeArgs {
+        worker_threads: Some(worker_threads),
+        ..
+    }) = args
+    {
+        runtime_builder.worker_threads(worker_threads);
+    }
+    runtime_builder
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run(args))
+}
+
+/// Runs the selected subcommand to completion
+async fn run(args: Cli) {
     match args {
         Cli::Serve(args) => {
-            setup_telemetry(args.log_level, args.otel_collector_url).unwrap();
-            let database = setup_database(args.database_url).await.unwrap();
-            let s3_client = Client::from_s3_client_args(args.s3_client);
-            let schema = root_schema_builder()
-                .data(s3_client)
-                .data(args.s3_bucket)
-                .finish();
-            let router = setup_router(schema, database);
-            serve(router, args.port).await.unwrap();
+            let log_level = setup_telemetry(
+                args.log_level,
+                args.otel_collector_url,
+                args.otel_propagator,
+            )
+            .unwrap();
+            let tls_config = match (args.tls_cert, args.tls_key) {
+                (Some(tls_cert), Some(tls_key)) => Some(
+                    RustlsConfig::from_pem_file(tls_cert, tls_key)
+                        .await
+                        .unwrap(),
+                ),
+                _ => None,
+            };
+            let database = setup_database(
+                args.database_url,
+                Duration::from_secs(args.db_acquire_timeout),
+            )
+            .await
+            .unwrap();
+            let s3_client = Client::from_s3_client_args(args.s3_client.clone()).await;
+            if args.s3_verify_on_start
+                && args.s3_client.is_aws_endpoint()
+                && !args.s3_client.s3_anonymous
+            {
+                let region = args.s3_client.region().unwrap_or_default().to_string();
+                verify_bucket_region(&s3_client, &args.s3_bucket, &region)
+                    .await
+                    .unwrap();
+            }
+            let bucket_versioned = if args.s3_client.s3_anonymous {
+                None
+            } else {
+                match bucket_versioned(&s3_client, &args.s3_bucket).await {
+                    Ok(versioned) => Some(BucketVersioned(versioned)),
+                    Err(err) => {
+                        warn!("Versioning of the S3 bucket is unknown, links to versions of objects will not be checked: {err}");
+                        None
+                    }
+                }
+            };
+            let presigner = SharedPresigner::from_s3_client_args(args.s3_client).await;
+            let maintenance_mode = MaintenanceMode::new(
+                args.maintenance_mode,
+                Duration::from_secs(args.maintenance_retry_after),
+            );
+            let federation_entities = FederationEntities::new(args.federation_entities);
+            let presign_expiry = PresignExpiry::new(
+                Duration::from_secs(args.s3_presign_expiry),
+                Duration::from_secs(args.presign_clock_skew),
+            );
+            let status_events = match args.status_notification_table {
+                Some(table) => {
+                    let events = ProcessingStatusEvents::new(args.status_event_capacity);
+                    match spawn_notification_source(
+                        database.clone(),
+                        table,
+                        Duration::from_secs(args.attachment_poll_interval),
+                        events.clone(),
+                    )
+                    .await
+                    {
+                        Ok(()) => Some(events),
+                        Err(err) => {
+                            warn!("Processing status notifications unavailable, subscriptions will poll: {err}");
+                            None
+                        }
+                    }
+                }
+                None => None,
+            };
+            let s3_limit = S3ConcurrencyLimit::new(args.s3_max_concurrency);
+            let status_codes = ProcessingStatusCodes::new(
+                args.processing_success_statuses,
+                args.processing_failure_statuses,
+            );
+            let mut schema_builder = root_schema_builder(args.standalone)
+                .data(
+                    DataLoader::new(
+                        ObjectMetadataDataLoader::new(
+                            s3_client.clone(),
+                            args.s3_bucket.clone(),
+                            args.s3_metadata_concurrency,
+                            s3_limit.clone(),
+                        ),
+                        tokio::spawn,
+                    )
+                    .max_batch_size(args.max_loader_batch_size),
+                )
+                .data(DependencyChecks::new(
+                    s3_client,
+                    args.s3_bucket.clone(),
+                    Duration::from_millis(args.dependency_check_timeout),
+                    Duration::from_millis(args.dependency_degraded_latency),
+                ))
+                .data(
+                    DataLoader::new(
+                        PresignDataLoader::new(
+                            presigner,
+                            args.s3_bucket,
+                            presign_expiry,
+                            args.presign_concurrency,
+                            s3_limit,
+                        ),
+                        tokio::spawn,
+                    )
+                    .max_batch_size(args.max_loader_batch_size),
+                )
+                .data(PresignExpiryBounds {
+                    min: Duration::from_secs(args.presign_min_expiry),
+                    max: presign_expiry.max_expiry(),
+                })
+                .data(ObjectKeyRewrite::new(
+                    args.s3_key_prefix_strip,
+                    args.s3_key_prefix_add,
+                ))
+                .data(maintenance_mode.clone())
+                .data(database.clone())
+                .data(FeatureSet::new(args.features))
+                .data(federation_entities.clone())
+                .data(S3SoftFail(args.s3_soft_fail))
+                .data(StatsPrecision(args.stats_precision))
+                .data(args.error_detail)
+                .data(DownloadableFileTypes::new(args.downloadable_file_types))
+                .data(status_codes.clone())
+                .data(ProcessingMessageMaxLength(
+                    args.processing_message_max_length,
+                ))
+                .data(MaxPresignsPerRequest(args.max_presigns_per_request))
+                .data(MaxPageSize(args.max_page_size))
+                .data(QualityGradeThresholds::from(args.quality_grade))
+                .data(AnomalousSignificanceThreshold(args.anomalous_cc_threshold))
+                .data(EffectiveResolutionExponent(
+                    args.effective_resolution_exponent,
+                ))
+                .data(AttachmentPollInterval::new(Duration::from_secs(
+                    args.attachment_poll_interval,
+                )));
+            if let Some(status_events) = status_events {
+                schema_builder = schema_builder.data(status_events);
+            }
+            if let Some(bucket_versioned) = bucket_versioned {
+                schema_builder = schema_builder.data(bucket_versioned);
+            }
+            if let Some(max_subscriptions) = args.max_subscriptions_per_connection {
+                schema_builder =
+                    schema_builder.data(MaxSubscriptionsPerConnection(max_subscriptions));
+            }
+            if let Some(rate_limit) = args.rate_limit {
+                schema_builder =
+                    schema_builder.data(RateLimiter::new(rate_limit, args.rate_limit_burst));
+            }
+            if let Some(share_secret) = args.share_secret {
+                schema_builder = schema_builder.data(ShareTokenSigner::new(
+                    String::from(share_secret),
+                    Duration::from_secs(args.share_token_ttl),
+                ));
+            }
+            let schema = schema_builder.finish();
+            let loader_settings = LoaderSettings {
+                slow_query_threshold: Duration::from_millis(args.slow_query_threshold),
+                max_batch_size: args.max_loader_batch_size,
+                circuit_breaker: CircuitBreaker::new(
+                    args.db_circuit_failure_threshold,
+                    Duration::from_secs(args.db_circuit_cooldown),
+                ),
+                status_codes,
+            };
+            let readiness = Readiness::default();
+            tokio::spawn(probe_readiness(
+                schema.clone(),
+                database.clone(),
+                loader_settings.clone(),
+                args.readiness_data_collection_id,
+                args.standalone,
+                readiness.clone(),
+            ));
+            let sdl = schema_sdl(&schema, args.standalone, &federation_entities);
+            let router = setup_router(
+                schema,
+                database,
+                args.admin_token
+                    .map(|token| admin_router(token, maintenance_mode, log_level)),
+                loader_settings,
+                sdl,
+                readiness,
+                args.base_path,
+                args.separate_graphiql_path,
+            );
+            let router = with_response_headers(router, args.response_header);
+            let router = if args.access_log {
+                with_access_log(router, args.access_log_level)
+            } else {
+                router
+            };
+            let timeouts = ConnectionTimeouts {
+                keepalive: args.http_keepalive_timeout.map(Duration::from_secs),
+                header_read: args.http_header_read_timeout.map(Duration::from_secs),
+            };
+            serve(router, args.port, args.http2, timeouts, tls_config)
+                .await
+                .unwrap();
         }
         Cli::Schema(args) => {
-            let schema = root_schema_builder().finish();
-            let schema_string = schema.sdl_with_options(SDLExportOptions::new().federation());
+            let schema = root_schema_builder(args.standalone).finish();
+            let schema_string = schema_sdl(
+                &schema,
+                args.standalone,
+                &FederationEntities::new(args.federation_entities),
+            );
             if let Some(path) = args.path {
                 let mut file = File::create(path).unwrap();
                 file.write_all(schema_string.as_bytes()).unwrap();
@@ -265,5 +1131,526 @@ async fn main() {
                 println!("{}", schema_string)
             }
         }
+        Cli::Config(_) => {
+            unreachable!("The configuration is printed before the runtime is started")
+        }
+        Cli::Examples => {
+            let schema = root_schema_builder(false)
+                .extension(ValidationOnly)
+                .finish();
+            if let Err(failures) = validate_examples(&schema).await {
+                for (name, errors) in failures {
+                    eprintln!("Example {name} does not validate against the schema: {errors:?}");
+                }
+                std::process::exit(1);
+            }
+            println!("# Variables: {EXAMPLE_VARIABLES}");
+            for (name, document) in EXAMPLES {
+                println!("\n# {name}\n{document}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use opentelemetry::trace::{SpanId, SpanKind, TraceId};
+    use opentelemetry_sdk::{export::trace::SpanData, testing::trace::InMemorySpanExporter};
+    use tower::ServiceExt;
+
+    /// The server span recorded whilst handling a request carrying the header, with the propagator of the format
+    async fn request_span(
+        otel_propagator: OtelPropagator,
+        header: &str,
+        header_value: &str,
+    ) -> SpanData {
+        set_text_map_propagator(otel_propagator);
+        let exporter = InMemorySpanExporter::default();
+        let tracer_provider = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let router = Router::new()
+            .route("/", get(|| async {}))
+            .layer(OtelAxumLayer::default());
+
+        router
+            .oneshot(
+                Request::get("/")
+                    .header(header, header_value)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        tracer_provider.force_flush();
+        exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .find(|span| span.span_kind == SpanKind::Server)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn incoming_trace_context_is_the_parent_of_the_request_span() {
+        let trace_id = TraceId::from_hex("0af7651916cd43dd8448eb211c80319c").unwrap();
+        let parent_span_id = SpanId::from_hex("b7ad6b7169203331").unwrap();
+        for (otel_propagator, header, header_value) in [
+            (
+                OtelPropagator::TraceContext,
+                "traceparent",
+                "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            ),
+            (
+                OtelPropagator::B3,
+                "b3",
+                "0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-1",
+            ),
+            (
+                OtelPropagator::Jaeger,
+                "uber-trace-id",
+                "0af7651916cd43dd8448eb211c80319c:b7ad6b7169203331:0:1",
+            ),
+        ] {
+            let span = request_span(otel_propagator, header, header_value).await;
+            assert_eq!(span.span_context.trace_id(), trace_id, "{header}");
+            assert_eq!(span.parent_span_id, parent_span_id, "{header}");
+        }
+    }
+
+    /// The S3 client arguments parsed from the command line flags
+    fn s3_client_args(flags: &[&str]) -> S3ClientArgs {
+        S3ClientArgs::parse_from(std::iter::once("processed_data").chain(flags.iter().copied()))
+    }
+
+    #[test]
+    fn default_and_amazonaws_endpoints_are_aws() {
+        assert!(s3_client_args(&[]).is_aws_endpoint());
+        for endpoint in [
+            "https://amazonaws.com",
+            "https://s3.eu-west-2.amazonaws.com",
+            "https://bucket.s3.amazonaws.com",
+            "https://s3.cn-north-1.amazonaws.com.cn",
+        ] {
+            assert!(
+                s3_client_args(&["--s3-endpoint-url", endpoint]).is_aws_endpoint(),
+                "{endpoint}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_endpoints_are_custom() {
+        for endpoint in [
+            "https://s3.echo.stfc.ac.uk",
+            "http://localhost:9000",
+            "http://127.0.0.1:9000",
+            "https://notamazonaws.com",
+            "https://amazonaws.com.example.org",
+        ] {
+            assert!(
+                !s3_client_args(&["--s3-endpoint-url", endpoint]).is_aws_endpoint(),
+                "{endpoint}"
+            );
+        }
+    }
+
+    #[test]
+    fn region_is_required_for_aws_endpoints_only() {
+        assert_eq!(s3_client_args(&[]).region(), None);
+        assert_eq!(
+            s3_client_args(&["--s3-endpoint-url", "https://s3.eu-west-2.amazonaws.com"]).region(),
+            None
+        );
+        assert_eq!(
+            s3_client_args(&["--s3-endpoint-url", "https://s3.echo.stfc.ac.uk"]).region(),
+            Some(CUSTOM_ENDPOINT_DEFAULT_REGION)
+        );
+    }
+
+    #[test]
+    fn supplied_region_is_used_for_any_endpoint() {
+        assert_eq!(
+            s3_client_args(&["--s3-region", "eu-west-2"]).region(),
+            Some("eu-west-2")
+        );
+        assert_eq!(
+            s3_client_args(&[
+                "--s3-endpoint-url",
+                "https://s3.echo.stfc.ac.uk",
+                "--s3-region",
+                "uk-1"
+            ])
+            .region(),
+            Some("uk-1")
+        );
+    }
+
+    /// Serves a single route answering `ok` on an ephemeral local port, returning its address
+    async fn serve_locally(http2: bool) -> SocketAddr {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let socket_addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/", get(|| async { "ok" }));
+        tokio::spawn(serve_connections(
+            listener,
+            router,
+            http2,
+            ConnectionTimeouts::default(),
+        ));
+        socket_addr
+    }
+
+    /// A request for the route served by [`serve_locally`]
+    fn local_request() -> Request<Body> {
+        Request::get("http://localhost/")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    /// Sends a request over HTTP/2 with prior knowledge, returning the version of the response
+    async fn http2_version(socket_addr: SocketAddr) -> Result<axum::http::Version, hyper::Error> {
+        let stream = tokio::net::TcpStream::connect(socket_addr).await.unwrap();
+        let (mut sender, connection) =
+            hyper::client::conn::http2::handshake(TokioExecutor::new(), TokioIo::new(stream))
+                .await?;
+        tokio::spawn(connection);
+        Ok(sender.send_request(local_request()).await?.version())
+    }
+
+    /// Sends a request over HTTP/1.1, returning the version of the response
+    async fn http1_version(socket_addr: SocketAddr) -> Result<axum::http::Version, hyper::Error> {
+        let stream = tokio::net::TcpStream::connect(socket_addr).await.unwrap();
+        let (mut sender, connection) =
+            hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+        tokio::spawn(connection);
+        Ok(sender.send_request(local_request()).await?.version())
+    }
+
+    #[tokio::test]
+    async fn cleartext_http2_is_negotiated_with_prior_knowledge_when_enabled() {
+        let socket_addr = serve_locally(true).await;
+
+        assert_eq!(
+            http2_version(socket_addr).await.unwrap(),
+            axum::http::Version::HTTP_2
+        );
+        assert_eq!(
+            http1_version(socket_addr).await.unwrap(),
+            axum::http::Version::HTTP_11
+        );
+    }
+
+    #[tokio::test]
+    async fn cleartext_http2_is_refused_when_disabled() {
+        let socket_addr = serve_locally(false).await;
+
+        assert!(http2_version(socket_addr).await.is_err());
+        assert_eq!(
+            http1_version(socket_addr).await.unwrap(),
+            axum::http::Version::HTTP_11
+        );
+    }
+
+    #[test]
+    fn mysql_is_the_only_supported_database_backend() {
+        assert!(check_database_backend(DatabaseBackend::MySql).is_ok());
+        for backend in [DatabaseBackend::Postgres, DatabaseBackend::Sqlite] {
+            let err = check_database_backend(backend).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("Custom Error: Unsupported database backend {backend:?}, expected one of [MySql]")
+            );
+        }
+    }
+
+    /// A router serving a standalone schema under the base path, without a database
+    fn router(base_path: Option<&str>) -> Router {
+        router_with_graphiql_path(base_path, false)
+    }
+
+    /// A router serving a standalone schema under the base path, without a database, with GraphiQL on a separate path if set
+    fn router_with_graphiql_path(base_path: Option<&str>, separate_graphiql_path: bool) -> Router {
+        let schema = root_schema_builder(true).finish();
+        setup_router(
+            schema.clone(),
+            DatabaseConnection::Disconnected,
+            None,
+            LoaderSettings {
+                slow_query_threshold: Duration::from_secs(1),
+                max_batch_size: 100,
+                circuit_breaker: CircuitBreaker::new(u32::MAX, Duration::ZERO),
+                status_codes: ProcessingStatusCodes::default(),
+            },
+            schema.sdl(),
+            Readiness::default(),
+            base_path.map(|base_path| base_path.parse().unwrap()),
+            separate_graphiql_path,
+        )
+    }
+
+    /// Sends the request to the router, returning the status and body of the response
+    async fn send(router: Router, request: Request<Body>) -> (StatusCode, String) {
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    /// A GraphQL request for the type name of the query root, posted to the path
+    fn typename_request(path: &str) -> Request<Body> {
+        Request::post(path)
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"query":"{ __typename }"}"#))
+            .unwrap()
+    }
+
+    #[test]
+    fn base_path_is_normalised_to_a_leading_slash() {
+        for path in ["processed-data", "/processed-data", "/processed-data/"] {
+            assert_eq!(*path.parse::<BasePath>().unwrap(), "/processed-data");
+        }
+        assert!("/".parse::<BasePath>().is_err());
+    }
+
+    #[tokio::test]
+    async fn graphql_is_served_under_the_base_path() {
+        let (status, body) = send(
+            router(Some("/processed-data")),
+            typename_request("/processed-data"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["data"]["__typename"], "Query");
+
+        let (status, _) = send(
+            router(Some("/processed-data")),
+            Request::get("/processed-data/schema")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn routes_are_not_served_outside_the_base_path() {
+        let (status, _) = send(router(Some("/processed-data")), typename_request("/")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(router(None), typename_request("/")).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn graphiql_uses_the_prefixed_endpoints() {
+        let (status, body) = send(
+            router(Some("/processed-data")),
+            Request::get("/processed-data").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("/processed-data/ws"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn graphiql_is_served_on_get_from_the_graphql_endpoint_by_default() {
+        let (status, body) =
+            send(router(None), Request::get("/").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("graphiql"), "{body}");
+
+        let (status, _) = send(
+            router(None),
+            Request::get("/graphiql").body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn graphiql_is_served_from_a_separate_path_if_set() {
+        for (base_path, prefix) in [(None, ""), (Some("/processed-data"), "/processed-data")] {
+            let router = || router_with_graphiql_path(base_path, true);
+            let graphql_path = match prefix {
+                "" => "/",
+                prefix => prefix,
+            };
+
+            let (status, body) = send(
+                router(),
+                Request::get(format!("{prefix}/graphiql"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(body.contains("graphiql"), "{body}");
+            assert!(body.contains(&format!("{prefix}/ws")), "{body}");
+
+            let (status, _) = send(
+                router(),
+                Request::get(graphql_path).body(Body::empty()).unwrap(),
+            )
+            .await;
+            assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+            let (status, body) = send(router(), typename_request(graphql_path)).await;
+            assert_eq!(status, StatusCode::OK);
+            let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+            assert_eq!(body["data"]["__typename"], "Query");
+
+            let (status, _) = send(router(), typename_request(&format!("{prefix}/graphiql"))).await;
+            assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        }
+    }
+
+    #[test]
+    fn location_constraints_are_mapped_to_regions() {
+        assert_eq!(location_region(None), "us-east-1");
+        assert_eq!(
+            location_region(Some(&BucketLocationConstraint::from(""))),
+            "us-east-1"
+        );
+        assert_eq!(
+            location_region(Some(&BucketLocationConstraint::from("EU"))),
+            "eu-west-1"
+        );
+        assert_eq!(
+            location_region(Some(&BucketLocationConstraint::from("eu-west-2"))),
+            "eu-west-2"
+        );
+    }
+
+    /// A client of a local mock S3 endpoint, answering every request for a bucket with the XML document
+    async fn mock_bucket_client(document: String) -> Client {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let socket_addr = listener.local_addr().unwrap();
+        let router = Router::new().route(
+            "/:bucket",
+            get(move || async move { ([("content-type", "application/xml")], document) }),
+        );
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Client::from_s3_client_args(s3_client_args(&[
+            "--s3-endpoint-url",
+            &format!("http://{socket_addr}"),
+            "--s3-force-path-style",
+            "--s3-region",
+            "eu-west-2",
+            "--s3-access-key-id",
+            "key",
+            "--s3-secret-access-key",
+            "secret",
+        ]))
+        .await
+    }
+
+    /// A client of a local mock S3 endpoint, answering every bucket location request with the location constraint
+    async fn mock_location_client(location_constraint: &str) -> Client {
+        mock_bucket_client(format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><LocationConstraint xmlns="http://s3.amazonaws.com/doc/2006-03-01/">{location_constraint}</LocationConstraint>"#
+        ))
+        .await
+    }
+
+    /// A client of a local mock S3 endpoint, answering every bucket versioning request with the versioning configuration
+    async fn mock_versioning_client(configuration: &str) -> Client {
+        mock_bucket_client(format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><VersioningConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">{configuration}</VersioningConfiguration>"#
+        ))
+        .await
+    }
+
+    #[tokio::test]
+    async fn buckets_with_enabled_or_suspended_versioning_are_versioned() {
+        for (configuration, versioned) in [
+            ("<Status>Enabled</Status>", true),
+            ("<Status>Suspended</Status>", true),
+            ("", false),
+        ] {
+            let client = mock_versioning_client(configuration).await;
+            assert_eq!(
+                bucket_versioned(&client, "processed").await.unwrap(),
+                versioned,
+                "{configuration}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn bucket_in_the_configured_region_is_verified() {
+        let client = mock_location_client("eu-west-2").await;
+        verify_bucket_region(&client, "processed", "eu-west-2")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn bucket_in_another_region_is_rejected() {
+        let client = mock_location_client("eu-west-2").await;
+        let error = verify_bucket_region(&client, "processed", "us-east-1")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "S3 bucket processed is located in eu-west-2, but the configured region is us-east-1"
+        );
+    }
+
+    #[tokio::test]
+    async fn bucket_without_a_location_constraint_is_in_us_east_1() {
+        let client = mock_location_client("").await;
+        verify_bucket_region(&client, "processed", "us-east-1")
+            .await
+            .unwrap();
+        assert!(verify_bucket_region(&client, "processed", "eu-west-2")
+            .await
+            .is_err());
+    }
+
+    /// The features enabled by the flags of the serve subcommand
+    fn serve_features(flags: &[&str]) -> Result<Vec<Feature>, clap::Error> {
+        let args = [
+            "processed_data",
+            "serve",
+            "--database-url",
+            "mysql://localhost/ispyb",
+            "--s3-bucket",
+            "processed",
+        ];
+        match Cli::try_parse_from(args.iter().chain(flags))? {
+            Cli::Serve(args) => Ok(args.features),
+            cli => panic!("Unexpected subcommand {cli:?}"),
+        }
+    }
+
+    #[test]
+    fn features_are_parsed_from_a_comma_separated_list() {
+        assert_eq!(serve_features(&[]).unwrap(), vec![Feature::Subscriptions]);
+        assert_eq!(
+            serve_features(&["--features", "explain,subscriptions"]).unwrap(),
+            vec![Feature::Explain, Feature::Subscriptions]
+        );
+        assert_eq!(
+            serve_features(&["--features", "explain"]).unwrap(),
+            vec![Feature::Explain]
+        );
+        assert_eq!(
+            serve_features(&["--features", "explain,uploads"])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidValue
+        );
     }
 }
@@ -0,0 +1,248 @@
+use aws_sdk_s3::{
+    presigning::{PresigningConfig, PresigningConfigError},
+    Client,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use url::Url;
+
+/// A boxed future returning a presigned URL
+pub type PresignFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Url, async_graphql::Error>> + Send + 'a>>;
+
+/// A thread-safe, shareable [`Presigner`], injected into the schema as data
+pub type SharedPresigner = Arc<dyn Presigner>;
+
+/// The longest period for which S3 permits presigned URLs to remain valid
+const MAX_PRESIGN_VALIDITY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The period for which presigned URLs remain valid, and the allowance made for clients with skewed clocks
+#[derive(Debug, Clone, Copy)]
+pub struct PresignExpiry {
+    /// The period, from the time of signing, for which presigned URLs remain valid
+    expiry: Duration,
+    /// The period by which the start of validity is backdated
+    clock_skew: Duration,
+}
+
+impl PresignExpiry {
+    /// Creates an expiry of the supplied duration, with validity starting `clock_skew` before the time of signing
+    pub fn new(expiry: Duration, clock_skew: Duration) -> Self {
+        Self { expiry, clock_skew }
+    }
+
+    /// The period, from the time of signing, for which presigned URLs remain valid
+    pub fn expiry(&self) -> Duration {
+        self.expiry
+    }
+
+    /// Creates an expiry with the same allowance for skewed clocks, but of the supplied duration
+    pub fn with_expiry(&self, expiry: Duration) -> Self {
+        Self { expiry, ..*self }
+    }
+
+    /// The longest expiry which S3 permits, as validity is extended by the allowance for skewed clocks
+    pub fn max_expiry(&self) -> Duration {
+        MAX_PRESIGN_VALIDITY.saturating_sub(self.clock_skew)
+    }
+
+    /// Configures a URL signed at `now` to become valid `clock_skew` earlier, whilst still expiring `expiry` after `now`
+    fn presigning_config(
+        &self,
+        now: SystemTime,
+    ) -> Result<PresigningConfig, PresigningConfigError> {
+        PresigningConfig::builder()
+            .start_time(now - self.clock_skew)
+            .expires_in(self.expiry + self.clock_skew)
+            .build()
+    }
+}
+
+/// Produces time-limited URLs granting access to objects in a bucket
+pub trait Presigner: Send + Sync {
+    /// Creates a URL which allows the object at `key` in `bucket` to be downloaded, served with `content_type` and `content_disposition` if supplied, until `expiry` has elapsed
+    ///
+    /// If a `range` is supplied, the URL is only valid for requests sending it as their `Range` header
+    #[allow(clippy::too_many_arguments)]
+    fn presign_get_object<'a>(
+        &'a self,
+        bucket: &'a str,
+        key: &'a str,
+        version_id: Option<String>,
+        content_type: Option<String>,
+        content_disposition: Option<String>,
+        range: Option<String>,
+        expiry: PresignExpiry,
+    ) -> PresignFuture<'a>;
+}
+
+impl Presigner for Client {
+    fn presign_get_object<'a>(
+        &'a self,
+        bucket: &'a str,
+        key: &'a str,
+        version_id: Option<String>,
+        content_type: Option<String>,
+        content_disposition: Option<String>,
+        range: Option<String>,
+        expiry: PresignExpiry,
+    ) -> PresignFuture<'a> {
+        Box::pin(async move {
+            let object_uri = self
+                .get_object()
+                .bucket(bucket)
+                .key(key)
+                .set_version_id(version_id)
+                .set_response_content_type(content_type)
+                .set_response_content_disposition(content_disposition)
+                .set_range(range)
+                .presigned(expiry.presigning_config(SystemTime::now())?)
+                .await?
+                .uri()
+                .clone();
+            Ok(Url::parse(&object_uri.to_string())?)
+        })
+    }
+}
+
+/// Produces direct, unsigned URLs to objects in a publicly readable bucket
+#[derive(Debug, Clone)]
+pub struct UnsignedObjectUrls {
+    /// The URL of the S3 endpoint which serves the objects
+    endpoint: Url,
+    /// Whether the bucket is addressed in the path rather than the host
+    force_path_style: bool,
+}
+
+impl UnsignedObjectUrls {
+    /// Creates a producer of object URLs against the supplied endpoint
+    pub fn new(endpoint: Url, force_path_style: bool) -> Self {
+        Self {
+            endpoint,
+            force_path_style,
+        }
+    }
+
+    /// Builds the URL of the object at `key` in `bucket`
+    fn object_url(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<String>,
+    ) -> Result<Url, async_graphql::Error> {
+        let mut url = self.endpoint.clone();
+        if !self.force_path_style {
+            let host = format!(
+                "{bucket}.{}",
+                url.host_str()
+                    .ok_or_else(|| async_graphql::Error::new("S3 endpoint URL has no host"))?
+            );
+            url.set_host(Some(&host))?;
+        }
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .map_err(|_| async_graphql::Error::new("S3 endpoint URL cannot be a base"))?;
+            segments.pop_if_empty();
+            if self.force_path_style {
+                segments.push(bucket);
+            }
+            segments.extend(key.split('/'));
+        }
+        if let Some(version_id) = version_id {
+            url.query_pairs_mut().append_pair("versionId", &version_id);
+        }
+        Ok(url)
+    }
+}
+
+impl Presigner for UnsignedObjectUrls {
+    fn presign_get_object<'a>(
+        &'a self,
+        bucket: &'a str,
+        key: &'a str,
+        version_id: Option<String>,
+        _content_type: Option<String>,
+        _content_disposition: Option<String>,
+        _range: Option<String>,
+        _expiry: PresignExpiry,
+    ) -> PresignFuture<'a> {
+        Box::pin(async move { self.object_url(bucket, key, version_id) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_credential_types::Credentials;
+    use aws_sdk_s3::config::Region;
+
+    #[test]
+    fn validity_is_backdated_by_the_clock_skew() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let expiry = PresignExpiry::new(Duration::from_secs(3600), Duration::from_secs(5));
+
+        let config = expiry.presigning_config(now).unwrap();
+
+        assert_eq!(config.start_time(), now - Duration::from_secs(5));
+        assert_eq!(config.expires(), Duration::from_secs(3605));
+        assert_eq!(
+            config.start_time() + config.expires(),
+            now + Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn validity_starts_at_signing_without_clock_skew() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let expiry = PresignExpiry::new(Duration::from_secs(60), Duration::ZERO);
+
+        let config = expiry.presigning_config(now).unwrap();
+
+        assert_eq!(config.start_time(), now);
+        assert_eq!(config.expires(), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn ranges_are_signed_as_a_header() {
+        let client = Client::from_conf(
+            aws_sdk_s3::config::Builder::new()
+                .credentials_provider(Credentials::new("key", "secret", None, None, "test"))
+                .endpoint_url("http://s3.test")
+                .force_path_style(true)
+                .region(Region::new("us-east-1"))
+                .build(),
+        );
+        let expiry = PresignExpiry::new(Duration::from_secs(60), Duration::ZERO);
+        let signed_headers = |url: Url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "X-Amz-SignedHeaders")
+                .map(|(_, headers)| headers.split(';').map(str::to_string).collect::<Vec<_>>())
+                .unwrap()
+        };
+
+        let ranged = client
+            .presign_get_object(
+                "processed",
+                "xia2.txt",
+                None,
+                None,
+                None,
+                Some("bytes=0-1023".to_string()),
+                expiry,
+            )
+            .await
+            .unwrap();
+        assert!(signed_headers(ranged).contains(&"range".to_string()));
+
+        let whole = client
+            .presign_get_object("processed", "xia2.txt", None, None, None, None, expiry)
+            .await
+            .unwrap();
+        assert!(!signed_headers(whole).contains(&"range".to_string()));
+    }
+}
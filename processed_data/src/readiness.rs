@@ -0,0 +1,80 @@
+use async_graphql::Executor;
+use axum::{extract::State, http::StatusCode};
+use sea_orm::DatabaseConnection;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tracing::{info, warn};
+
+use crate::graphql::{AddDataLoadersExt, LoaderSettings};
+
+/// A shared flag indicating whether the service is ready to receive traffic
+#[derive(Debug, Clone, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    /// Returns true if the service is ready to receive traffic
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Marks the service as ready to receive traffic
+    fn set_ready(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+}
+
+/// Responds with [`StatusCode::OK`] once the service is ready, otherwise [`StatusCode::SERVICE_UNAVAILABLE`]
+pub async fn ready(State(readiness): State<Readiness>) -> StatusCode {
+    if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+/// Runs a representative query for the data collection through the schema, marking the service ready only if it succeeds
+///
+/// The probe is skipped, and the service marked ready immediately, if no data collection is configured or the schema is
+/// not federated, as the data collection is only reachable through the federation entity resolver.
+pub async fn probe_readiness(
+    executor: impl Executor,
+    database: DatabaseConnection,
+    loader_settings: LoaderSettings,
+    data_collection_id: Option<u32>,
+    standalone: bool,
+    readiness: Readiness,
+) {
+    let Some(data_collection_id) = data_collection_id else {
+        readiness.set_ready();
+        return;
+    };
+    if standalone {
+        warn!("Skipping readiness probe as federation is disabled");
+        readiness.set_ready();
+        return;
+    }
+    let query = format!(
+        r#"{{
+            _entities(representations: [{{ __typename: "DataCollection", id: {data_collection_id} }}]) {{
+                ... on DataCollection {{
+                    processingJobs {{ processingJobId }}
+                    autoProcessing {{ autoProcIntegrationId overall {{ autoProcScalingStatisticsId }} }}
+                }}
+            }}
+        }}"#
+    );
+    let response = executor
+        .execute(async_graphql::Request::new(query).add_data_loaders(database, loader_settings))
+        .await;
+    if response.is_ok() {
+        info!("Readiness probe of data collection {data_collection_id} succeeded");
+        readiness.set_ready();
+    } else {
+        warn!(
+            "Readiness probe of data collection {data_collection_id} failed: {:?}",
+            response.errors
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    http::{HeaderName, HeaderValue},
+    Router,
+};
+use std::str::FromStr;
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// A header, of the form `name=value`, added to every response
+#[derive(Debug, Clone)]
+pub struct ResponseHeader {
+    /// The name of the header
+    name: HeaderName,
+    /// The value of the header
+    value: HeaderValue,
+}
+
+impl FromStr for ResponseHeader {
+    type Err = String;
+
+    fn from_str(header: &str) -> Result<Self, Self::Err> {
+        let (name, value) = header
+            .split_once('=')
+            .ok_or_else(|| format!("Response header {header} is not of the form name=value"))?;
+        Ok(Self {
+            name: HeaderName::from_str(name.trim())
+                .map_err(|err| format!("Invalid response header name {name}: {err}"))?,
+            value: HeaderValue::from_str(value.trim())
+                .map_err(|err| format!("Invalid value for response header {name}: {err}"))?,
+        })
+    }
+}
+
+/// Adds the headers to every response, including errors, unless the response already carries a header of the same name
+pub fn with_response_headers(router: Router, headers: Vec<ResponseHeader>) -> Router {
+    headers.into_iter().fold(router, |router, header| {
+        router.layer(SetResponseHeaderLayer::if_not_present(
+            header.name,
+            header.value,
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{header::CACHE_CONTROL, Request, StatusCode},
+        response::IntoResponse,
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    /// The response of the router, with the configured headers, to a request for the path
+    async fn response(path: &str, headers: &[&str]) -> axum::response::Response {
+        let router = Router::new().route("/", get(|| async { "ok" })).route(
+            "/cached",
+            get(|| async { ([(CACHE_CONTROL, "max-age=60")], "ok").into_response() }),
+        );
+        let headers = headers
+            .iter()
+            .map(|header| header.parse().unwrap())
+            .collect();
+        with_response_headers(router, headers)
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn headers_are_parsed_from_name_value_pairs() {
+        let header = " X-Frame-Options = DENY "
+            .parse::<ResponseHeader>()
+            .unwrap();
+        assert_eq!(header.name, "x-frame-options");
+        assert_eq!(header.value, "DENY");
+
+        let header = "Content-Security-Policy=default-src 'self'; img-src *"
+            .parse::<ResponseHeader>()
+            .unwrap();
+        assert_eq!(header.name, "content-security-policy");
+        assert_eq!(header.value, "default-src 'self'; img-src *");
+    }
+
+    #[test]
+    fn malformed_headers_are_rejected() {
+        assert!("X-Frame-Options".parse::<ResponseHeader>().is_err());
+        assert!("X Frame=DENY".parse::<ResponseHeader>().is_err());
+        assert!("=DENY".parse::<ResponseHeader>().is_err());
+        assert!("X-Frame-Options=DE\nNY".parse::<ResponseHeader>().is_err());
+    }
+
+    #[tokio::test]
+    async fn configured_headers_are_added_to_every_response() {
+        let headers = ["X-Frame-Options=DENY", "Cache-Control=no-store"];
+
+        let ok = response("/", &headers).await;
+        assert_eq!(ok.status(), StatusCode::OK);
+        assert_eq!(ok.headers()["x-frame-options"], "DENY");
+        assert_eq!(ok.headers()[CACHE_CONTROL], "no-store");
+
+        let not_found = response("/missing", &headers).await;
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+        assert_eq!(not_found.headers()["x-frame-options"], "DENY");
+        assert_eq!(not_found.headers()[CACHE_CONTROL], "no-store");
+    }
+
+    #[tokio::test]
+    async fn headers_set_by_handlers_are_not_replaced() {
+        let cached = response(
+            "/cached",
+            &["X-Frame-Options=DENY", "Cache-Control=no-store"],
+        )
+        .await;
+
+        assert_eq!(cached.headers()["x-frame-options"], "DENY");
+        assert_eq!(
+            cached
+                .headers()
+                .get_all(CACHE_CONTROL)
+                .iter()
+                .collect::<Vec<_>>(),
+            ["max-age=60"]
+        );
+    }
+}
@@ -1,16 +1,25 @@
-use async_graphql::Executor;
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql::{Data, Executor};
+use async_graphql_axum::{
+    GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket, ALL_WEBSOCKET_PROTOCOLS,
+};
 use axum::{
-    extract::Request,
+    extract::{ws::WebSocketUpgrade, Request, State},
     handler::Handler,
-    http::StatusCode,
+    http::{
+        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+        HeaderValue, StatusCode,
+    },
     response::{IntoResponse, Response},
-    RequestExt,
+    Json, RequestExt,
 };
 use sea_orm::DatabaseConnection;
-use std::{future::Future, pin::Pin};
+use std::{future::Future, pin::Pin, sync::Arc};
 
-use crate::graphql::AddDataLoadersExt;
+use crate::graphql::{
+    AddDataLoadersExt, ConnectionSubscriptions, GrantedScopes, IncludeQueryComplexity,
+    IncludeTiming, LoaderSettings, RateLimitKey, RootSchema, GRANTED_SCOPES_HEADER,
+    INCLUDE_QUERY_COMPLEXITY_HEADER, INCLUDE_TIMING_HEADER, INTROSPECTION_QUERY,
+};
 
 /// An [`Handler`] which executes an [`Executor`] including the [`Authorization<Bearer>`] in the [`async_graphql::Context`]
 #[derive(Debug, Clone)]
@@ -19,12 +28,18 @@ pub struct GraphQLHandler<E: Executor> {
     executor: E,
     /// Database connection
     database: DatabaseConnection,
+    /// The settings applied to the dataloaders of each request
+    loader_settings: LoaderSettings,
 }
 
 impl<E: Executor> GraphQLHandler<E> {
     /// Constructs an instance of the handler with the provided schema.
-    pub fn new(executor: E, database: DatabaseConnection) -> Self {
-        Self { executor, database }
+    pub fn new(executor: E, database: DatabaseConnection, loader_settings: LoaderSettings) -> Self {
+        Self {
+            executor,
+            database,
+            loader_settings,
+        }
     }
 }
 
@@ -36,16 +51,407 @@ where
 
     fn call(self, req: Request, _state: S) -> Self::Future {
         Box::pin(async move {
+            let granted_scopes = req
+                .headers()
+                .get(GRANTED_SCOPES_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(GrantedScopes::from_header)
+                .unwrap_or_default();
+            let rate_limit_key = RateLimitKey::from_bearer(
+                req.headers()
+                    .get(AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer ")),
+            );
+            let accepts_msgpack = accepts_msgpack(req.headers().get(ACCEPT));
+            let include_query_complexity = req
+                .headers()
+                .get(INCLUDE_QUERY_COMPLEXITY_HEADER)
+                .is_some_and(|value| value == "true");
+            let include_timing = req
+                .headers()
+                .get(INCLUDE_TIMING_HEADER)
+                .is_some_and(|value| value == "true");
             let request = req.extract::<GraphQLRequest, _>().await;
             match request {
-                Ok(request) => GraphQLResponse::from(
+                Ok(request) => {
+                    let mut request = request
+                        .into_inner()
+                        .add_data_loaders(self.database, self.loader_settings)
+                        .data(granted_scopes)
+                        .data(rate_limit_key);
+                    if include_query_complexity {
+                        request = request.data(IncludeQueryComplexity);
+                    }
+                    if include_timing {
+                        request = request.data(IncludeTiming);
+                    }
+                    let response = self.executor.execute(request).await;
+                    if accepts_msgpack {
+                        msgpack_response(response)
+                    } else {
+                        GraphQLResponse::from(response).into_response()
+                    }
+                }
+                Err(err) => (StatusCode::BAD_REQUEST, err.0.to_string()).into_response(),
+            }
+        })
+    }
+}
+
+/// Serves GraphQL subscriptions over a websocket, counting the subscriptions active on each connection
+pub async fn subscription_handler(
+    State(schema): State<RootSchema>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            let mut data = Data::default();
+            data.insert(ConnectionSubscriptions::default());
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .with_data(data)
+                .serve()
+        })
+}
+
+/// The media type of MessagePack encoded responses
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Returns true if the client explicitly accepts MessagePack encoded responses
+fn accepts_msgpack(accept: Option<&HeaderValue>) -> bool {
+    accept
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| {
+            accept
+                .split(',')
+                .map(|media_range| media_range.split(';').next().unwrap_or_default().trim())
+                .any(|media_type| media_type == MSGPACK_CONTENT_TYPE)
+        })
+}
+
+/// Encodes the GraphQL response as MessagePack, including any HTTP headers set during execution
+fn msgpack_response(response: async_graphql::Response) -> Response {
+    match rmp_serde::to_vec_named(&response) {
+        Ok(body) => {
+            let mut http_response = ([(CONTENT_TYPE, MSGPACK_CONTENT_TYPE)], body).into_response();
+            http_response
+                .headers_mut()
+                .extend(response.http_headers.clone());
+            http_response
+        }
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+/// An [`Handler`] which serves the schema as SDL or introspection JSON, according to the [`ACCEPT`] header
+#[derive(Debug, Clone)]
+pub struct SchemaHandler<E: Executor> {
+    /// The GraphQL executor used to process introspection requests
+    executor: E,
+    /// The SDL of the schema
+    sdl: Arc<str>,
+}
+
+impl<E: Executor> SchemaHandler<E> {
+    /// Constructs an instance of the handler with the provided schema and its SDL.
+    pub fn new(executor: E, sdl: String) -> Self {
+        Self {
+            executor,
+            sdl: sdl.into(),
+        }
+    }
+}
+
+/// The representations in which the schema can be served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SchemaFormat {
+    /// The schema definition language, served with the given content type
+    Sdl(&'static str),
+    /// The JSON result of an introspection query
+    Introspection,
+}
+
+/// Selects the first representation of the schema acceptable to the client, defaulting to SDL
+fn negotiate_schema_format(accept: Option<&HeaderValue>) -> Option<SchemaFormat> {
+    let Some(accept) = accept
+        .and_then(|accept| accept.to_str().ok())
+        .filter(|accept| !accept.trim().is_empty())
+    else {
+        return Some(SchemaFormat::Sdl("text/plain; charset=utf-8"));
+    };
+    accept
+        .split(',')
+        .map(|media_range| media_range.split(';').next().unwrap_or_default().trim())
+        .find_map(|media_type| match media_type {
+            "text/plain" | "text/*" | "*/*" => Some(SchemaFormat::Sdl("text/plain; charset=utf-8")),
+            "application/graphql" => Some(SchemaFormat::Sdl("application/graphql")),
+            "application/json" => Some(SchemaFormat::Introspection),
+            _ => None,
+        })
+}
+
+impl<S, E> Handler<((),), S> for SchemaHandler<E>
+where
+    E: Executor,
+{
+    type Future = Pin<Box<dyn Future<Output = Response> + Send + 'static>>;
+
+    fn call(self, req: Request, _state: S) -> Self::Future {
+        Box::pin(async move {
+            match negotiate_schema_format(req.headers().get(ACCEPT)) {
+                Some(SchemaFormat::Sdl(content_type)) => {
+                    ([(CONTENT_TYPE, content_type)], self.sdl.to_string()).into_response()
+                }
+                Some(SchemaFormat::Introspection) => Json(
                     self.executor
-                        .execute(request.into_inner().add_data_loaders(self.database))
+                        .execute(async_graphql::Request::new(INTROSPECTION_QUERY))
                         .await,
                 )
                 .into_response(),
-                Err(err) => (StatusCode::BAD_REQUEST, err.0.to_string()).into_response(),
+                None => StatusCode::NOT_ACCEPTABLE.into_response(),
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::{root_schema_builder, CircuitBreaker, ProcessingStatusCodes};
+    use axum::{
+        body::Body,
+        routing::{get, post},
+        Router,
+    };
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    /// The negotiated format of the schema for the value of the [`ACCEPT`] header
+    fn format(accept: &str) -> Option<SchemaFormat> {
+        negotiate_schema_format(Some(&HeaderValue::from_str(accept).unwrap()))
+    }
+
+    #[test]
+    fn sdl_is_served_without_a_preference() {
+        assert_eq!(
+            negotiate_schema_format(None),
+            Some(SchemaFormat::Sdl("text/plain; charset=utf-8"))
+        );
+        assert_eq!(
+            format(" "),
+            Some(SchemaFormat::Sdl("text/plain; charset=utf-8"))
+        );
+        assert_eq!(
+            format("*/*"),
+            Some(SchemaFormat::Sdl("text/plain; charset=utf-8"))
+        );
+    }
+
+    #[test]
+    fn each_accepted_media_type_selects_its_format() {
+        assert_eq!(
+            format("text/plain"),
+            Some(SchemaFormat::Sdl("text/plain; charset=utf-8"))
+        );
+        assert_eq!(
+            format("text/*"),
+            Some(SchemaFormat::Sdl("text/plain; charset=utf-8"))
+        );
+        assert_eq!(
+            format("application/graphql"),
+            Some(SchemaFormat::Sdl("application/graphql"))
+        );
+        assert_eq!(
+            format("application/json"),
+            Some(SchemaFormat::Introspection)
+        );
+    }
+
+    #[test]
+    fn first_acceptable_media_type_is_selected() {
+        assert_eq!(
+            format("image/png, application/json;q=0.9, text/plain"),
+            Some(SchemaFormat::Introspection)
+        );
+    }
+
+    #[test]
+    fn unacceptable_media_types_select_no_format() {
+        assert_eq!(format("image/png"), None);
+        assert_eq!(format("application/xml, text/html"), None);
+    }
+
+    /// The response of the schema route to a request with the [`ACCEPT`] header, if supplied
+    async fn schema_response(accept: Option<&str>) -> Response {
+        let schema = root_schema_builder(true).finish();
+        let router = Router::new().route(
+            "/schema",
+            get(SchemaHandler::new(schema, "type Query".to_string())),
+        );
+        let mut request = axum::http::Request::get("/schema");
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    /// The body of the response, as a string
+    async fn body(response: Response) -> String {
+        String::from_utf8(
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap()
+                .to_vec(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sdl_is_served_as_plain_text_or_graphql() {
+        let response = schema_response(None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[CONTENT_TYPE],
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(body(response).await, "type Query");
+
+        let response = schema_response(Some("application/graphql")).await;
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/graphql");
+        assert_eq!(body(response).await, "type Query");
+    }
+
+    #[tokio::test]
+    async fn introspection_is_served_as_json() {
+        let response = schema_response(Some("application/json")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let introspection =
+            serde_json::from_str::<serde_json::Value>(&body(response).await).unwrap();
+        assert_eq!(
+            introspection["data"]["__schema"]["queryType"]["name"],
+            "Query"
+        );
+    }
+
+    #[tokio::test]
+    async fn unacceptable_media_type_is_refused() {
+        let response = schema_response(Some("image/png")).await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn msgpack_is_accepted_only_when_listed() {
+        let accepts = |accept: &str| accepts_msgpack(Some(&HeaderValue::from_str(accept).unwrap()));
+        assert!(!accepts_msgpack(None));
+        assert!(accepts("application/msgpack"));
+        assert!(accepts("application/json;q=0.5, application/msgpack;q=0.9"));
+        assert!(!accepts("application/json"));
+        assert!(!accepts("*/*"));
+    }
+
+    /// The response of the GraphQL route to the query, sent with the [`ACCEPT`] header
+    async fn graphql_response(query: &str, accept: &str) -> Response {
+        graphql_response_with_headers(query, &[(ACCEPT.as_str(), accept)]).await
+    }
+
+    /// The response of the GraphQL route to the query, sent as JSON with the additional headers
+    async fn graphql_response_with_headers(query: &str, headers: &[(&str, &str)]) -> Response {
+        let handler = GraphQLHandler::new(
+            root_schema_builder(true).finish(),
+            DatabaseConnection::Disconnected,
+            LoaderSettings {
+                slow_query_threshold: Duration::from_secs(1),
+                max_batch_size: 100,
+                circuit_breaker: CircuitBreaker::new(u32::MAX, Duration::ZERO),
+                status_codes: ProcessingStatusCodes::default(),
+            },
+        );
+        let router = Router::new().route("/", post(handler));
+        let mut request = axum::http::Request::post("/").header(CONTENT_TYPE, "application/json");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        router
+            .oneshot(
+                request
+                    .body(Body::from(
+                        serde_json::json!({ "query": query }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn response_round_trips_through_msgpack() {
+        let query = "{ __typename unknownField }";
+        let json = serde_json::from_str::<serde_json::Value>(
+            &body(graphql_response(query, "application/json").await).await,
+        )
+        .unwrap();
+
+        let response = graphql_response(query, MSGPACK_CONTENT_TYPE).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let decoded = rmp_serde::from_slice::<serde_json::Value>(&bytes).unwrap();
+
+        assert_eq!(decoded, json);
+        assert!(!decoded["errors"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn data_round_trips_through_msgpack() {
+        let response = graphql_response("{ __typename }", MSGPACK_CONTENT_TYPE).await;
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            rmp_serde::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            serde_json::json!({ "data": { "__typename": "Query" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn timing_is_included_only_when_requested() {
+        for (headers, included) in [
+            (vec![], false),
+            (vec![(INCLUDE_TIMING_HEADER, "false")], false),
+            (vec![(INCLUDE_TIMING_HEADER, "true")], true),
+        ] {
+            let response = graphql_response_with_headers("{ __typename }", &headers).await;
+            let response =
+                serde_json::from_str::<serde_json::Value>(&body(response).await).unwrap();
+
+            assert_eq!(
+                response["data"],
+                serde_json::json!({ "__typename": "Query" })
+            );
+            if included {
+                assert!(
+                    response["extensions"]["timing"]["totalMs"]
+                        .as_f64()
+                        .unwrap()
+                        >= 0.0,
+                    "{response}"
+                );
+            } else {
+                assert_eq!(
+                    response["extensions"]["timing"],
+                    serde_json::Value::Null,
+                    "{response}"
+                );
+            }
+        }
+    }
+}
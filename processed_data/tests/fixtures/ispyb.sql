@@ -0,0 +1,55 @@
+-- A minimal set of ISPyB rows, loaded into an empty ispyb-database by the tests.
+-- The ids of each table are drawn from a distinct range, such that a lookup keyed on the wrong id finds nothing.
+-- In particular, the autoProcId and autoProcScalingId of each run differ, such that statistics keyed on the AutoProc are
+-- not found in place of those of the scaling.
+--
+-- Data collection 1 is processed by three programs: a successful xia2 dials run with all three shells, a failed
+-- fast_dp run without an outer shell and a manually triggered xia2 dials run which is still in progress.
+-- Data collection 2 is processed once, successfully, and data collection 3 has not been processed.
+-- The attachments of data collection 2 have names which are ordered differently by case insensitive collations.
+INSERT INTO Proposal (proposalId, proposalCode, proposalNumber) VALUES (1, 'cm', '12345');
+INSERT INTO BLSession (sessionId, proposalId, visit_number) VALUES (1, 1, 1);
+INSERT INTO DataCollectionGroup (dataCollectionGroupId, sessionId) VALUES (1, 1);
+INSERT INTO DataCollection (dataCollectionId, dataCollectionGroupId) VALUES (1, 1), (2, 1), (3, 1);
+INSERT INTO ProcessingJob (processingJobId, dataCollectionId, displayName, automatic) VALUES
+    (11, 1, 'xia2 dials', 1),
+    (12, 1, 'fast_dp', 1),
+    (13, 1, 'xia2 dials rerun', 0),
+    (14, 2, 'xia2 dials', 1);
+INSERT INTO ProcessingJobParameter (processingJobParameterId, processingJobId, parameterKey, parameterValue) VALUES
+    (21, 11, 'beamline', 'i03'),
+    (22, 11, 'ispyb_dcid', '1'),
+    (23, 13, 'd_min', '1.5');
+INSERT INTO AutoProcProgram (autoProcProgramId, processingPrograms, processingStatus, processingMessage, processingJobId) VALUES
+    (31, 'xia2 dials', 1, 'processing successful', 11),
+    (32, 'fast_dp', 0, 'indexing failed', 12),
+    (33, 'xia2 dials', NULL, NULL, 13),
+    (34, 'xia2 dials', 1, 'processing successful', 14);
+INSERT INTO AutoProcIntegration (autoProcIntegrationId, dataCollectionId, autoProcProgramId, refinedXBeam, refinedYBeam) VALUES
+    (41, 1, 31, 210.5, 220.5),
+    (42, 1, 32, 211.0, 221.0),
+    (43, 1, 33, NULL, NULL),
+    (44, 2, 34, 212.0, 222.0);
+INSERT INTO AutoProc (autoProcId, autoProcProgramId, spaceGroup, refinedCell_a, refinedCell_b, refinedCell_c, refinedCell_alpha, refinedCell_beta, refinedCell_gamma) VALUES
+    (51, 31, 'P 21 21 21', 78.1, 78.2, 37.5, 90, 90, 90),
+    (52, 32, 'P 1 21 1', 50.0, 60.0, 70.0, 90, 101.5, 90),
+    (54, 34, 'P 21 21 21', 78.0, 78.3, 37.4, 90, 90, 90);
+INSERT INTO AutoProcScaling (autoProcScalingId, autoProcId) VALUES (61, 51), (62, 52), (64, 54);
+INSERT INTO AutoProcScalingStatistics (autoProcScalingStatisticsId, autoProcScalingId, scalingStatisticsType, resolutionLimitLow, resolutionLimitHigh, rMerge, rMeasAllIPlusIMinus, nTotalObservations, nTotalUniqueObservations, meanIOverSigI, completeness, multiplicity, anomalousCompleteness, anomalousMultiplicity, ccHalf, ccAnomalous, resioversigi2) VALUES
+    (71, 61, 'overall', 55.2, 1.5, 0.05, 0.06, 100000, 25000, 12.0, 99.5, 4.0, 98.0, 2.1, 99.8, 35.0, 1.6),
+    (72, 61, 'innerShell', 55.2, 4.1, 0.03, 0.04, 5000, 1250, 40.0, 99.9, 4.0, 99.0, 2.1, 99.9, 60.0, NULL),
+    (73, 61, 'outerShell', 1.55, 1.5, 0.9, 1.1, 4000, 1100, 1.1, 95.0, 3.6, 90.0, 1.9, 45.0, 5.0, NULL),
+    (74, 62, 'overall', 60.0, 2.0, 0.08, 0.09, 2147483647, 1000000, 8.0, 80.0, 3.0, 70.0, 1.5, 90.0, 10.0, 2.1),
+    (75, 62, 'innerShell', 60.0, 5.0, 0.04, 0.05, 2147483647, NULL, 30.0, 85.0, 3.0, 75.0, 1.5, 99.0, 20.0, NULL),
+    (76, 64, 'overall', 50.0, 1.8, 0.06, 0.07, 80000, 20000, 10.0, 90.0, 4.0, 88.0, 2.0, 95.0, 15.0, 1.9);
+INSERT INTO AutoProcProgramAttachment (autoProcProgramAttachmentId, autoProcProgramId, fileType, fileName, filePath) VALUES
+    (81, 31, 'Log', 'xia2.txt', '/dls/i03/data/2024/cm12345-1/processed/xia2'),
+    (82, 31, 'Result', 'xia2.mtz', '/dls/i03/data/2024/cm12345-1/processed/xia2'),
+    (83, 31, 'Debug', 'xia2-debug.txt', '/dls/i03/data/2024/cm12345-1/processed/xia2'),
+    (84, 32, 'Graph', 'fast_dp.png', '/dls/i03/data/2024/cm12345-1/processed/fast_dp'),
+    (85, 34, 'Log', 'beta.txt', '/dls/i03/data/2024/cm12345-1/processed/xia2');
+INSERT INTO DataCollectionFileAttachment (dataCollectionFileAttachmentId, dataCollectionId, fileFullPath, fileType) VALUES
+    (91, 1, '/dls/i03/data/2024/cm12345-1/snapshot.png', 'snapshot'),
+    (92, 1, '/dls/i03/data/2024/cm12345-1/collection.log', 'log'),
+    (93, 2, '/dls/i03/data/2024/cm12345-1/Zinc.log', 'log'),
+    (94, 2, '/dls/i03/data/2024/cm12345-1/alpha.png', 'snapshot');